@@ -35,6 +35,7 @@ fn main() -> std::io::Result<()> {
         "cadical/src/stats.cpp",
         "cadical/src/message.cpp",
         "cadical/src/external.cpp",
+        "cadical/src/external_propagate.cpp",
         "cadical/src/profile.cpp",
         "cadical/src/terminal.cpp",
         "cadical/src/clause.cpp",
@@ -252,6 +252,22 @@ Internal::analyze_reason (int lit, Clause * reason, int & open) {
       analyze_literal (other, open);
 }
 
+// Records a reason clause resolved during conflict analysis or minimization
+// for exporting the derivation of the learned clause.  The pivot is zero for
+// the conflict clause.  Root level literals are skipped, since
+// 'analyze_literal' drops them from the learned clause too.
+
+void
+Internal::record_resolution_step (vector<int> & steps,
+                                  int pivot, Clause * reason) {
+  assert (reason);
+  steps.push_back (pivot);
+  for (const auto & lit : *reason)
+    if (lit == pivot || var (lit).level)
+      steps.push_back (lit);
+  steps.push_back (0);
+}
+
 /*------------------------------------------------------------------------*/
 
 // This is an idea which was implicit in MapleCOMSPS 2016 for 'limit = 1'.
@@ -287,7 +303,7 @@ inline void Internal::bump_also_reason_literals (int lit, int limit) {
   assert (val (lit));
   if (!v.level) return;
   Clause * reason = v.reason;
-  if (!reason) return;
+  if (!reason || reason == external_reason) return;
   for (const auto & other : *reason) {
     if (other == lit)  continue;
     if (!bump_also_reason_literal (other)) continue;
@@ -700,7 +716,11 @@ void Internal::analyze () {
   int open = 0;                 // Seen but not processed on this level.
   int uip = 0;                  // The first UIP literal.
 
+  resolution.clear ();
+  minimization.clear ();
+
   for (;;) {
+    if (external->learner) record_resolution_step (resolution, uip, reason);
     analyze_reason (uip, reason, open);
     uip = 0;
     while (!uip) {
@@ -711,6 +731,8 @@ void Internal::analyze () {
     }
     if (!--open) break;
     reason = var (uip).reason;
+    if (reason == external_reason)
+      reason = learn_external_reason_clause (uip);
     LOG (reason, "analyzing %d reason", uip);
   }
   LOG ("first UIP %d", uip);
@@ -108,6 +108,8 @@ void Internal::failing () {
         Var & v = var (lit);
         if (!v.level) continue;
 
+        if (v.reason == external_reason)
+          learn_external_reason_clause (val (lit) > 0 ? lit : -lit);
         if (v.reason) {
           assert (v.level);
           LOG (v.reason, "analyze reason");
@@ -117,9 +117,12 @@ void Internal::backtrack (int new_level) {
   if (propagated > assigned) propagated = assigned;
   if (propagated2 > assigned) propagated2 = assigned;
   if (no_conflict_until > assigned) no_conflict_until = assigned;
+  if (notified > assigned) notified = assigned;
 
   control.resize (new_level + 1);
   level = new_level;
+
+  notify_backtrack (new_level);
 }
 
 }
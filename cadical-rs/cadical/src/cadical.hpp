@@ -193,6 +193,7 @@ struct External;
 // Forward declaration of call-back classes. See bottom of this file.
 
 class Learner;
+class ExternalPropagator;
 class Terminator;
 class ClauseIterator;
 class WitnessIterator;
@@ -286,6 +287,43 @@ public:
 
   // ====== END IPASIR =====================================================
 
+  // ====== BEGIN IPASIR-UP ================================================
+
+  // Add call-back which observes the assignments of observed variables
+  // and can propagate, decide and add clauses during search (see
+  // 'ExternalPropagator' below).  There can only be one propagator
+  // connected.  While it is connected the solver neither backtracks
+  // chronologically nor searches lucky phases, local search,
+  // preprocessing and inprocessing, which all change the trail without
+  // the propagator taking part.
+  //
+  //   require (VALID)
+  //   ensure (VALID)
+  //
+  void connect_external_propagator (ExternalPropagator * propagator);
+  void disconnect_external_propagator ();
+
+  // Only assignments of observed variables are notified to the propagator
+  // and only those can be propagated or decided by it.  Observed variables
+  // are frozen until they are removed again.
+  //
+  //   require (VALID)
+  //   ensure (VALID)
+  //
+  void add_observed_var (int var);
+  void remove_observed_var (int var);
+  void reset_observed_vars ();
+
+  // Determine whether the valid non-zero literal is currently assigned as
+  // a decision.  Can also be called from within the propagator.
+  //
+  //   require (VALID | SOLVING)
+  //   ensure (VALID | SOLVING)
+  //
+  bool is_decision (int lit);
+
+  // ====== END IPASIR-UP ==================================================
+
   //------------------------------------------------------------------------
   // This function determines a good splitting literal.  The result can be
   // zero if the formula is proven to be satisfiable or unsatisfiable.  This
@@ -611,6 +649,16 @@ public:
   int64_t learned_literals ();
   int64_t decisions ();
   int64_t restarts ();
+  int64_t propagations ();
+
+  // Number of decisions made on the variable of the literal, and the
+  // activity of the variable in the current decision heuristic.  The
+  // activity is the VSIDS score in stable mode and the VMTF enqueue time
+  // stamp in focused mode, so only activities of the same solver and mode
+  // can be compared.  Variables without an internal variable have zero.
+  //
+  int64_t variable_decisions (int lit);
+  double variable_activity (int lit);
 
   //   require (VALID)
   //   ensure (VALID)
@@ -842,8 +890,53 @@ public:
   virtual bool learning (int size) = 0;
   virtual void learn (int lit) = 0;
 
-  // PAAVO:
-  virtual void learn_trail (unsigned long conflict_size, int* conflict_literals, unsigned long propagated_size, int* is_propagated, unsigned long size, int* trail) = 0;
+  // Called after the solver has restarted and backtracked the trail.
+  virtual void restart () { }
+
+  // Called when a reduction deletes learned clauses, with the literals of
+  // the deleted clauses each terminated by a zero.
+  virtual void reduce (unsigned long size, int* clauses) { }
+
+  // Called after 'learn' with the reason clauses resolved to derive
+  // the learned clause, each as the pivot literal (zero for the conflict
+  // clause) followed by the literals of the clause and a zero.  The first
+  // array derives the 1st UIP clause, the second one removes the literals
+  // dropped by minimization from it.
+  virtual void learn_resolution (unsigned long size, int* steps,
+                                 unsigned long minimization_size,
+                                 int* minimization) { }
+};
+
+// Connected external propagators are notified about assignments of
+// observed variables, new decision levels and backtracking.  Assignments
+// are notified lazily, but always before a new decision level is opened,
+// before the propagator is asked to propagate, decide or check a model and
+// before a conflict is analyzed.  A literal returned by 'cb_propagate' is
+// assigned without a reason, which is only asked for through
+// 'cb_add_reason_clause_lit' (literal by literal, terminated by zero) if
+// conflict analysis needs it.  The reason clause has to contain the
+// propagated literal and otherwise only falsified literals.  External
+// clauses are given literal by literal terminated by zero as long as
+// 'cb_has_external_clause' returns true.  If 'cb_check_found_model'
+// rejects a complete assignment it has to add a clause falsified by it.
+// A lazy propagator is only asked to check models.
+
+class ExternalPropagator {
+public:
+  bool is_lazy = false;
+  virtual ~ExternalPropagator () { }
+  virtual void notify_assignment (int lit, bool is_fixed) = 0;
+  virtual void notify_new_decision_level () = 0;
+  virtual void notify_backtrack (size_t new_level) = 0;
+  virtual bool cb_check_found_model (const std::vector<int> & model) = 0;
+  virtual int cb_decide () { return 0; }
+  virtual int cb_propagate () { return 0; }
+  virtual int cb_add_reason_clause_lit (int propagated_lit) {
+    (void) propagated_lit;
+    return 0;
+  }
+  virtual bool cb_has_external_clause () = 0;
+  virtual int cb_add_external_clause_lit () = 0;
 };
 
 /*------------------------------------------------------------------------*/
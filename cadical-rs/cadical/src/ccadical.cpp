@@ -1,4 +1,5 @@
 #include "cadical.hpp"
+#include "ccadical.h"
 
 #include <cstdint>
 #include <cstdlib>
@@ -19,10 +20,49 @@ struct Wrapper : Learner, Terminator {
     int max_length;
     int * begin_clause, * end_clause, * capacity_clause;
     void (*function) (void *, int *);
-    // PAAVO:
-    void (*trail_function) (void *, unsigned long, int *,  unsigned long, int *, unsigned long, int *);
+    void (*restart_function) (void *);
+    void (*resolution_function) (void *, unsigned long, int *, unsigned long, int *);
+    void (*reduce_function) (void *, unsigned long, int *);
   } learner;
 
+  struct Propagator : ExternalPropagator {
+    CCaDiCaLPropagator callbacks;
+
+    void notify_assignment (int lit, bool is_fixed) {
+      callbacks.notify_assignment (callbacks.state, lit, is_fixed);
+    }
+
+    void notify_new_decision_level () {
+      callbacks.notify_new_decision_level (callbacks.state);
+    }
+
+    void notify_backtrack (size_t new_level) {
+      callbacks.notify_backtrack (callbacks.state, new_level);
+    }
+
+    bool cb_check_found_model (const std::vector<int> & model) {
+      return callbacks.check_found_model (callbacks.state,
+                                          model.size (), model.data ());
+    }
+
+    int cb_decide () { return callbacks.decide (callbacks.state); }
+
+    int cb_propagate () { return callbacks.propagate (callbacks.state); }
+
+    int cb_add_reason_clause_lit (int propagated_lit) {
+      return callbacks.add_reason_clause_lit (callbacks.state,
+                                              propagated_lit);
+    }
+
+    bool cb_has_external_clause () {
+      return callbacks.has_external_clause (callbacks.state);
+    }
+
+    int cb_add_external_clause_lit () {
+      return callbacks.add_external_clause_lit (callbacks.state);
+    }
+  } propagator;
+
   bool terminate () {
     if (!terminator.function)
       return false;
@@ -51,9 +91,19 @@ struct Wrapper : Learner, Terminator {
     learner.end_clause = learner.begin_clause;
   }
 
-  // PAAVO:
-  void learn_trail (unsigned long conflict_size, int* conflict_literals, unsigned long propagated_size, int* is_propagated, unsigned long size, int* data) {
-    learner.trail_function (learner.state, conflict_size, conflict_literals, propagated_size, is_propagated, size, data);
+  void restart () {
+    if (learner.restart_function)
+      learner.restart_function (learner.state);
+  }
+
+  void learn_resolution (unsigned long size, int* steps, unsigned long minimization_size, int* minimization) {
+    if (learner.resolution_function)
+      learner.resolution_function (learner.state, size, steps, minimization_size, minimization);
+  }
+
+  void reduce (unsigned long size, int* clauses) {
+    if (learner.reduce_function)
+      learner.reduce_function (learner.state, size, clauses);
   }
 
   Wrapper () : solver (new Solver ()) {
@@ -165,11 +215,50 @@ void ccadical_set_learn (CCaDiCaL * ptr,
   else wrapper->solver->disconnect_learner ();
 }
 
-// PAAVO:
-void ccadical_set_learn_trail(CCaDiCaL * ptr, void *state, void (*trail)(void * state, unsigned long conflict_size, int * conflict_literals, unsigned long propagated_size, int* is_propagated, unsigned long size, int * trail)) {
+void ccadical_set_restart(CCaDiCaL * ptr, void *state, void (*restart)(void * state)) {
   Wrapper * wrapper = (Wrapper *) ptr;
   wrapper->learner.state = state;
-  wrapper->learner.trail_function = trail;
+  wrapper->learner.restart_function = restart;
+}
+
+void ccadical_set_learn_resolution(CCaDiCaL * ptr, void *state, void (*resolution)(void * state, unsigned long size, int * steps, unsigned long minimization_size, int * minimization)) {
+  Wrapper * wrapper = (Wrapper *) ptr;
+  wrapper->learner.state = state;
+  wrapper->learner.resolution_function = resolution;
+}
+
+void ccadical_set_reduce(CCaDiCaL * ptr, void *state, void (*reduce)(void * state, unsigned long size, int * clauses)) {
+  Wrapper * wrapper = (Wrapper *) ptr;
+  wrapper->learner.state = state;
+  wrapper->learner.reduce_function = reduce;
+}
+
+void ccadical_connect_external_propagator (CCaDiCaL * ptr,
+  const CCaDiCaLPropagator * propagator) {
+  Wrapper * wrapper = (Wrapper *) ptr;
+  wrapper->propagator.callbacks = *propagator;
+  wrapper->propagator.is_lazy = propagator->is_lazy;
+  wrapper->solver->connect_external_propagator (&wrapper->propagator);
+}
+
+void ccadical_disconnect_external_propagator (CCaDiCaL * wrapper) {
+  ((Wrapper*) wrapper)->solver->disconnect_external_propagator ();
+}
+
+void ccadical_add_observed_var (CCaDiCaL * wrapper, int var) {
+  ((Wrapper*) wrapper)->solver->add_observed_var (var);
+}
+
+void ccadical_remove_observed_var (CCaDiCaL * wrapper, int var) {
+  ((Wrapper*) wrapper)->solver->remove_observed_var (var);
+}
+
+void ccadical_reset_observed_vars (CCaDiCaL * wrapper) {
+  ((Wrapper*) wrapper)->solver->reset_observed_vars ();
+}
+
+int ccadical_is_decision (CCaDiCaL * wrapper, int lit) {
+  return ((Wrapper*) wrapper)->solver->is_decision (lit);
 }
 
 double ccadical_process_time(CCaDiCaL * wrapper) {
@@ -204,6 +293,18 @@ int64_t ccadical_restarts(CCaDiCaL * wrapper) {
     return ((Wrapper*) wrapper)->solver->restarts();
 }
 
+int64_t ccadical_propagations(CCaDiCaL * wrapper) {
+    return ((Wrapper*) wrapper)->solver->propagations();
+}
+
+int64_t ccadical_variable_decisions(CCaDiCaL * wrapper, int lit) {
+    return ((Wrapper*) wrapper)->solver->variable_decisions(lit);
+}
+
+double ccadical_variable_activity(CCaDiCaL * wrapper, int lit) {
+    return ((Wrapper*) wrapper)->solver->variable_activity(lit);
+}
+
 
 void ccadical_freeze (CCaDiCaL * ptr, int lit) {
   ((Wrapper*) ptr)->solver->freeze (lit);
@@ -30,9 +30,38 @@ void ccadical_set_terminate (CCaDiCaL *,
 void ccadical_set_learn (CCaDiCaL *,
   void * state, int max_length, void (*learn)(void * state, int * clause));
 
-// PAAVO:
-void ccadical_set_learn_trail (CCaDiCaL *,
-  void * state, void (*trail)(void * state, unsigned long conflict_size, int * conflict_literals, unsigned long propagated_size, int * is_propagated, unsigned long size, int * trail));
+void ccadical_set_restart (CCaDiCaL *,
+  void * state, void (*restart)(void * state));
+void ccadical_set_learn_resolution (CCaDiCaL *,
+  void * state, void (*resolution)(void * state, unsigned long size, int * steps,
+    unsigned long minimization_size, int * minimization));
+void ccadical_set_reduce (CCaDiCaL *,
+  void * state, void (*reduce)(void * state, unsigned long size, int * clauses));
+
+// Call-backs of an external propagator, see 'ExternalPropagator' in
+// 'cadical.hpp'.  Each is given the 'state' and booleans are integers.
+
+typedef struct CCaDiCaLPropagator {
+  void * state;
+  int is_lazy;
+  void (*notify_assignment) (void * state, int lit, int is_fixed);
+  void (*notify_new_decision_level) (void * state);
+  void (*notify_backtrack) (void * state, unsigned long new_level);
+  int (*check_found_model) (void * state, unsigned long size, const int * model);
+  int (*decide) (void * state);
+  int (*propagate) (void * state);
+  int (*add_reason_clause_lit) (void * state, int propagated_lit);
+  int (*has_external_clause) (void * state);
+  int (*add_external_clause_lit) (void * state);
+} CCaDiCaLPropagator;
+
+void ccadical_connect_external_propagator (CCaDiCaL *,
+  const CCaDiCaLPropagator * propagator);
+void ccadical_disconnect_external_propagator (CCaDiCaL *);
+void ccadical_add_observed_var (CCaDiCaL *, int var);
+void ccadical_remove_observed_var (CCaDiCaL *, int var);
+void ccadical_reset_observed_vars (CCaDiCaL *);
+int ccadical_is_decision (CCaDiCaL *, int lit);
 
 double ccadical_process_time (CCaDiCaL *);
 double ccadical_real_time (CCaDiCaL *);
@@ -42,6 +71,9 @@ int64_t ccadical_learned_clauses (CCaDiCaL *);
 int64_t ccadical_learned_literals (CCaDiCaL *);
 int64_t ccadical_decisions (CCaDiCaL *);
 int64_t ccadical_restarts(CCaDiCaL *);
+int64_t ccadical_propagations (CCaDiCaL *);
+int64_t ccadical_variable_decisions (CCaDiCaL *, int lit);
+double ccadical_variable_activity (CCaDiCaL *, int lit);
 /*------------------------------------------------------------------------*/
 
 // Non-IPASIR conformant 'C' functions.
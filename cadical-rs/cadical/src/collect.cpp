@@ -94,7 +94,7 @@ void Internal::protect_reasons () {
     Var & v = var (lit);
     assert (v.level > 0);
     Clause * reason = v.reason;
-    if (!reason) continue;
+    if (!reason || reason == external_reason) continue;
     LOG (reason, "protecting assigned %d reason %p", lit, reason);
     assert (!reason->reason);
     reason->reason = true;
@@ -119,7 +119,7 @@ void Internal::unprotect_reasons () {
     Var & v = var (lit);
     assert (v.level > 0);
     Clause * reason = v.reason;
-    if (!reason) continue;
+    if (!reason || reason == external_reason) continue;
     LOG (reason, "unprotecting assigned %d reason %p", lit, reason);
     assert (reason->reason);
     reason->reason = false;
@@ -206,7 +206,7 @@ void Internal::update_reason_references () {
     if (!active (lit)) continue;
     Var & v = var (lit);
     Clause * c = v.reason;
-    if (!c) continue;
+    if (!c || c == external_reason) continue;
     LOG (c, "updating assigned %d reason", lit);
     assert (c->reason);
     assert (c->moved);
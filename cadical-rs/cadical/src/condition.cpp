@@ -35,6 +35,7 @@ namespace CaDiCaL {
 bool Internal::conditioning () {
 
   if (!opts.condition) return false;
+  if (external_prop) return false;
   if (!preprocessing && !opts.inprocessing) return false;
   if (preprocessing) assert (lim.preprocessing);
 
@@ -90,6 +90,7 @@ int Internal::decide () {
       res = 20;
     } else if (tmp > 0) {
       LOG ("assumption %d already satisfied", lit);
+      notify_decision ();
       level++;
       control.push_back (Level (0, trail.size ()));
       LOG ("added pseudo decision level");
@@ -99,9 +100,13 @@ int Internal::decide () {
     }
   } else {
     stats.decisions++;
-    int idx = next_decision_variable ();
-    const bool target = opts.stabilizephase && stable;
-    int decision = decide_phase (idx, target);
+    int decision = ask_decision ();
+    if (!decision) {
+      int idx = next_decision_variable ();
+      const bool target = opts.stabilizephase && stable;
+      decision = decide_phase (idx, target);
+    }
+    external->count_decision (externalize (decision));
     search_assume_decision (decision);
   }
   STOP (decide);
@@ -55,6 +55,7 @@ inline bool elim_more::operator () (unsigned a, unsigned b) {
 bool Internal::eliminating () {
 
   if (!opts.elim) return false;
+  if (external_prop) return false;
   if (!preprocessing && !opts.inprocessing) return false;
   if (preprocessing) assert (lim.preprocessing);
 
@@ -10,6 +10,7 @@ External::External (Internal * i)
   extended (false),
   terminator (0),
   learner (0),
+  propagator (0),
   solution (0),
   vars (max_var)
 {
@@ -457,6 +458,64 @@ void External::copy_flags (External & other) const {
 
 /*------------------------------------------------------------------------*/
 
+void External::count_decision (int elit) {
+  const int eidx = abs (elit);
+  if ((size_t) eidx >= decision_counts.size ())
+    decision_counts.resize (eidx + 1, 0);
+  decision_counts[eidx]++;
+}
+
+int64_t External::decisions (int elit) const {
+  const int eidx = abs (elit);
+  if ((size_t) eidx >= decision_counts.size ()) return 0;
+  return decision_counts[eidx];
+}
+
+/*------------------------------------------------------------------------*/
+
+// Observed variables are frozen, so they are neither eliminated nor
+// substituted.  A variable which is already fixed and notified before it
+// became observed is notified right away.
+
+void External::add_observed_var (int elit) {
+  const int eidx = abs (elit);
+  if (observed (eidx)) return;
+  freeze (eidx);
+  if ((size_t) eidx >= is_observed.size ())
+    is_observed.resize (1 + (size_t) eidx, false);
+  is_observed[eidx] = true;
+  LOG ("observing external variable %d", eidx);
+  const int ilit = internalize (eidx);
+  const signed char tmp = internal->val (ilit);
+  if (!internal->external_prop || !tmp) return;
+  const Var & v = internal->var (ilit);
+  if (v.level || (size_t) v.trail >= internal->notified) return;
+  propagator->notify_assignment (tmp < 0 ? -eidx : eidx, true);
+}
+
+void External::remove_observed_var (int elit) {
+  const int eidx = abs (elit);
+  if (!observed (eidx)) return;
+  is_observed[eidx] = false;
+  melt (eidx);
+  LOG ("stopped observing external variable %d", eidx);
+}
+
+void External::reset_observed_vars () {
+  for (auto eidx : vars)
+    remove_observed_var (eidx);
+}
+
+bool External::is_decision (int elit) {
+  const int eidx = abs (elit);
+  if (eidx > max_var) return false;
+  const int ilit = e2i[eidx];
+  if (!ilit) return false;
+  return internal->is_decision (ilit);
+}
+
+/*------------------------------------------------------------------------*/
+
 void External::export_learned_empty_clause () {
   assert (learner);
   if (learner->learning (0)) {
@@ -474,24 +533,27 @@ void External::export_learned_unit_clause (int ilit) {
     assert (elit);
     learner->learn (elit);
     learner->learn (0);
+    export_learned_resolution ();
   } else
     LOG ("not exporting learned unit clause");
+}
 
-    //PAAVO:
-    // Could be in a function to remove duplicate code, but I don't think
-    // It matters / is worth the effort, since it's only two copies, and on the cadical side of the codebase
-    vector<int> trail_var_is_propagated;
-    trail_var_is_propagated.reserve(internal->trail.size());
-    for (int &literal : internal->trail) {
-        int idx = vidx(literal);
-        Var &v = internal->vtab[idx];
-        trail_var_is_propagated.push_back(v.reason != nullptr);
-    }
+// Exports the reason clauses resolved to derive the latest learned clause
+// in conflict analysis and in minimization, each as the pivot followed by
+// the literals of the reason and a zero.
 
-    learner->learn_trail(internal->conflict->size, internal->conflict->literals,
-                      trail_var_is_propagated.size(),
-                      trail_var_is_propagated.data(),
-                      internal->trail.size(), internal->trail.data());
+void External::export_learned_resolution () {
+  assert (learner);
+  vector<int> steps;
+  steps.reserve (internal->resolution.size ());
+  for (const auto & ilit : internal->resolution)
+    steps.push_back (ilit ? internal->externalize (ilit) : 0);
+  vector<int> minimization;
+  minimization.reserve (internal->minimization.size ());
+  for (const auto & ilit : internal->minimization)
+    minimization.push_back (ilit ? internal->externalize (ilit) : 0);
+  learner->learn_resolution (steps.size (), steps.data (),
+                             minimization.size (), minimization.data ());
 }
 
 void External::export_learned_large_clause (const vector<int> & clause) {
@@ -506,22 +568,7 @@ void External::export_learned_large_clause (const vector<int> & clause) {
       learner->learn (elit);
     }
     learner->learn (0);
-
-    //PAAVO:
-    // Could be in a function to remove duplicate code, but I don't think
-    // It matters / is worth the effort, since it's only two copies, and on the cadical side of the codebase
-    vector<int> trail_var_is_propagated;
-    trail_var_is_propagated.reserve(internal->trail.size());
-    for (int &literal : internal->trail) {
-        int idx = vidx(literal);
-        Var &v = internal->vtab[idx];
-        trail_var_is_propagated.push_back(v.reason != nullptr);
-    }
-
-    learner->learn_trail(internal->conflict->size, internal->conflict->literals,
-                      trail_var_is_propagated.size(),
-                      trail_var_is_propagated.data(),
-                      internal->trail.size(), internal->trail.data());
+    export_learned_resolution ();
 
   } else
     LOG ("not exporting learned clause of size %zu", size);
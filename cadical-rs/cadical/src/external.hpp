@@ -59,6 +59,7 @@ struct External {
 
   vector<bool> vals;          // Current external (extended) assignment.
   vector<int> e2i;            // External 'idx' to internal 'lit'.
+  vector<int64_t> decision_counts; // Decisions on each external 'idx'.
 
   vector<int> assumptions;    // External assumptions.
 
@@ -89,6 +90,23 @@ struct External {
   void export_learned_empty_clause ();
   void export_learned_unit_clause (int ilit);
   void export_learned_large_clause (const vector<int> &);
+  void export_learned_resolution ();
+
+  // If there is an external propagator it observes the variables marked
+  // in 'is_observed' (see 'external_propagate.cpp').
+
+  ExternalPropagator * propagator;
+  vector<bool> is_observed;   // Observed external variables.
+
+  bool observed (int elit) const {
+    const size_t eidx = abs (elit);
+    return eidx < is_observed.size () && is_observed[eidx];
+  }
+
+  void add_observed_var (int elit);
+  void remove_observed_var (int elit);
+  void reset_observed_vars ();
+  bool is_decision (int elit);
 
   //----------------------------------------------------------------------//
 
@@ -279,6 +297,9 @@ struct External {
 
   int fixed (int elit) const;   // Implemented in 'internal.hpp'.
 
+  void count_decision (int elit);
+  int64_t decisions (int elit) const;
+
   /*----------------------------------------------------------------------*/
 
   void phase (int elit);
@@ -0,0 +1,261 @@
+#include "internal.hpp"
+
+namespace CaDiCaL {
+
+/*------------------------------------------------------------------------*/
+
+// Literals propagated by the external propagator are assigned with the
+// address of 'external_reason' as pseudo reason, similar to decisions in
+// 'propagate.cpp'.  Their actual reason clause is only asked for if
+// conflict analysis (or the analysis of failed assumptions) resolves on
+// them and then added as redundant clause.  Minimization and reason
+// bumping skip them instead, since the propagator might have to do real
+// work to explain a literal.
+
+static Clause external_reason_clause;
+Clause * const Internal::external_reason = &external_reason_clause;
+
+// The propagator should only observe the search.  Chronological
+// backtracking would keep literals of higher levels on the trail below
+// literals of lower levels and the pre-solving and inprocessing steps
+// change the trail without it (see 'solve' and the scheduling functions).
+
+void Internal::connect_propagator () {
+  external_prop = true;
+  external_prop_is_lazy = external->propagator->is_lazy;
+  notified = 0;
+}
+
+/*------------------------------------------------------------------------*/
+
+// Assignments are notified lazily, but all of them before a new decision
+// level is opened, before the propagator is asked for anything and before
+// a conflict is analyzed.  Thus the propagator always knows the trail up
+// to the conflict when learned clauses are exported.
+
+void Internal::notify_assignments () {
+  if (!external_prop) return;
+  const size_t end_of_trail = trail.size ();
+  while (notified < end_of_trail) {
+    const int ilit = trail[notified++];
+    const int elit = externalize (ilit);
+    if (!external->observed (elit)) continue;
+    const bool fixed = !var (ilit).level;
+    external->propagator->notify_assignment (elit, fixed);
+  }
+}
+
+void Internal::notify_decision () {
+  if (!external_prop) return;
+  notify_assignments ();
+  external->propagator->notify_new_decision_level ();
+}
+
+void Internal::notify_backtrack (int new_level) {
+  if (!external_prop) return;
+  external->propagator->notify_backtrack (new_level);
+}
+
+bool Internal::is_decision (int ilit) {
+  if (!val (ilit)) return false;
+  const Var & v = var (ilit);
+  if (!v.level) return false;
+  const int decision = control[v.level].decision;
+  return decision && vidx (decision) == vidx (ilit);
+}
+
+// Decisions of the propagator take precedence over the decision heuristic.
+
+int Internal::ask_decision () {
+  if (!external_prop || external_prop_is_lazy) return 0;
+  notify_assignments ();
+  const int elit = external->propagator->cb_decide ();
+  if (!elit) return 0;
+  if (!external->observed (elit))
+    FATAL ("external propagator decided unobserved literal %d", elit);
+  const int ilit = external->internalize (elit);
+  if (val (ilit)) return 0;
+  LOG ("external decision %d", ilit);
+  return ilit;
+}
+
+/*------------------------------------------------------------------------*/
+
+// Clauses of the propagator are collected in 'external_clause' and then
+// turned into actual clauses through the 'clause' stack, which might still
+// hold the learned clause during conflict analysis.
+
+Clause * Internal::new_external_clause (bool redundant) {
+  swap (clause, external_clause);
+  const int size = (int) clause.size ();
+  Clause * res = new_clause (redundant, size);
+  swap (clause, external_clause);
+  external_clause.clear ();
+  watch_clause (res);
+  return res;
+}
+
+Clause * Internal::learn_external_reason_clause (int ilit) {
+  assert (external_prop);
+  assert (external_clause.empty ());
+  assert (val (ilit) > 0);
+  assert (var (ilit).reason == external_reason);
+  const int elit = externalize (ilit);
+  int other;
+  while ((other = external->propagator->cb_add_reason_clause_lit (elit)))
+    external_clause.push_back (external->internalize (other));
+  LOG (external_clause, "external reason of %d", ilit);
+
+  // The propagated literal and the other literal assigned last are the
+  // watches as in a driving clause.
+
+  const auto begin = external_clause.begin ();
+  const auto end = external_clause.end ();
+  const auto pos = find (begin, end, ilit);
+  if (pos == end || external_clause.size () < 2)
+    FATAL ("invalid reason clause of externally propagated %d", elit);
+  swap (*begin, *pos);
+  for (auto i = begin + 2; i != end; i++)
+    if (var (*i).level > var (begin[1]).level)
+      swap (begin[1], *i);
+
+  Clause * res = new_external_clause (true);
+  var (ilit).reason = res;
+  return res;
+}
+
+// Adds the clause in 'external_clause' while keeping the watch invariant.
+// True literals go first, then unassigned ones, then false literals with
+// decreasing level.  If the clause is unit or falsified on a lower level we
+// backtrack to the level where it became so, since we only backtrack
+// non-chronologically while a propagator is connected.  A conflict is only
+// recorded and counted in 'propagate' as the conflicts found there.
+
+void Internal::add_external_clause (bool redundant) {
+  LOG (external_clause, "external clause");
+  for (const auto & lit : external_clause)
+    if (val (lit) > 0 && !var (lit).level) {
+      LOG ("external clause satisfied by root-level unit %d", lit);
+      external_clause.clear ();
+      return;
+    }
+
+  const auto rank = [this] (int lit) {
+    const signed char tmp = val (lit);
+    if (tmp > 0) return INT_MAX;
+    if (!tmp) return INT_MAX - 1;
+    return var (lit).level;
+  };
+  stable_sort (external_clause.begin (), external_clause.end (),
+    [&rank] (int a, int b) { return rank (a) > rank (b); });
+
+  if (external_clause.size () < 2) {
+    const int lit = external_clause.empty () ? 0 : external_clause[0];
+    external_clause.clear ();
+    backtrack ();
+    if (!lit || val (lit) < 0) learn_empty_clause ();
+    else if (!val (lit)) assign_unit (lit);
+    return;
+  }
+
+  const int lit = external_clause[0];
+  const int other = external_clause[1];
+  Clause * c = new_external_clause (redundant);
+
+  const signed char u = val (lit);
+  const signed char v = val (other);
+  if (u > 0 || !v) return;
+
+  const int other_level = var (other).level;
+  if (!u || var (lit).level > other_level) {
+    backtrack (other_level);
+    search_assign_driving (lit, c);
+  } else {
+    backtrack (var (lit).level);
+    LOG (c, "external conflict");
+    conflict = c;
+  }
+}
+
+/*------------------------------------------------------------------------*/
+
+// Adds the clauses of the propagator and assigns the literals it propagates
+// until it has none left.  Falsified propagated literals and those on the
+// root level need their reason right away.  Returns whether the trail or
+// the clauses changed.
+
+bool Internal::external_propagate () {
+  if (external_prop_is_lazy) return false;
+  ExternalPropagator * propagator = external->propagator;
+  bool changed = false;
+  while (!conflict && !unsat) {
+    if (propagator->cb_has_external_clause ()) {
+      int elit;
+      while ((elit = propagator->cb_add_external_clause_lit ()))
+        external_clause.push_back (external->internalize (elit));
+      add_external_clause (false);
+      changed = true;
+      continue;
+    }
+    const int elit = propagator->cb_propagate ();
+    if (!elit) break;
+    if (!external->observed (elit))
+      FATAL ("external propagator propagated unobserved literal %d", elit);
+    const int ilit = external->internalize (elit);
+    const signed char tmp = val (ilit);
+    if (tmp > 0) continue;
+    if (tmp < 0 || !level) {
+      int other;
+      while ((other = propagator->cb_add_reason_clause_lit (elit)))
+        external_clause.push_back (external->internalize (other));
+      add_external_clause (true);
+    } else {
+      LOG ("external propagation %d", ilit);
+      search_assign_external (ilit);
+    }
+    changed = true;
+  }
+  return changed;
+}
+
+// Used instead of 'propagate' in the search loop.  A conflict of the
+// propagator is counted by the final 'propagate' call.
+
+bool Internal::propagate_wrapper () {
+  if (!external_prop) return propagate ();
+  while (propagate ()) {
+    notify_assignments ();
+    if (unsat || !external_propagate ()) break;
+  }
+  notify_assignments ();
+  return !conflict;
+}
+
+// Asks the propagator whether the model satisfies its constraints.  If it
+// rejects the model it has to add a clause falsified by it.
+
+bool Internal::external_check_solution () {
+  if (!external_prop) return true;
+  notify_assignments ();
+  vector<int> model;
+  for (int eidx = 1; eidx <= external->max_var; eidx++) {
+    if (!external->observed (eidx)) continue;
+    const int ilit = external->e2i[eidx];
+    if (!ilit) continue;
+    model.push_back (val (ilit) < 0 ? -eidx : eidx);
+  }
+  ExternalPropagator * propagator = external->propagator;
+  if (propagator->cb_check_found_model (model)) return true;
+  bool added = false;
+  while (!conflict && !unsat && propagator->cb_has_external_clause ()) {
+    int elit;
+    while ((elit = propagator->cb_add_external_clause_lit ()))
+      external_clause.push_back (external->internalize (elit));
+    add_external_clause (false);
+    added = true;
+  }
+  if (!added) LOG ("model rejected without external clause");
+  return !added;
+}
+
+}
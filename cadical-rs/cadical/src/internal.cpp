@@ -15,6 +15,8 @@ Internal::Internal ()
   protected_reasons (false),
   force_saved_phase (false),
   searching_lucky_phases (false),
+  external_prop (false),
+  external_prop_is_lazy (false),
   stable (false),
   reported (false),
   rephased (0),
@@ -31,6 +33,7 @@ Internal::Internal ()
   best_assigned (0),
   target_assigned (0),
   no_conflict_until (0),
+  notified (0),
   proof (0),
   checker (0),
   tracer (0),
@@ -196,9 +199,11 @@ int Internal::cdcl_loop_with_inprocessing () {
 
   while (!res) {
          if (unsat) res = 20;
-    else if (!propagate ()) analyze ();      // propagate and analyze
+    else if (!propagate_wrapper ()) analyze (); // propagate and analyze
+    else if (unsat) res = 20;                // external empty clause
     else if (iterating) iterate ();          // report learned unit
-    else if (satisfied ()) res = 10;         // found model
+    else if (satisfied ())                   // found model unless the
+      res = external_check_solution () ? 10 : 0; // propagator rejects it
     else if (search_limits_hit ()) break;    // decision or conflict limit
     else if (terminated_asynchronously ())    // externally terminated
       break;
@@ -413,6 +418,23 @@ void Internal::init_search_limits () {
       inc.decisions, lim.decisions);
   }
 
+  if (inc.propagations < 0) {
+    lim.propagations = -1;
+    LOG ("no limit on propagations");
+  } else {
+    lim.propagations = stats.propagations.search + inc.propagations;
+    LOG ("propagation limit after %" PRId64 " propagations at %" PRId64
+      " propagations", inc.propagations, lim.propagations);
+  }
+
+  if (inc.time < 0) {
+    lim.time = -1;
+    LOG ("no time limit");
+  } else {
+    lim.time = absolute_real_time () + inc.time;
+    LOG ("time limit after %" PRId64 " seconds", inc.time);
+  }
+
   /*----------------------------------------------------------------------*/
 
   // Initial preprocessing rounds.
@@ -466,6 +488,7 @@ bool Internal::preprocess_round (int round) {
 }
 
 int Internal::preprocess () {
+  if (external_prop) return 0;
   for (int i = 0; i < lim.preprocessing; i++)
     if (!preprocess_round (i))
       break;
@@ -555,6 +578,7 @@ int Internal::local_search () {
   if (unsat) return 0;
   if (!max_var) return 0;
   if (!opts.walk) return 0;
+  if (external_prop) return 0;
 
   int res = 0;
 
@@ -582,6 +606,8 @@ int Internal::solve (bool preprocess_only) {
   if (preprocess_only) LOG ("internal solving in preprocessing only mode");
   else LOG ("internal solving in full mode");
   init_report_limits ();
+  const int chrono = opts.chrono;
+  if (external_prop) opts.chrono = 0;
   int res = already_solved ();
   if (!res) res = restore_clauses ();
   if (!res) {
@@ -594,6 +620,7 @@ int Internal::solve (bool preprocess_only) {
     if (!res) res = lucky_phases ();
     if (!res) res = cdcl_loop_with_inprocessing ();
   }
+  opts.chrono = chrono;
   reset_solving ();
   report_solving (res);
   STOP (solve);
@@ -144,6 +144,8 @@ struct Internal {
   bool protected_reasons;       // referenced reasons are protected
   bool force_saved_phase;       // force saved phase in decision
   bool searching_lucky_phases;  // during 'lucky_phases'
+  bool external_prop;           // external propagator connected
+  bool external_prop_is_lazy;   // only checks models
   bool stable;                  // true during stabilization phase
   bool reported;                // reported in this solving call
   char rephased;                // last type of resetting phases
@@ -178,6 +180,7 @@ struct Internal {
   size_t best_assigned;         // best maximum assigned ever
   size_t target_assigned;       // maximum assigned without conflict
   size_t no_conflict_until;     // largest trail prefix without conflict
+  size_t notified;              // trail prefix notified to propagator
   vector<int> trail;            // currently assigned literals
   vector<int> clause;           // simplified in parsing & learning
   vector<int> assumptions;      // assumed literals
@@ -185,7 +188,10 @@ struct Internal {
   vector<int> levels;           // decision levels in learned clause
   vector<int> analyzed;         // analyzed literals in 'analyze'
   vector<int> minimized;        // removable or poison in 'minimize'
+  vector<int> resolution;       // resolved reasons in 'analyze' (pivot, literals, 0)
+  vector<int> minimization;     // resolved reasons in 'minimize' (pivot, literals, 0)
   vector<int> probes;           // remaining scheduled probes
+  vector<int> external_clause;  // clause given by external propagator
   vector<Level> control;        // 'level + 1 == control.size ()'
   vector<Clause*> clauses;      // ordered collection of all clauses
   Averages averages;            // glue, size, jump moving averages
@@ -500,6 +506,7 @@ struct Internal {
   int assignment_level (int lit, Clause*);
   void search_assign (int lit, Clause *);
   void search_assign_driving (int lit, Clause * reason);
+  void search_assign_external (int lit);
   void search_assume_decision (int decision);
   void assign_unit (int lit);
   bool propagate ();
@@ -526,11 +533,13 @@ struct Internal {
   void clear_analyzed_literals ();
   void clear_analyzed_levels ();
   void clear_minimized_literals ();
+  void record_minimization_steps (const vector<int> & removed);
   bool bump_also_reason_literal (int lit);
   void bump_also_reason_literals (int lit, int limit);
   void bump_also_all_reason_literals ();
   void analyze_literal (int lit, int & open);
   void analyze_reason (int lit, Clause *, int & open);
+  void record_resolution_step (vector<int> & steps, int pivot, Clause *);
   Clause * new_driving_clause (const int glue, int & jump);
   int find_conflict_level (int & forced);
   int determine_actual_backtrack_level (int jump);
@@ -600,6 +609,7 @@ struct Internal {
   void mark_useless_redundant_clauses_as_garbage ();
   bool propagate_out_of_order_units ();
   void unprotect_reasons ();
+  void export_reduced_clauses ();
   void reduce ();
 
   // Garbage collection in 'collect.cpp' called from 'reduce' and during
@@ -945,11 +955,31 @@ struct Internal {
   int likely_phase (int idx);
   int decide ();                        // 0=decision, 20=failed
 
+  // Communication with the external propagator in 'external_propagate.cpp'.
+  // Literals it propagates get 'external_reason' as pseudo reason until
+  // conflict analysis asks for their actual reason clause.
+  //
+  static Clause * const external_reason;
+  void connect_propagator ();
+  void notify_assignments ();
+  void notify_decision ();
+  void notify_backtrack (int new_level);
+  bool is_decision (int ilit);
+  int ask_decision ();
+  Clause * new_external_clause (bool redundant);
+  Clause * learn_external_reason_clause (int ilit);
+  void add_external_clause (bool redundant);
+  bool external_propagate ();
+  bool propagate_wrapper ();
+  bool external_check_solution ();
+
   // Internal functions to enable explicit search limits.
   //
   void limit_terminate (int);
   void limit_decisions (int);           // Force decision limit.
   void limit_conflicts (int);           // Force conflict limit.
+  void limit_propagations (int);        // Force propagation limit.
+  void limit_time (int);                // Force wall-clock time limit.
   void limit_preprocessing (int);       // Enable 'n' preprocessing rounds.
   void limit_local_search (int);        // Enable 'n' local search rounds.
 
@@ -1307,6 +1337,18 @@ inline bool Internal::search_limits_hit ()
     return true;
   }
 
+  if (lim.propagations >= 0 &&
+      stats.propagations.search >= lim.propagations) {
+    LOG ("propagation limit %" PRId64 " reached", lim.propagations);
+    return true;
+  }
+
+  if (lim.time >= 0 &&
+      absolute_real_time () >= lim.time) {
+    LOG ("time limit %.2f reached", lim.time);
+    return true;
+  }
+
   return false;
 }
 
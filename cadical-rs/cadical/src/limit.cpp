@@ -26,7 +26,7 @@ Last::Last () {
 
 Inc::Inc () {
   memset (this, 0, sizeof *this);
-  decisions = conflicts = -1;           // unlimited
+  decisions = conflicts = propagations = time = -1;     // unlimited
 }
 
 void Internal::limit_terminate (int l) {
@@ -65,6 +65,30 @@ void Internal::limit_decisions (int l) {
   }
 }
 
+void Internal::limit_propagations (int l) {
+  if (l < 0 && inc.propagations < 0) {
+    LOG ("keeping unbounded propagation limit");
+  } else if (l < 0) {
+    LOG ("reset propagation limit to be unbounded");
+    inc.propagations = -1;
+  } else {
+    inc.propagations = l;
+    LOG ("new propagation limit of %d propagations", l);
+  }
+}
+
+void Internal::limit_time (int l) {
+  if (l < 0 && inc.time < 0) {
+    LOG ("keeping unbounded time limit");
+  } else if (l < 0) {
+    LOG ("reset time limit to be unbounded");
+    inc.time = -1;
+  } else {
+    inc.time = l;
+    LOG ("new time limit of %d seconds", l);
+  }
+}
+
 void Internal::limit_preprocessing (int l) {
   if (l < 0) {
     LOG ("ignoring invalid preprocessing limit %d", l);
@@ -93,6 +117,8 @@ bool Internal::is_valid_limit (const char * name) {
   if (!strcmp (name, "terminate")) return true;
   if (!strcmp (name, "conflicts")) return true;
   if (!strcmp (name, "decisions")) return true;
+  if (!strcmp (name, "propagations")) return true;
+  if (!strcmp (name, "time")) return true;
   if (!strcmp (name, "preprocessing")) return true;
   if (!strcmp (name, "localsearch")) return true;
   return false;
@@ -103,6 +129,8 @@ bool Internal::limit (const char * name, int l) {
        if (!strcmp (name, "terminate")) limit_terminate (l);
   else if (!strcmp (name, "conflicts")) limit_conflicts (l);
   else if (!strcmp (name, "decisions")) limit_decisions (l);
+  else if (!strcmp (name, "propagations")) limit_propagations (l);
+  else if (!strcmp (name, "time")) limit_time (l);
   else if (!strcmp (name, "preprocessing")) limit_preprocessing (l);
   else if (!strcmp (name, "localsearch")) limit_local_search (l);
   else res = false;
@@ -114,6 +142,8 @@ void Internal::reset_limits () {
   limit_terminate (0);
   limit_conflicts (-1);
   limit_decisions (-1);
+  limit_propagations (-1);
+  limit_time (-1);
   limit_preprocessing (0);
   limit_local_search (0);
 }
@@ -9,6 +9,8 @@ struct Limit {
 
   int64_t conflicts;       // conflict limit if non-negative
   int64_t decisions;       // decision limit if non-negative
+  int64_t propagations;    // propagation limit if non-negative
+  double time;             // wall-clock time limit if non-negative
   int64_t preprocessing;   // limit on preprocessing rounds
   int64_t localsearch;     // limit on local search rounds
 
@@ -58,6 +60,8 @@ struct Inc {
   int64_t stabilize;       // stabilization interval increment
   int64_t conflicts;       // next conflict limit if non-negative
   int64_t decisions;       // next decision limit if non-negative
+  int64_t propagations;    // next propagation limit if non-negative
+  int64_t time;            // next time limit in seconds if non-negative
   int64_t preprocessing;   // next preprocessing limit if non-negative
   int64_t localsearch;     // next local search limit if non-negative
   Inc ();
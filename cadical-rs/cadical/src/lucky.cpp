@@ -280,6 +280,11 @@ int Internal::lucky_phases () {
   //
   if (!assumptions.empty ()) return 0;
 
+  // Lucky assignments are neither notified to an external propagator nor
+  // checked by it.
+  //
+  if (external_prop) return 0;
+
   START (search);
   START (lucky);
   assert (!searching_lucky_phases);
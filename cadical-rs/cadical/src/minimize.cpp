@@ -19,6 +19,7 @@ bool Internal::minimize_literal (int lit, int depth) {
   Var & v = var (lit);
   if (!v.level || f.removable || f.keep) return true;
   if (!v.reason || f.poison || v.level == level) return false;
+  if (v.reason == external_reason) return false;
   const Level & l = control[v.level];
   if (!depth && l.seen.count < 2) return false;   // Don Knuth's idea
   if (v.trail <= l.seen.trail) return false;      // new early abort
@@ -76,17 +77,43 @@ void Internal::minimize_clause () {
     minimize_trail_positive_rank (this), minimize_trail_smaller (this));
 
   assert (minimized.empty ());
+  vector<int> removed;
   const auto end = clause.end ();
   auto j = clause.begin (), i = j;
   for (; i != end; i++)
-    if (minimize_literal (-*i)) stats.minimized++;
-    else flags (*j++ = *i).keep = true;
+    if (minimize_literal (-*i)) {
+      stats.minimized++;
+      if (external->learner) removed.push_back (*i);
+    } else flags (*j++ = *i).keep = true;
   LOG ("minimized %zd literals", (size_t)(clause.end () - j));
   if (j != end) clause.resize (j - clause.begin ());
+  if (external->learner) record_minimization_steps (removed);
   clear_minimized_literals ();
   STOP (minimize);
 }
 
+// Records the reasons resolved to remove the literals of the 1st UIP clause
+// for exporting the derivation of the learned clause.  Literals are pushed
+// to 'minimized' after the literals of their reason, so going backwards
+// resolves every literal before the literals its reason brings in.  Literals
+// found removable on a path that failed were never brought in and are
+// skipped.
+
+void Internal::record_minimization_steps (const vector<int> & removed) {
+  vector<bool> needed (max_var + 1, false);
+  for (const auto & lit : removed)
+    needed[vidx (lit)] = true;
+  for (auto i = minimized.rbegin (); i != minimized.rend (); i++) {
+    const int lit = *i;
+    if (!flags (lit).removable || !needed[vidx (lit)]) continue;
+    Clause * reason = var (lit).reason;
+    record_resolution_step (minimization, lit, reason);
+    for (const auto & other : *reason)
+      if (other != lit && var (other).level)
+        needed[vidx (other)] = true;
+  }
+}
+
 void Internal::clear_minimized_literals () {
   LOG ("clearing %zd minimized literals", minimized.size ());
   for (const auto & lit : minimized) {
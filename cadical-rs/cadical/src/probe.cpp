@@ -15,6 +15,7 @@ namespace CaDiCaL {
 
 bool Internal::probing () {
   if (!opts.probe) return false;
+  if (external_prop) return false;
   if (!preprocessing && !opts.inprocessing) return false;
   if (preprocessing) assert (lim.preprocessing);
   if (stats.probingphases &&
@@ -110,6 +110,7 @@ void Internal::assign_unit (int lit) {
 void Internal::search_assume_decision (int lit) {
   require_mode (SEARCH);
   assert (propagated == trail.size ());
+  notify_decision ();
   level++;
   control.push_back (Level (lit, trail.size ()));
   LOG ("search decide %d", lit);
@@ -121,6 +122,12 @@ void Internal::search_assign_driving (int lit, Clause * c) {
   search_assign (lit, c);
 }
 
+void Internal::search_assign_external (int lit) {
+  require_mode (SEARCH);
+  assert (level);
+  search_assign (lit, external_reason);
+}
+
 /*------------------------------------------------------------------------*/
 
 // The 'propagate' function is usually the hot-spot of a CDCL SAT solver.
@@ -163,6 +163,21 @@ bool Internal::propagate_out_of_order_units () {
 
 /*------------------------------------------------------------------------*/
 
+// Exports the learned clauses deleted by the current reduction, before the
+// garbage collection frees them.  Each clause is terminated by a zero.
+
+void Internal::export_reduced_clauses () {
+  assert (external->learner);
+  vector<int> deleted;
+  for (const auto & c : clauses) {
+    if (!c->redundant || !c->garbage) continue;
+    for (const auto & lit : *c)
+      deleted.push_back (externalize (lit));
+    deleted.push_back (0);
+  }
+  external->learner->reduce (deleted.size (), deleted.data ());
+}
+
 void Internal::reduce () {
   START (reduce);
 
@@ -178,6 +193,7 @@ void Internal::reduce () {
   protect_reasons ();
   if (flush) mark_clauses_to_be_flushed ();
   else mark_useless_redundant_clauses_as_garbage ();
+  if (external->learner) export_reduced_clauses ();
   garbage_collection ();
 
   {
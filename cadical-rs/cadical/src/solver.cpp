@@ -736,6 +736,70 @@ void Solver::disconnect_learner () {
   LOG_API_CALL_END ("disconnect_learner");
 }
 
+/*===== IPASIR-UP ========================================================*/
+
+void Solver::connect_external_propagator (ExternalPropagator * propagator) {
+  LOG_API_CALL_BEGIN ("connect_external_propagator");
+  REQUIRE_VALID_STATE ();
+  REQUIRE (propagator, "can not connect zero propagator");
+#ifdef LOGGING
+  if (external->propagator)
+    LOG ("connecting new propagator (disconnecting previous one)");
+  else
+    LOG ("connecting new propagator (no previous one)");
+#endif
+  external->propagator = propagator;
+  internal->connect_propagator ();
+  LOG_API_CALL_END ("connect_external_propagator");
+}
+
+void Solver::disconnect_external_propagator () {
+  LOG_API_CALL_BEGIN ("disconnect_external_propagator");
+  REQUIRE_VALID_STATE ();
+#ifdef LOGGING
+  if (external->propagator)
+    LOG ("disconnecting previous propagator");
+  else
+    LOG ("ignoring to disconnect propagator (no previous one)");
+#endif
+  external->propagator = 0;
+  internal->external_prop = false;
+  internal->external_prop_is_lazy = false;
+  LOG_API_CALL_END ("disconnect_external_propagator");
+}
+
+void Solver::add_observed_var (int var) {
+  TRACE ("add_observed_var", var);
+  REQUIRE_VALID_STATE ();
+  REQUIRE_VALID_LIT (var);
+  external->add_observed_var (var);
+  LOG_API_CALL_END ("add_observed_var", var);
+}
+
+void Solver::remove_observed_var (int var) {
+  TRACE ("remove_observed_var", var);
+  REQUIRE_VALID_STATE ();
+  REQUIRE_VALID_LIT (var);
+  external->remove_observed_var (var);
+  LOG_API_CALL_END ("remove_observed_var", var);
+}
+
+void Solver::reset_observed_vars () {
+  TRACE ("reset_observed_vars");
+  REQUIRE_VALID_STATE ();
+  external->reset_observed_vars ();
+  LOG_API_CALL_END ("reset_observed_vars");
+}
+
+bool Solver::is_decision (int lit) {
+  TRACE ("is_decision", lit);
+  REQUIRE_VALID_OR_SOLVING_STATE ();
+  REQUIRE_VALID_LIT (lit);
+  bool res = external->is_decision (lit);
+  LOG_API_CALL_RETURNS ("is_decision", lit, res);
+  return res;
+}
+
 /*===== IPASIR END =======================================================*/
 
 int Solver::active () const {
@@ -957,6 +1021,25 @@ int64_t Solver::restarts() {
     return internal->stats.restarts;
 }
 
+int64_t Solver::propagations () {
+    return internal->stats.propagations.search;
+}
+
+int64_t Solver::variable_decisions (int lit) {
+    REQUIRE_VALID_LIT (lit);
+    return external->decisions (lit);
+}
+
+double Solver::variable_activity (int lit) {
+    REQUIRE_VALID_LIT (lit);
+    const int eidx = abs (lit);
+    if (eidx > external->max_var) return 0;
+    const int ilit = external->e2i[eidx];
+    if (!ilit) return 0;
+    if (internal->use_scores ()) return internal->score (ilit);
+    return (double) internal->bumped (ilit);
+}
+
 /*------------------------------------------------------------------------*/
 
 const char * Solver::read_dimacs (File * file, int & vars, int strict,
@@ -60,6 +60,7 @@ namespace CaDiCaL {
 bool Internal::subsuming () {
 
   if (!opts.subsume && !opts.vivify) return false;
+  if (external_prop) return false;
   if (!preprocessing && !opts.inprocessing) return false;
   if (preprocessing) assert (lim.preprocessing);
 
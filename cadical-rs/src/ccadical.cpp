@@ -35,4 +35,26 @@ extern "C"
   {
     return ((Wrapper *)wrapper)->solver->limit(name, val);
   }
+
+  int ccadical_set_option2(CCaDiCaL *wrapper,
+                           const char *name, int val)
+  {
+    Solver *solver = ((Wrapper *)wrapper)->solver;
+    if (solver->state() != CONFIGURING)
+      return 0;
+    return solver->set(name, val);
+  }
+
+  int ccadical_trace_proof(CCaDiCaL *wrapper, const char *path)
+  {
+    Solver *solver = ((Wrapper *)wrapper)->solver;
+    if (solver->state() != CONFIGURING)
+      return 0;
+    return solver->trace_proof(path);
+  }
+
+  void ccadical_close_proof(CCaDiCaL *wrapper)
+  {
+    ((Wrapper *)wrapper)->solver->close_proof_trace();
+  }
 }
@@ -35,22 +35,26 @@ extern "C" {
         max_len: c_int,
         cbs: Option<extern "C" fn(*mut c_void, *const c_int)>,
     );
-    // PAAVO:
-    fn ccadical_set_learn_trail(
+    fn ccadical_set_restart(
         ptr: *mut c_void,
         data: *mut c_void,
-        cbs: Option<
-            extern "C" fn(
-                *mut c_void,
-                c_ulong,
-                *const c_int,
-                c_ulong,
-                *const c_int,
-                c_ulong,
-                *const c_int,
-            ),
-        >,
+        cbs: Option<extern "C" fn(*mut c_void)>,
     );
+    fn ccadical_set_learn_resolution(
+        ptr: *mut c_void,
+        data: *mut c_void,
+        cbs: Option<extern "C" fn(*mut c_void, c_ulong, *const c_int, c_ulong, *const c_int)>,
+    );
+    fn ccadical_set_reduce(
+        ptr: *mut c_void,
+        data: *mut c_void,
+        cbs: Option<extern "C" fn(*mut c_void, c_ulong, *const c_int)>,
+    );
+    fn ccadical_connect_external_propagator(ptr: *mut c_void, propagator: *const CPropagator);
+    fn ccadical_disconnect_external_propagator(ptr: *mut c_void);
+    fn ccadical_add_observed_var(ptr: *mut c_void, var: c_int);
+    fn ccadical_remove_observed_var(ptr: *mut c_void, var: c_int);
+    fn ccadical_reset_observed_vars(ptr: *mut c_void);
     fn ccadical_status(ptr: *mut c_void) -> c_int;
     fn ccadical_vars(ptr: *mut c_void) -> c_int;
     fn ccadical_active(ptr: *mut c_void) -> i64;
@@ -68,6 +72,9 @@ extern "C" {
     ) -> *const c_char;
     fn ccadical_configure(ptr: *mut c_void, name: *const c_char) -> c_int;
     fn ccadical_limit2(ptr: *mut c_void, name: *const c_char, limit: c_int) -> c_int;
+    fn ccadical_set_option2(ptr: *mut c_void, name: *const c_char, val: c_int) -> c_int;
+    fn ccadical_trace_proof(ptr: *mut c_void, path: *const c_char) -> c_int;
+    fn ccadical_close_proof(ptr: *mut c_void);
     fn ccadical_fixed(ptr: *mut c_void, lit: c_int) -> c_int;
     fn ccadical_process_time(ptr: *mut c_void) -> c_double;
     fn ccadical_real_time(ptr: *mut c_void) -> c_double;
@@ -77,6 +84,25 @@ extern "C" {
     fn ccadical_learned_literals(ptr: *mut c_void) -> i64;
     fn ccadical_decisions(ptr: *mut c_void) -> i64;
     fn ccadical_restarts(ptr: *mut c_void) -> i64;
+    fn ccadical_propagations(ptr: *mut c_void) -> i64;
+    fn ccadical_variable_decisions(ptr: *mut c_void, lit: c_int) -> i64;
+    fn ccadical_variable_activity(ptr: *mut c_void, lit: c_int) -> f64;
+}
+
+/// The callbacks of an external propagator as passed to the C wrapper
+#[repr(C)]
+struct CPropagator {
+    state: *mut c_void,
+    is_lazy: c_int,
+    notify_assignment: extern "C" fn(*mut c_void, c_int, c_int),
+    notify_new_decision_level: extern "C" fn(*mut c_void),
+    notify_backtrack: extern "C" fn(*mut c_void, c_ulong),
+    check_found_model: extern "C" fn(*mut c_void, c_ulong, *const c_int) -> c_int,
+    decide: extern "C" fn(*mut c_void) -> c_int,
+    propagate: extern "C" fn(*mut c_void) -> c_int,
+    add_reason_clause_lit: extern "C" fn(*mut c_void, c_int) -> c_int,
+    has_external_clause: extern "C" fn(*mut c_void) -> c_int,
+    add_external_clause_lit: extern "C" fn(*mut c_void) -> c_int,
 }
 
 /// The CaDiCaL incremental SAT solver. The literals are unwrapped positive
@@ -93,14 +119,22 @@ extern "C" {
 
 pub struct Solver<C: Callbacks = Timeout> {
     ptr: *mut c_void,
-    cbs: Option<Box<C>>,
+    hooks: Box<Hooks<C>>,
+    /// All variables up to this one are observed for the trail callbacks
+    observed_up_to: i32,
+    tracing_proof: bool,
 }
 
 impl<C: Callbacks> Solver<C> {
     /// Constructs a new solver instance.
     pub fn new() -> Self {
         let ptr = unsafe { ccadical_init() };
-        Self { ptr, cbs: None }
+        Self {
+            ptr,
+            hooks: Box::new(Hooks::new()),
+            observed_up_to: 0,
+            tracing_proof: false,
+        }
     }
 
     /// Constructs a new solver with one of the following pre-defined
@@ -139,6 +173,9 @@ impl<C: Callbacks> Solver<C> {
             unsafe { ccadical_add(self.ptr, lit) };
         }
         unsafe { ccadical_add(self.ptr, 0) };
+        if self.hooks.observe_trail {
+            self.observe_new_variables();
+        }
     }
 
     /// Solves the formula defined by the added clauses. If the formula is
@@ -146,11 +183,12 @@ impl<C: Callbacks> Solver<C> {
     /// unsatisfiable, then `Some(false)` is returned. If the solver runs out
     /// of resources or was terminated, then `None` is returned.
     pub fn solve(&mut self) -> Option<bool> {
-        if let Some(cbs) = &mut self.cbs {
-            cbs.as_mut().started();
+        if let Some(cbs) = &mut self.hooks.callbacks {
+            cbs.started();
         }
 
         let r = unsafe { ccadical_solve(self.ptr) };
+        self.hooks.assumptions = 0;
         if r == 10 {
             Some(true)
         } else if r == 20 {
@@ -166,10 +204,16 @@ impl<C: Callbacks> Solver<C> {
     where
         I: Iterator<Item = i32>,
     {
+        let mut assumed = Vec::new();
         for lit in assumptions {
             debug_assert!(lit != 0 && lit != std::i32::MIN);
             unsafe { ccadical_assume(self.ptr, lit) };
+            assumed.push(lit);
         }
+        // The solver assumes each distinct literal on its own decision level
+        assumed.sort_unstable();
+        assumed.dedup();
+        self.hooks.assumptions = assumed.len();
         self.solve()
     }
 
@@ -261,6 +305,10 @@ impl<C: Callbacks> Solver<C> {
     ///    and aborts the solver when it becomes negative (defaults to `-1`).
     /// * `decisions`: decremented when a decision is made
     ///    and aborts the solver when it becomes negative (defaults to `-1`).
+    /// * `propagations`: the number of propagations after which the solver
+    ///    is aborted (defaults to `-1`).
+    /// * `time`: the number of seconds of wall-clock time after which the
+    ///    solver is aborted (defaults to `-1`).
     pub fn set_limit(&mut self, name: &str, limit: i32) -> Result<(), Error> {
         let name = CString::new(name).map_err(|_| Error::new("invalid string"))?;
         let valid = unsafe { ccadical_limit2(self.ptr, name.as_ptr(), limit) };
@@ -271,6 +319,54 @@ impl<C: Callbacks> Solver<C> {
         }
     }
 
+    /// Sets the option with the given name to the given value. Options can
+    /// only be set right after initialization, before any clauses are added.
+    /// # Examples
+    /// ```
+    /// let mut sat: cadical::Solver = Default::default();
+    /// assert!(sat.set_option("binary", 0).is_ok());
+    /// assert!(sat.set_option("bad", 0).is_err());
+    /// ```
+    pub fn set_option(&mut self, name: &str, value: i32) -> Result<(), Error> {
+        let name = CString::new(name).map_err(|_| Error::new("invalid string"))?;
+        let valid = unsafe { ccadical_set_option2(self.ptr, name.as_ptr(), value) };
+        if valid != 0 {
+            Ok(())
+        } else {
+            Err(Error::new("invalid option or state"))
+        }
+    }
+
+    /// Starts writing a DRAT proof of the derived and deleted clauses to
+    /// the given file. When `binary` is false the proof is written in the
+    /// textual DRAT format. You must call this function during configuration
+    /// time, before adding any clauses. The proof is completed when
+    /// `close_proof` is called or the solver is dropped.
+    pub fn trace_proof(&mut self, path: &Path, binary: bool) -> Result<(), Error> {
+        if self.tracing_proof || self.max_variable() != 0 {
+            return Err(Error::new("invalid state"));
+        }
+        self.set_option("binary", binary as i32)?;
+        let path = dimacs_path(path)?;
+        let res = unsafe { ccadical_trace_proof(self.ptr, path.as_ptr()) };
+        if res != 0 {
+            self.tracing_proof = true;
+            Ok(())
+        } else {
+            Err(Error::new("could not open proof file"))
+        }
+    }
+
+    /// Flushes and closes the proof file started with `trace_proof`.
+    pub fn close_proof(&mut self) -> Result<(), Error> {
+        if !self.tracing_proof {
+            return Err(Error::new("proof is not traced"));
+        }
+        unsafe { ccadical_close_proof(self.ptr) };
+        self.tracing_proof = false;
+        Ok(())
+    }
+
     /// Sets the callbacks to be called while the solver is running.
     /// # Examples
     /// ```
@@ -281,34 +377,175 @@ impl<C: Callbacks> Solver<C> {
     /// ```
     pub fn set_callbacks(&mut self, cbs: Option<C>) {
         if let Some(cbs) = cbs {
-            if let Some(data) = &mut self.cbs {
-                *data.as_mut() = cbs;
-            } else {
-                self.cbs = Some(Box::new(cbs));
-            }
-            let data = self.cbs.as_mut().unwrap();
-            let max_length = data.max_length();
-            let data = data.as_mut() as *mut C as *mut c_void;
+            let max_length = cbs.max_length();
+            self.hooks.observe_trail = cbs.observe_trail();
+            self.hooks.callbacks = Some(cbs);
+            let data = self.hooks.as_mut() as *mut Hooks<C> as *mut c_void;
             unsafe {
                 ccadical_set_terminate(self.ptr, data, Some(Self::terminate_cb));
                 ccadical_set_learn(self.ptr, data, max_length, Some(Self::learn_cb));
-                // PAAVO:
-                ccadical_set_learn_trail(self.ptr, data, Some(Self::learn_trail_cb));
+                ccadical_set_restart(self.ptr, data, Some(Self::restart_cb));
+                ccadical_set_learn_resolution(self.ptr, data, Some(Self::learn_resolution_cb));
+                ccadical_set_reduce(self.ptr, data, Some(Self::reduce_cb));
             }
         } else {
-            self.cbs = None;
+            self.hooks.callbacks = None;
+            self.hooks.observe_trail = false;
             let data = null_mut() as *mut c_void;
             unsafe {
                 ccadical_set_terminate(self.ptr, data, None);
                 ccadical_set_learn(self.ptr, data, 0, None);
+                ccadical_set_restart(self.ptr, data, None);
+                ccadical_set_learn_resolution(self.ptr, data, None);
+                ccadical_set_reduce(self.ptr, data, None);
             }
         }
+        self.connect_propagator();
+    }
+
+    /// Connects an external propagator, which takes part in the search through the IPASIR-UP
+    /// interface of the solver. It is notified about the assignments of the variables it
+    /// observes (see `add_observed_var`), new decision levels and backtracking, and it can
+    /// propagate and decide literals and add clauses. The reason clause of a propagated literal
+    /// is only asked for when conflict analysis needs it. Connecting another propagator replaces
+    /// this one. While a propagator is connected, the solver searches without lucky phases,
+    /// chronological backtracking, local search, preprocessing and inprocessing.
+    /// # Examples
+    /// ```
+    /// // Rejects the models with both 1 and 2 true
+    /// #[derive(Default)]
+    /// struct AtMostOne {
+    ///     clause: Vec<i32>,
+    /// }
+    ///
+    /// impl cadical::ExternalPropagator for AtMostOne {
+    ///     fn is_lazy(&self) -> bool {
+    ///         true
+    ///     }
+    ///     fn notify_assignment(&mut self, _lit: i32, _is_fixed: bool) {}
+    ///     fn notify_new_decision_level(&mut self) {}
+    ///     fn notify_backtrack(&mut self, _new_level: usize) {}
+    ///     fn check_found_model(&mut self, model: &[i32]) -> bool {
+    ///         if model.contains(&1) && model.contains(&2) {
+    ///             // Popped in reverse order, ending with 0
+    ///             self.clause = vec![0, -2, -1];
+    ///             return false;
+    ///         }
+    ///         true
+    ///     }
+    ///     fn has_external_clause(&mut self) -> bool {
+    ///         !self.clause.is_empty()
+    ///     }
+    ///     fn add_external_clause_lit(&mut self) -> i32 {
+    ///         self.clause.pop().unwrap_or(0)
+    ///     }
+    /// }
+    ///
+    /// let mut sat: cadical::Solver = Default::default();
+    /// sat.add_clause([1, 2]);
+    /// sat.add_observed_var(1);
+    /// sat.add_observed_var(2);
+    /// sat.connect_external_propagator(AtMostOne::default());
+    /// assert_eq!(sat.solve(), Some(true));
+    /// assert_ne!(sat.value(1), sat.value(2));
+    /// ```
+    pub fn connect_external_propagator<P>(&mut self, propagator: P)
+    where
+        P: ExternalPropagator + Send + 'static,
+    {
+        self.hooks.propagator = Some(Box::new(propagator));
+        self.connect_propagator();
+    }
+
+    /// Disconnects the external propagator and stops observing its variables.
+    pub fn disconnect_external_propagator(&mut self) {
+        self.hooks.propagator = None;
+        self.hooks.observed.clear();
+        self.connect_propagator();
+    }
+
+    /// Makes the external propagator observe the variable. Observed variables are frozen, so
+    /// they are not eliminated.
+    pub fn add_observed_var(&mut self, var: i32) {
+        debug_assert!(var != 0 && var != i32::MIN);
+        let var = var.unsigned_abs() as usize;
+        if self.hooks.observed.len() <= var {
+            self.hooks.observed.resize(var + 1, false);
+        }
+        self.hooks.observed[var] = true;
+        unsafe { ccadical_add_observed_var(self.ptr, var as c_int) };
+    }
+
+    /// Stops the external propagator from observing the variable.
+    pub fn remove_observed_var(&mut self, var: i32) {
+        debug_assert!(var != 0 && var != i32::MIN);
+        if !self.hooks.observes(var) {
+            return;
+        }
+        let var = var.abs();
+        self.hooks.observed[var as usize] = false;
+        if !self.hooks.observe_trail || var > self.observed_up_to {
+            unsafe { ccadical_remove_observed_var(self.ptr, var) };
+        }
+    }
+
+    /// Stops the external propagator from observing any variable.
+    pub fn reset_observed_vars(&mut self) {
+        self.hooks.observed.clear();
+        self.connect_propagator();
+    }
+
+    /// Connects the solver to the hooks if the trail callbacks or an external propagator need
+    /// it, and observes their variables from scratch.
+    fn connect_propagator(&mut self) {
+        unsafe { ccadical_reset_observed_vars(self.ptr) };
+        self.observed_up_to = 0;
+        let hooks = self.hooks.as_mut();
+        hooks.trail = ObservedTrail::default();
+        if !hooks.observe_trail && hooks.propagator.is_none() {
+            unsafe { ccadical_disconnect_external_propagator(self.ptr) };
+            return;
+        }
+
+        let is_lazy = hooks.propagator.as_ref().is_none_or(|p| p.is_lazy());
+        let propagator = CPropagator {
+            state: hooks as *mut Hooks<C> as *mut c_void,
+            is_lazy: is_lazy as c_int,
+            notify_assignment: Self::notify_assignment_cb,
+            notify_new_decision_level: Self::notify_new_decision_level_cb,
+            notify_backtrack: Self::notify_backtrack_cb,
+            check_found_model: Self::check_found_model_cb,
+            decide: Self::decide_cb,
+            propagate: Self::propagate_cb,
+            add_reason_clause_lit: Self::add_reason_clause_lit_cb,
+            has_external_clause: Self::has_external_clause_cb,
+            add_external_clause_lit: Self::add_external_clause_lit_cb,
+        };
+        unsafe { ccadical_connect_external_propagator(self.ptr, &propagator) };
+
+        for (var, &observed) in self.hooks.observed.iter().enumerate() {
+            if observed {
+                unsafe { ccadical_add_observed_var(self.ptr, var as c_int) };
+            }
+        }
+        if self.hooks.observe_trail {
+            self.observe_new_variables();
+        }
+    }
+
+    /// Observes the variables added since the last call for the trail callbacks
+    fn observe_new_variables(&mut self) {
+        let max_variable = self.max_variable();
+        for var in self.observed_up_to + 1..=max_variable {
+            unsafe { ccadical_add_observed_var(self.ptr, var) };
+        }
+        self.observed_up_to = self.observed_up_to.max(max_variable);
     }
 
     extern "C" fn terminate_cb(data: *mut c_void) -> c_int {
         debug_assert!(!data.is_null());
-        let cbs = unsafe { &mut *(data as *mut C) };
-        cbs.terminate() as c_int
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        hooks.callbacks.as_mut().is_some_and(|cbs| cbs.terminate()) as c_int
     }
 
     extern "C" fn learn_cb(data: *mut c_void, clause: *const c_int) {
@@ -321,38 +558,153 @@ impl<C: Callbacks> Solver<C> {
         let clause = unsafe { slice::from_raw_parts(clause, len as usize) };
         let clause = ManuallyDrop::new(clause);
 
-        let cbs = unsafe { &mut *(data as *mut C) };
-        cbs.learn(&clause);
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        if let Some(cbs) = &mut hooks.callbacks {
+            cbs.learn(&clause);
+        }
+    }
+
+    extern "C" fn restart_cb(data: *mut c_void) {
+        debug_assert!(!data.is_null());
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        if let Some(cbs) = &mut hooks.callbacks {
+            cbs.on_restart();
+        }
     }
 
-    // PAAVO:
-    extern "C" fn learn_trail_cb(
+    extern "C" fn learn_resolution_cb(
         data: *mut c_void,
-        conflict_size: c_ulong,
-        conflict_literals: *const c_int,
-        propagated_size: c_ulong,
-        is_propagated: *const c_int,
         size: c_ulong,
-        trail: *const c_int,
+        steps: *const c_int,
+        minimization_size: c_ulong,
+        minimization: *const c_int,
     ) {
-        let conflict_literals =
-            unsafe { slice::from_raw_parts(conflict_literals, conflict_size as usize) };
-        let conflict_literals = ManuallyDrop::new(conflict_literals);
+        debug_assert!(!data.is_null());
+        let steps = Self::resolution_steps(size, steps);
+        let minimization = Self::resolution_steps(minimization_size, minimization);
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        if let Some(cbs) = &mut hooks.callbacks {
+            if hooks.observe_trail {
+                // The conflict clause is the first step, and all assignments are notified
+                // before the conflict is analyzed
+                let conflict = steps.first().map_or(&[][..], |(_, lits)| &lits[..]);
+                cbs.learn_trail(conflict, &hooks.trail.is_propagated, &hooks.trail.literals);
+            }
+            cbs.learn_resolution(&steps, &minimization);
+        }
+    }
+
+    /// Splits the steps, each the pivot followed by the literals of the reason and a zero
+    fn resolution_steps(size: c_ulong, steps: *const c_int) -> Vec<(i32, Vec<i32>)> {
+        let mut rest = if size == 0 {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts(steps, size as usize) }
+        };
+
+        let mut steps = Vec::new();
+        while let Some((&pivot, tail)) = rest.split_first() {
+            let end = tail.iter().position(|&lit| lit == 0).unwrap_or(tail.len());
+            steps.push((pivot, tail[..end].to_vec()));
+            rest = tail.get(end + 1..).unwrap_or(&[]);
+        }
+        steps
+    }
+
+    extern "C" fn reduce_cb(data: *mut c_void, size: c_ulong, clauses: *const c_int) {
+        debug_assert!(!data.is_null());
+        let clauses = if size == 0 {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts(clauses, size as usize) }
+        };
+        let deleted_clauses = clauses
+            .split(|&lit| lit == 0)
+            .filter(|clause| !clause.is_empty())
+            .map(|clause| clause.to_vec())
+            .collect::<Vec<Vec<i32>>>();
+
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        if let Some(cbs) = &mut hooks.callbacks {
+            cbs.on_reduce(&deleted_clauses);
+        }
+    }
 
-        let is_propagated =
-            unsafe { slice::from_raw_parts(is_propagated, propagated_size as usize) };
-        let is_propagated = ManuallyDrop::new(is_propagated);
+    extern "C" fn notify_assignment_cb(data: *mut c_void, lit: c_int, is_fixed: c_int) {
+        debug_assert!(!data.is_null());
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        hooks.notify_assignment(lit, is_fixed != 0);
+    }
 
-        let trail = unsafe { slice::from_raw_parts(trail, size as usize) };
-        let trail = ManuallyDrop::new(trail);
+    extern "C" fn notify_new_decision_level_cb(data: *mut c_void) {
+        debug_assert!(!data.is_null());
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        hooks.notify_new_decision_level();
+    }
 
-        let cbs = unsafe { &mut *(data as *mut C) };
-        cbs.learn_trail(&conflict_literals, &is_propagated, &trail);
+    extern "C" fn notify_backtrack_cb(data: *mut c_void, new_level: c_ulong) {
+        debug_assert!(!data.is_null());
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        hooks.notify_backtrack(new_level as usize);
+    }
+
+    extern "C" fn check_found_model_cb(
+        data: *mut c_void,
+        size: c_ulong,
+        model: *const c_int,
+    ) -> c_int {
+        debug_assert!(!data.is_null());
+        let model = if size == 0 {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts(model, size as usize) }
+        };
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        hooks.check_found_model(model) as c_int
+    }
+
+    extern "C" fn decide_cb(data: *mut c_void) -> c_int {
+        debug_assert!(!data.is_null());
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        hooks.propagator.as_mut().map_or(0, |p| p.decide())
+    }
+
+    extern "C" fn propagate_cb(data: *mut c_void) -> c_int {
+        debug_assert!(!data.is_null());
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        hooks.propagator.as_mut().map_or(0, |p| p.propagate())
+    }
+
+    extern "C" fn add_reason_clause_lit_cb(data: *mut c_void, propagated_lit: c_int) -> c_int {
+        debug_assert!(!data.is_null());
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        hooks
+            .propagator
+            .as_mut()
+            .map_or(0, |p| p.add_reason_clause_lit(propagated_lit))
+    }
+
+    extern "C" fn has_external_clause_cb(data: *mut c_void) -> c_int {
+        debug_assert!(!data.is_null());
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        hooks
+            .propagator
+            .as_mut()
+            .is_some_and(|p| p.has_external_clause()) as c_int
+    }
+
+    extern "C" fn add_external_clause_lit_cb(data: *mut c_void) -> c_int {
+        debug_assert!(!data.is_null());
+        let hooks = unsafe { &mut *(data as *mut Hooks<C>) };
+        hooks
+            .propagator
+            .as_mut()
+            .map_or(0, |p| p.add_external_clause_lit())
     }
 
     /// Returns a mutable reference to the callbacks.
     pub fn get_callbacks(&mut self) -> Option<&mut C> {
-        self.cbs.as_mut().map(|a| a.as_mut())
+        self.hooks.callbacks.as_mut()
     }
 
     /// Writes the problem in DIMACS format to the given file.
@@ -378,6 +730,9 @@ impl<C: Callbacks> Solver<C> {
         let err =
             unsafe { ccadical_read_dimacs(self.ptr, path.as_ptr(), &mut vars as *mut c_int, 0) };
         if err.is_null() {
+            if self.hooks.observe_trail {
+                self.observe_new_variables();
+            }
             Ok(vars)
         } else {
             Err(dimacs_error(err))
@@ -390,6 +745,26 @@ impl<C: Callbacks> Solver<C> {
         unsafe { ccadical_fixed(self.ptr, literal) }
     }
 
+    /// Returns the number of decisions made on the variable of the literal.
+    /// # Examples
+    /// ```
+    /// let mut sat: cadical::Solver = Default::default();
+    /// sat.add_clause([1, 2]);
+    /// sat.add_clause([-1, -2]);
+    /// assert_eq!(sat.solve(), Some(true));
+    /// assert_eq!(sat.variable_decisions(1) + sat.variable_decisions(2), sat.stats().decisions);
+    /// ```
+    pub fn variable_decisions(&self, literal: i32) -> i64 {
+        unsafe { ccadical_variable_decisions(self.ptr, literal) }
+    }
+
+    /// Returns the activity of the variable of the literal in the decision heuristic.
+    /// This is the VSIDS score in stable mode and the VMTF enqueue time stamp in focused
+    /// mode, so it can only be compared between variables of the same solver.
+    pub fn variable_activity(&self, literal: i32) -> f64 {
+        unsafe { ccadical_variable_activity(self.ptr, literal) }
+    }
+
     pub fn stats(&mut self) -> CadicalStats {
         CadicalStats {
             process_time: unsafe { ccadical_process_time(self.ptr) },
@@ -400,6 +775,7 @@ impl<C: Callbacks> Solver<C> {
             learned_literals: unsafe { ccadical_learned_literals(self.ptr) },
             decisions: unsafe { ccadical_decisions(self.ptr) },
             restarts: unsafe { ccadical_restarts(self.ptr) },
+            propagations: unsafe { ccadical_propagations(self.ptr) },
         }
     }
 }
@@ -414,6 +790,7 @@ pub struct CadicalStats {
     pub learned_literals: i64,
     pub decisions: i64,
     pub restarts: i64,
+    pub propagations: i64,
 }
 
 fn dimacs_path(path: &Path) -> Result<CString, Error> {
@@ -444,6 +821,125 @@ impl<C: Callbacks> Drop for Solver<C> {
 /// do not implement `Sync`.
 unsafe impl<C: Callbacks + Send> Send for Solver<C> {}
 
+/// The state shared by the callbacks of the solver. The trail callbacks are built on the
+/// notifications of the external propagator interface, which are passed on to the external
+/// propagator for the variables it observes.
+struct Hooks<C> {
+    callbacks: Option<C>,
+    /// Whether the trail callbacks are enabled
+    observe_trail: bool,
+    trail: ObservedTrail,
+    /// The number of assumption levels of the current solve call
+    assumptions: usize,
+    propagator: Option<Box<dyn ExternalPropagator + Send>>,
+    /// The variables observed by the external propagator
+    observed: Vec<bool>,
+}
+
+impl<C: Callbacks> Hooks<C> {
+    fn new() -> Self {
+        Hooks {
+            callbacks: None,
+            observe_trail: false,
+            trail: ObservedTrail::default(),
+            assumptions: 0,
+            propagator: None,
+            observed: Vec::new(),
+        }
+    }
+
+    fn observes(&self, lit: i32) -> bool {
+        let var = lit.unsigned_abs() as usize;
+        self.observed.get(var).copied().unwrap_or(false)
+    }
+
+    fn notify_assignment(&mut self, lit: i32, is_fixed: bool) {
+        if self.observe_trail && self.trail.push(lit, is_fixed) {
+            let level = self.trail.levels.len();
+            if level > self.assumptions {
+                if let Some(cbs) = &mut self.callbacks {
+                    cbs.on_decision(lit, level as i32);
+                }
+            }
+        }
+        if self.observes(lit) {
+            if let Some(propagator) = &mut self.propagator {
+                propagator.notify_assignment(lit, is_fixed);
+            }
+        }
+    }
+
+    fn notify_new_decision_level(&mut self) {
+        if self.observe_trail {
+            self.trail.new_level();
+        }
+        if let Some(propagator) = &mut self.propagator {
+            propagator.notify_new_decision_level();
+        }
+    }
+
+    fn notify_backtrack(&mut self, new_level: usize) {
+        if self.observe_trail {
+            self.trail.backtrack(new_level);
+            if let Some(cbs) = &mut self.callbacks {
+                cbs.on_backtrack(new_level as i32);
+            }
+        }
+        if let Some(propagator) = &mut self.propagator {
+            propagator.notify_backtrack(new_level);
+        }
+    }
+
+    fn check_found_model(&mut self, model: &[i32]) -> bool {
+        if self.propagator.is_none() {
+            return true;
+        }
+        let model: Vec<i32> = model
+            .iter()
+            .copied()
+            .filter(|&lit| self.observes(lit))
+            .collect();
+        let propagator = self.propagator.as_mut().unwrap();
+        propagator.check_found_model(&model)
+    }
+}
+
+/// The trail of the solver as far as it has been notified
+#[derive(Default)]
+struct ObservedTrail {
+    literals: Vec<i32>,
+    /// Whether each literal was propagated, that is neither decided nor fixed
+    is_propagated: Vec<i32>,
+    /// The length of the trail when each decision level was opened
+    levels: Vec<usize>,
+    /// Whether the next literal is the decision of the last level
+    deciding: bool,
+}
+
+impl ObservedTrail {
+    /// Adds the literal to the trail and returns whether it was decided
+    fn push(&mut self, lit: i32, is_fixed: bool) -> bool {
+        let decision = std::mem::take(&mut self.deciding);
+        self.literals.push(lit);
+        self.is_propagated.push((!decision && !is_fixed) as i32);
+        decision
+    }
+
+    fn new_level(&mut self) {
+        self.levels.push(self.literals.len());
+        self.deciding = true;
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if let Some(&len) = self.levels.get(level) {
+            self.literals.truncate(len);
+            self.is_propagated.truncate(len);
+        }
+        self.levels.truncate(level);
+        self.deciding = false;
+    }
+}
+
 /// Callbacks trait for finer control.
 pub trait Callbacks {
     /// Called when the `solve` method is called.
@@ -468,9 +964,104 @@ pub trait Callbacks {
     #[inline(always)]
     fn learn(&mut self, clause: &[i32]) {}
 
-    // PAAVO:
+    /// Returns whether the solver should follow its trail for `learn_trail`, `on_decision` and
+    /// `on_backtrack`. The trail is observed through the external propagator interface, so
+    /// the solver then searches as with an external propagator connected. This method will be
+    /// called only once when `set_callbacks` is called.
+    #[inline(always)]
+    fn observe_trail(&self) -> bool {
+        false
+    }
+
+    /// Called by the solver after `learn`, so only for learned clauses up to `max_length`,
+    /// with the literals of the conflict clause, whether
+    /// each literal of the trail was propagated (1) or decided or fixed (0), and the trail at
+    /// the conflict. Root level literals of the conflict clause are left out.
     #[allow(unused_variables)]
+    #[inline(always)]
     fn learn_trail(&mut self, conflict_literals: &[i32], is_propagated: &[i32], trail: &[i32]) {}
+
+    /// Called by the solver after a restart.
+    #[inline(always)]
+    fn on_restart(&mut self) {}
+
+    /// Called by the solver after deciding a literal, with the decision level it opened.
+    /// Assumptions are not reported. Requires `observe_trail`.
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn on_decision(&mut self, lit: i32, level: i32) {}
+
+    /// Called by the solver after backtracking to the decision level. Requires `observe_trail`.
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn on_backtrack(&mut self, level: i32) {}
+
+    /// Called by the solver when a reduction deletes learned clauses.
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn on_reduce(&mut self, deleted_clauses: &[Vec<i32>]) {}
+
+    /// Called by the solver after `learn` and `learn_trail` with the reason clauses resolved in
+    /// conflict analysis, in order. Each step is the pivot literal and the literals of the
+    /// reason, starting with the conflict clause with the pivot 0. Root level literals are left
+    /// out. The minimization steps resolve the literals removed by minimization out of the 1st
+    /// UIP clause the same way, and are empty if no literal was removed.
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn learn_resolution(&mut self, steps: &[(i32, Vec<i32>)], minimization: &[(i32, Vec<i32>)]) {}
+}
+
+/// An external propagator taking part in the search of the solver, as in the IPASIR-UP
+/// interface. It is only notified about the variables it observes, and its literals have to
+/// be observed.
+pub trait ExternalPropagator {
+    /// Returns whether the propagator only checks complete models, so the solver does not ask
+    /// it for propagations, decisions or clauses during search.
+    fn is_lazy(&self) -> bool {
+        false
+    }
+
+    /// Called when the literal is assigned, `is_fixed` if on the root level.
+    fn notify_assignment(&mut self, lit: i32, is_fixed: bool);
+
+    /// Called when a new decision level is opened.
+    fn notify_new_decision_level(&mut self);
+
+    /// Called when the solver backtracks to the decision level, unassigning all literals
+    /// above it.
+    fn notify_backtrack(&mut self, new_level: usize);
+
+    /// Called with the observed literals of a model found by the solver. Returns whether the
+    /// model is accepted. If not, the propagator has to add a clause falsified by the model.
+    fn check_found_model(&mut self, model: &[i32]) -> bool;
+
+    /// Returns a literal to decide, or 0 to leave the decision to the solver.
+    fn decide(&mut self) -> i32 {
+        0
+    }
+
+    /// Returns a literal implied by the current assignment, or 0 if there is none.
+    fn propagate(&mut self) -> i32 {
+        0
+    }
+
+    /// Returns the next literal of the reason clause of a propagated literal, which must
+    /// contain it with all other literals false, and 0 at the end of the clause. Only called
+    /// when the solver needs the reason, possibly after further assignments.
+    #[allow(unused_variables)]
+    fn add_reason_clause_lit(&mut self, propagated_lit: i32) -> i32 {
+        0
+    }
+
+    /// Returns whether the propagator has a clause to add.
+    fn has_external_clause(&mut self) -> bool {
+        false
+    }
+
+    /// Returns the next literal of the clause to add, and 0 at the end of the clause.
+    fn add_external_clause_lit(&mut self) -> i32 {
+        0
+    }
 }
 
 /// Callbacks implementing a simple timeout.
@@ -524,7 +1115,9 @@ impl fmt::Display for Error {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::thread;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::{thread, time::Duration};
 
     #[test]
     fn solver() {
@@ -626,6 +1219,29 @@ mod tests {
         assert_eq!(result, Some(false));
     }
 
+    #[test]
+    fn propagation_limit() {
+        let mut sat = pigeon_hole(5);
+        sat.set_limit("propagations", 100).unwrap();
+        let result = sat.solve();
+        assert_eq!(result, None);
+        assert!(sat.stats().propagations >= 100);
+        // The limit only holds for one solve
+        let result = sat.solve();
+        assert_eq!(result, Some(false));
+    }
+
+    #[test]
+    fn time_limit() {
+        let mut sat = pigeon_hole(9);
+        let started = Instant::now();
+        sat.set_limit("time", 1).unwrap();
+        let result = sat.solve();
+        // Only a generous upper bound, so that a loaded machine does not fail the test
+        assert!(result.is_none() || result == Some(false));
+        assert!(started.elapsed() < Duration::from_secs(30));
+    }
+
     #[test]
     fn bad_limit() {
         let mut sat = pigeon_hole(5);
@@ -633,6 +1249,411 @@ mod tests {
         assert!(sat.set_limit("bad", 0) == Err(Error::new("unknown limit")));
     }
 
+    #[test]
+    fn proof() {
+        let mut path = std::env::temp_dir();
+        path.push("pigeon4.drat");
+
+        let mut sat: Solver = Solver::new();
+        assert!(sat.trace_proof(&path, false).is_ok());
+        assert!(sat.trace_proof(&path, false).is_err());
+        for i in 0..5 {
+            sat.add_clause((0..4).map(|j| 1 + i * 4 + j));
+        }
+        for i1 in 0..5 {
+            for i2 in (i1 + 1)..5 {
+                for j in 0..4 {
+                    sat.add_clause([-(1 + i1 * 4 + j), -(1 + i2 * 4 + j)]);
+                }
+            }
+        }
+        assert_eq!(sat.solve(), Some(false));
+        assert!(sat.close_proof().is_ok());
+        assert!(sat.close_proof().is_err());
+
+        let proof = std::fs::read_to_string(&path).unwrap();
+        assert!(proof.lines().any(|line| line == "0"));
+
+        let mut sat: Solver = Solver::new();
+        sat.add_clause([1, 2]);
+        assert!(sat.trace_proof(&path, false).is_err());
+    }
+
+    struct RestartCounter {
+        restarts: i64,
+    }
+
+    impl Callbacks for RestartCounter {
+        fn on_restart(&mut self) {
+            self.restarts += 1;
+        }
+    }
+
+    #[test]
+    fn restart_callback() {
+        let mut sat: Solver<RestartCounter> = Solver::new();
+        for i in 0..8 {
+            sat.add_clause((0..7).map(|j| 1 + i * 7 + j));
+        }
+        for i1 in 0..8 {
+            for i2 in (i1 + 1)..8 {
+                for j in 0..7 {
+                    sat.add_clause([-(1 + i1 * 7 + j), -(1 + i2 * 7 + j)]);
+                }
+            }
+        }
+        sat.set_callbacks(Some(RestartCounter { restarts: 0 }));
+        assert_eq!(sat.solve(), Some(false));
+
+        let restarts = sat.stats().restarts;
+        assert!(restarts > 0);
+        assert_eq!(sat.get_callbacks().unwrap().restarts, restarts);
+    }
+
+    #[derive(Default)]
+    struct EventCounter {
+        decisions: i64,
+        backtracks: i64,
+        deleted: usize,
+    }
+
+    impl Callbacks for EventCounter {
+        fn observe_trail(&self) -> bool {
+            true
+        }
+
+        fn on_decision(&mut self, _lit: i32, level: i32) {
+            assert!(level > 0);
+            self.decisions += 1;
+        }
+
+        fn on_backtrack(&mut self, level: i32) {
+            assert!(level >= 0);
+            self.backtracks += 1;
+        }
+
+        fn on_reduce(&mut self, deleted_clauses: &[Vec<i32>]) {
+            assert!(deleted_clauses.iter().all(|clause| !clause.is_empty()));
+            self.deleted += deleted_clauses.len();
+        }
+    }
+
+    #[test]
+    fn search_event_callbacks() {
+        let mut sat: Solver<EventCounter> = Solver::new();
+        for i in 0..9 {
+            sat.add_clause((0..8).map(|j| 1 + i * 8 + j));
+        }
+        for i1 in 0..9 {
+            for i2 in (i1 + 1)..9 {
+                for j in 0..8 {
+                    sat.add_clause([-(1 + i1 * 8 + j), -(1 + i2 * 8 + j)]);
+                }
+            }
+        }
+        sat.set_callbacks(Some(EventCounter::default()));
+        assert_eq!(sat.solve(), Some(false));
+
+        let decisions = sat.stats().decisions;
+        let counter = sat.get_callbacks().unwrap();
+        assert_eq!(counter.decisions, decisions);
+        assert!(counter.backtracks > 0);
+        assert!(counter.deleted > 0);
+    }
+
+    #[derive(Default)]
+    struct TrailChecker {
+        learned: usize,
+        conflicts: usize,
+    }
+
+    impl Callbacks for TrailChecker {
+        fn observe_trail(&self) -> bool {
+            true
+        }
+
+        fn max_length(&self) -> i32 {
+            i32::MAX
+        }
+
+        fn learn(&mut self, clause: &[i32]) {
+            // The empty clause is learned without conflict analysis
+            if !clause.is_empty() {
+                self.learned += 1;
+            }
+        }
+
+        fn learn_trail(&mut self, conflict_literals: &[i32], is_propagated: &[i32], trail: &[i32]) {
+            assert_eq!(is_propagated.len(), trail.len());
+            assert!(!conflict_literals.is_empty());
+            // The conflict clause is falsified by the trail
+            for lit in conflict_literals {
+                assert!(trail.contains(&-lit));
+            }
+            self.conflicts += 1;
+        }
+    }
+
+    #[test]
+    fn trail_callback() {
+        let mut sat: Solver<TrailChecker> = Solver::new();
+        sat.set_callbacks(Some(TrailChecker::default()));
+        for i in 0..7 {
+            sat.add_clause((0..6).map(|j| 1 + i * 6 + j));
+        }
+        for i1 in 0..7 {
+            for i2 in (i1 + 1)..7 {
+                for j in 0..6 {
+                    sat.add_clause([-(1 + i1 * 6 + j), -(1 + i2 * 6 + j)]);
+                }
+            }
+        }
+        assert_eq!(sat.solve(), Some(false));
+
+        let checker = sat.get_callbacks().unwrap();
+        assert!(checker.conflicts > 0);
+        assert_eq!(checker.conflicts, checker.learned);
+    }
+
+    /// Puts each pigeon in at most one hole by propagation, explaining the propagations on
+    /// demand
+    struct HolePropagator {
+        num_vars: i32,
+        holes: i32,
+        /// The pigeon literal in each hole
+        occupants: Vec<Option<i32>>,
+        /// The occupying literals in assignment order
+        occupied: Vec<i32>,
+        /// The length of `occupied` at each decision level
+        levels: Vec<usize>,
+        /// The propagations not yet passed to the solver with their causes
+        pending: Vec<(i32, i32)>,
+        /// The cause of each propagated literal
+        causes: std::collections::HashMap<i32, i32>,
+        clauses: Vec<Vec<i32>>,
+        reason: Vec<i32>,
+        reasons: Arc<AtomicUsize>,
+    }
+
+    impl HolePropagator {
+        fn new(pigeons: i32, holes: i32, reasons: Arc<AtomicUsize>) -> Self {
+            HolePropagator {
+                num_vars: pigeons * holes,
+                holes,
+                occupants: vec![None; holes as usize],
+                occupied: Vec::new(),
+                levels: Vec::new(),
+                pending: Vec::new(),
+                causes: Default::default(),
+                clauses: Vec::new(),
+                reason: Vec::new(),
+                reasons,
+            }
+        }
+
+        fn hole(&self, lit: i32) -> usize {
+            ((lit.abs() - 1) % self.holes) as usize
+        }
+
+        fn pigeons(&self, hole: usize) -> impl Iterator<Item = i32> + '_ {
+            (1..)
+                .step_by(self.holes as usize)
+                .map(move |first| first + hole as i32)
+        }
+    }
+
+    impl ExternalPropagator for HolePropagator {
+        fn notify_assignment(&mut self, lit: i32, _is_fixed: bool) {
+            if lit < 0 {
+                return;
+            }
+            let hole = self.hole(lit);
+            if let Some(other) = self.occupants[hole] {
+                self.clauses.push(vec![0, -other, -lit]);
+                return;
+            }
+            self.occupants[hole] = Some(lit);
+            self.occupied.push(lit);
+            let others: Vec<i32> = self
+                .pigeons(hole)
+                .take_while(|&var| var <= self.num_vars)
+                .filter(|&var| var != lit)
+                .collect();
+            self.pending
+                .extend(others.into_iter().map(|var| (-var, lit)));
+        }
+
+        fn notify_new_decision_level(&mut self) {
+            self.levels.push(self.occupied.len());
+        }
+
+        fn notify_backtrack(&mut self, new_level: usize) {
+            if let Some(&len) = self.levels.get(new_level) {
+                for lit in self.occupied.split_off(len) {
+                    let hole = self.hole(lit);
+                    self.occupants[hole] = None;
+                }
+            }
+            self.levels.truncate(new_level);
+            let occupants = &self.occupants;
+            self.pending
+                .retain(|&(_, cause)| occupants.contains(&Some(cause)));
+        }
+
+        fn check_found_model(&mut self, model: &[i32]) -> bool {
+            for (i, &lit) in model.iter().enumerate() {
+                for &other in &model[i + 1..] {
+                    if lit > 0 && other > 0 && self.hole(lit) == self.hole(other) {
+                        self.clauses.push(vec![0, -other, -lit]);
+                        return false;
+                    }
+                }
+            }
+            true
+        }
+
+        fn propagate(&mut self) -> i32 {
+            let Some((lit, cause)) = self.pending.pop() else {
+                return 0;
+            };
+            self.causes.insert(lit, cause);
+            lit
+        }
+
+        fn add_reason_clause_lit(&mut self, propagated_lit: i32) -> i32 {
+            if self.reason.is_empty() {
+                self.reasons.fetch_add(1, Ordering::Relaxed);
+                let cause = self.causes[&propagated_lit];
+                self.reason = vec![0, -cause, propagated_lit];
+            }
+            let lit = self.reason.pop().unwrap();
+            if lit == 0 {
+                self.reason.clear();
+            }
+            lit
+        }
+
+        fn has_external_clause(&mut self) -> bool {
+            !self.clauses.is_empty()
+        }
+
+        fn add_external_clause_lit(&mut self) -> i32 {
+            let clause = self.clauses.last_mut().unwrap();
+            let lit = clause.pop().unwrap();
+            if lit == 0 {
+                self.clauses.pop();
+            }
+            lit
+        }
+    }
+
+    fn propagated_pigeon_hole(pigeons: i32, holes: i32, reasons: Arc<AtomicUsize>) -> Solver {
+        let mut sat: Solver = Solver::new();
+        for i in 0..pigeons {
+            sat.add_clause((0..holes).map(|j| 1 + i * holes + j));
+        }
+        sat.connect_external_propagator(HolePropagator::new(pigeons, holes, reasons));
+        for var in 1..=pigeons * holes {
+            sat.add_observed_var(var);
+        }
+        sat
+    }
+
+    #[test]
+    fn external_propagator() {
+        let reasons = Arc::new(AtomicUsize::new(0));
+        let mut sat = propagated_pigeon_hole(6, 5, reasons.clone());
+        assert_eq!(sat.solve(), Some(false));
+        assert!(reasons.load(Ordering::Relaxed) > 0);
+        assert!(reasons.load(Ordering::Relaxed) < sat.stats().propagations as usize);
+
+        let mut sat = propagated_pigeon_hole(5, 5, Default::default());
+        assert_eq!(sat.solve(), Some(true));
+        for hole in 0..5 {
+            let occupied = (0..5).filter(|i| sat.value(1 + i * 5 + hole) == Some(true));
+            assert_eq!(occupied.count(), 1);
+        }
+
+        // Incremental solving with assumptions, keeping the clauses of the propagator after
+        // disconnecting it
+        assert_eq!(sat.solve_with([1, 6].iter().copied()), Some(false));
+        assert!(sat.failed(1) && sat.failed(6));
+        assert_eq!(sat.solve_with([1, 7].iter().copied()), Some(true));
+        sat.disconnect_external_propagator();
+        assert_eq!(sat.solve_with([1, 7].iter().copied()), Some(true));
+    }
+
+    #[derive(Default)]
+    struct ResolutionRecorder {
+        clauses: Vec<Vec<i32>>,
+        derivations: Vec<Vec<(i32, Vec<i32>)>>,
+        minimizations: Vec<Vec<(i32, Vec<i32>)>>,
+    }
+
+    impl Callbacks for ResolutionRecorder {
+        fn max_length(&self) -> i32 {
+            i32::MAX
+        }
+
+        fn learn(&mut self, clause: &[i32]) {
+            self.clauses.push(clause.to_vec());
+        }
+
+        fn learn_resolution(
+            &mut self,
+            steps: &[(i32, Vec<i32>)],
+            minimization: &[(i32, Vec<i32>)],
+        ) {
+            self.derivations.push(steps.to_vec());
+            self.minimizations.push(minimization.to_vec());
+        }
+    }
+
+    #[test]
+    fn resolution_callback() {
+        let mut sat: Solver<ResolutionRecorder> = Solver::new();
+        for i in 0..6 {
+            sat.add_clause((0..5).map(|j| 1 + i * 5 + j));
+        }
+        for i1 in 0..6 {
+            for i2 in (i1 + 1)..6 {
+                for j in 0..5 {
+                    sat.add_clause([-(1 + i1 * 5 + j), -(1 + i2 * 5 + j)]);
+                }
+            }
+        }
+        sat.set_callbacks(Some(ResolutionRecorder::default()));
+        assert_eq!(sat.solve(), Some(false));
+
+        let recorder = sat.get_callbacks().unwrap();
+        assert!(!recorder.derivations.is_empty());
+        assert!(recorder.minimizations.iter().any(|steps| !steps.is_empty()));
+        for ((clause, steps), minimization) in recorder
+            .clauses
+            .iter()
+            .zip(recorder.derivations.iter())
+            .zip(recorder.minimizations.iter())
+        {
+            assert_eq!(steps[0].0, 0);
+            // Resolving the reasons in order gives the 1st UIP clause, and resolving the
+            // minimization steps after them gives the learned clause
+            let mut resolvent = steps[0].1.clone();
+            for (pivot, reason) in steps[1..].iter().chain(minimization) {
+                assert!(resolvent.contains(&-pivot) && reason.contains(pivot));
+                resolvent.retain(|&lit| lit != -pivot);
+                for &lit in reason {
+                    if lit != *pivot && !resolvent.contains(&lit) {
+                        resolvent.push(lit);
+                    }
+                }
+            }
+            resolvent.sort();
+            let mut clause = clause.clone();
+            clause.sort();
+            assert_eq!(resolvent, clause);
+        }
+    }
+
     #[test]
     fn moving() {
         let mut sat = pigeon_hole(5);
@@ -0,0 +1,116 @@
+//! Decision counts and activities of the solver's variables, mapped back to the sudoku
+
+use cadical::Solver;
+
+use crate::{app_state::EncodingType, cadical_wrapper::CadicalCallbackWrapper, cnf::CnfVariable};
+
+/// Decision count and activity of a single variable
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariableEntry {
+    pub variable: CnfVariable,
+    pub decisions: i64,
+    pub activity: f64,
+}
+
+/// Branching information of all variables of a solver.
+/// The decision counts of a reused solver include the decisions of earlier solves.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VariableActivity {
+    pub variables: Vec<VariableEntry>,
+}
+
+impl VariableActivity {
+    pub fn from_solver(solver: &Solver<CadicalCallbackWrapper>, encoding: &EncodingType) -> Self {
+        let variables = (1..=solver.max_variable())
+            .map(|variable| VariableEntry {
+                variable: CnfVariable::from_cnf(variable, encoding),
+                decisions: solver.variable_decisions(variable),
+                activity: solver.variable_activity(variable),
+            })
+            .collect();
+        Self { variables }
+    }
+
+    /// Variables that have been decided at least once, the most decided first
+    pub fn most_decided(&self) -> Vec<&VariableEntry> {
+        let mut decided = self
+            .variables
+            .iter()
+            .filter(|entry| entry.decisions > 0)
+            .collect::<Vec<&VariableEntry>>();
+        decided.sort_by(|a, b| {
+            b.decisions
+                .cmp(&a.decisions)
+                .then(b.activity.total_cmp(&a.activity))
+        });
+        decided
+    }
+
+    /// Activity of the variable relative to the most active variable, between 0 and 1
+    pub fn relative_activity(&self, entry: &VariableEntry) -> f64 {
+        let max_activity = self
+            .variables
+            .iter()
+            .map(|entry| entry.activity)
+            .fold(0.0, f64::max);
+        if max_activity > 0.0 {
+            entry.activity / max_activity
+        } else {
+            0.0
+        }
+    }
+
+    /// Number of decisions on the variables of each cell.
+    /// Indexed from 0, so `decisions[0][0]` is cell (1, 1).
+    pub fn cell_decisions(&self) -> Vec<Vec<usize>> {
+        let mut decisions = vec![vec![0; 9]; 9];
+        for entry in &self.variables {
+            for (row, col) in entry.variable.cells() {
+                decisions[row as usize - 1][col as usize - 1] += entry.decisions as usize;
+            }
+        }
+        decisions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sudoku::{get_sudoku, solve_sudoku};
+
+    use super::*;
+
+    #[test]
+    fn test_variable_activity() {
+        let clues = get_sudoku("data/hardest-ever-sudoku.txt".to_string()).unwrap();
+        let encoding = EncodingType::Decimal {
+            cell_at_least_one: true,
+            cell_at_most_one: false,
+            sudoku_has_all_values: false,
+            sudoku_has_unique_values: true,
+        };
+        let mut solver = Solver::with_config("plain").unwrap();
+        solve_sudoku(&clues, &mut solver, &encoding).unwrap();
+
+        let activity = VariableActivity::from_solver(&solver, &encoding);
+        assert_eq!(activity.variables.len(), 9 * 9 * 9);
+
+        let decisions: i64 = activity.variables.iter().map(|entry| entry.decisions).sum();
+        assert_eq!(solver.stats().decisions, decisions);
+        let cell_decisions = activity
+            .cell_decisions()
+            .into_iter()
+            .flatten()
+            .sum::<usize>();
+        assert_eq!(cell_decisions as i64, decisions);
+
+        let most_decided = activity.most_decided();
+        assert!(!most_decided.is_empty());
+        assert!(most_decided
+            .windows(2)
+            .all(|entries| entries[0].decisions >= entries[1].decisions));
+        assert!(activity
+            .variables
+            .iter()
+            .all(|entry| (0.0..=1.0).contains(&activity.relative_activity(entry))));
+    }
+}
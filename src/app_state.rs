@@ -1,17 +1,28 @@
 //! State info for the main app struct SATApp
 
-use std::sync::{Arc, Mutex};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    backend::{Backend, SolveLimits, SolverBackend},
     cnf::{binary_encoding, decimal_encoding, CnfVariable},
+    events::SolverEvent,
     filtering::ListFilter,
+    heatmap::HeatmapMetric,
     parse_numeric_input,
+    replay::Replay,
     statistics::Statistics,
+    timeline::TimelineSeries,
     warning::Warning,
-    CadicalCallbackWrapper, ConstraintList, Solver, Trail,
+    ConstraintList, Trail,
 };
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 /// Enum denoting which encoding is used for the CNF variables.
 /// Decimal encoding also contains options for the ruleset.
 pub enum EncodingType {
@@ -45,7 +56,7 @@ impl EncodingType {
 
     pub fn get_cell_value(
         &self,
-        solver: &Solver<CadicalCallbackWrapper>,
+        solver: &(impl SolverBackend + ?Sized),
         row: i32,
         col: i32,
     ) -> i32 {
@@ -55,9 +66,54 @@ impl EncodingType {
         }
     }
 
+    /// All values of a cell in the solver's model. Can be empty or have several values
+    /// if the encoding rules are incomplete.
+    pub fn get_cell_values(
+        &self,
+        solver: &(impl SolverBackend + ?Sized),
+        row: i32,
+        col: i32,
+    ) -> Vec<i32> {
+        match self {
+            EncodingType::Decimal { .. } => decimal_encoding::get_cell_values(solver, row, col),
+            EncodingType::Binary => vec![binary_encoding::get_cell_value(solver, row, col)],
+        }
+    }
+
+    /// Clauses that limit the values of each cell to its candidates (pencil marks).
+    /// Cells without candidates are not limited.
+    pub fn candidate_clauses(&self, candidates: &[Vec<Vec<i32>>]) -> Vec<Vec<i32>> {
+        let mut clauses = Vec::new();
+        for (row, line) in candidates.iter().enumerate() {
+            for (col, cell_candidates) in line.iter().enumerate() {
+                if cell_candidates.is_empty() {
+                    continue;
+                }
+                let (row, col) = (row as i32 + 1, col as i32 + 1);
+                clauses.extend(match self {
+                    EncodingType::Decimal { .. } => {
+                        decimal_encoding::candidate_clauses(row, col, cell_candidates)
+                    }
+                    EncodingType::Binary => {
+                        binary_encoding::candidate_clauses(row, col, cell_candidates)
+                    }
+                });
+            }
+        }
+        clauses
+    }
+
+    /// Literals that set the value of a cell, used for adding clues as assumptions
+    pub fn clue_literals(&self, row: i32, col: i32, val: i32) -> Vec<i32> {
+        match self {
+            EncodingType::Decimal { .. } => decimal_encoding::clue_literals(row, col, val),
+            EncodingType::Binary => binary_encoding::clue_literals(row, col, val),
+        }
+    }
+
     pub fn fixed(
         &self,
-        solver: &Solver<CadicalCallbackWrapper>,
+        solver: &(impl SolverBackend + ?Sized),
         row: i32,
         col: i32,
         val: i32,
@@ -102,9 +158,39 @@ pub struct AppState {
     pub encoding: EncodingType, // Currently chosen encoding used for converting sudoku to CNF
     pub show_conflict_literals: bool,
     pub show_trail: bool,
-    pub editor_active: bool, // Is sudoku input mode active
+    pub show_trail_panel: bool, // Show the trail of the selected constraint as a list next to the grid
+    pub hovered_trail_literal: Option<i32>, // Literal hovered in the trail panel, its cells are highlighted
+    pub editor_active: bool,                // Is sudoku input mode active
     pub highlight_fixed_literals: bool,
     pub show_statistics: bool,
+    pub show_proof: bool,
+    pub show_human_solver: bool,
+    pub show_timeline: bool,
+    pub show_activity: bool,
+    pub show_analysis: bool,
+    pub show_event_log: bool,
+    pub event_kinds: Vec<&'static str>, // Kinds of events shown in the event log
+    pub analysis_step: usize,           // Index of the last shown step of the conflict analysis
+    pub timeline_series: Vec<TimelineSeries>, // Series shown on the timeline chart
+    pub scroll_to_constraint: bool, // Scroll the list to the selected constraint on the next frame
+    pub heatmap: Option<HeatmapMetric>, // Metric shown as a heatmap on the grid, None if the overlay is off
+    pub replay: Replay,                 // Replay of the conflicts of the latest solve
+    pub compared_constraint_index: Option<usize>, // Constraint whose trail is compared with the selected one, index in the full list
+    pub candidate_mode: bool, // Digits typed in the editor toggle candidates instead of setting clues
+    pub encode_candidates: bool, // Add the candidates as clauses when solving
+    pub incremental: bool,    // Reuse the solver and its learned clauses between solves
+    pub backend: Backend,     // Solver used for solving
+    pub compare_solvers: bool, // Process all configurations with every solver
+    pub external_solver: Option<PathBuf>, // Solver executable also used to process all configurations
+    pub external_timeout_input: String,   // Seconds an external solver may run
+    pub time_limit_input: String,         // Limits of each solve, empty if there is no limit
+    pub conflict_limit_input: String,
+    pub decision_limit_input: String,
+    pub propagation_limit_input: String,
+    pub limit_reached: bool, // The latest solve stopped at a limit before it found out the result
+    pub reused_constraint_count: usize, // Number of learned constraints that came from earlier solves
+    pub rendered_indexes: Vec<usize>, // Indexes of the rendered constraints in the full constraint list
+    pub selected_proof_step: Option<usize>, // Index of the proof step shown on the grid
     pub history: Arc<Mutex<Vec<Statistics>>>,
     pub highlight_decided_vars: bool,
     pub show_warning: Warning,
@@ -139,11 +225,41 @@ impl AppState {
             show_solved_sudoku: true,
             show_conflict_literals: false,
             show_trail: false,
+            show_trail_panel: false,
+            hovered_trail_literal: None,
             little_number_constraints: Vec::new(),
             encoding,
             editor_active: false,
             highlight_fixed_literals: false,
             show_statistics: false,
+            show_proof: false,
+            show_human_solver: false,
+            show_timeline: false,
+            show_activity: false,
+            show_analysis: false,
+            show_event_log: false,
+            event_kinds: SolverEvent::kinds().to_vec(),
+            analysis_step: 0,
+            timeline_series: TimelineSeries::all().to_vec(),
+            scroll_to_constraint: false,
+            heatmap: None,
+            replay: Replay::default(),
+            compared_constraint_index: None,
+            candidate_mode: false,
+            encode_candidates: false,
+            incremental: false,
+            backend: Backend::Cadical,
+            compare_solvers: false,
+            external_solver: None,
+            external_timeout_input: "10".to_string(),
+            time_limit_input: String::new(),
+            conflict_limit_input: String::new(),
+            decision_limit_input: String::new(),
+            propagation_limit_input: String::new(),
+            limit_reached: false,
+            reused_constraint_count: 0,
+            rendered_indexes: Vec::new(),
+            selected_proof_step: None,
             history: Arc::new(Mutex::new(Vec::new())),
             highlight_decided_vars: false,
             show_warning: Warning::new(),
@@ -152,6 +268,25 @@ impl AppState {
         }
     }
 
+    /// Limits of each solve from the inputs. Inputs that are not positive numbers are no limit.
+    pub fn solve_limits(&self) -> SolveLimits {
+        SolveLimits {
+            seconds: parse_numeric_input(&self.time_limit_input),
+            conflicts: parse_numeric_input(&self.conflict_limit_input),
+            decisions: parse_numeric_input(&self.decision_limit_input),
+            propagations: parse_numeric_input(&self.propagation_limit_input),
+        }
+    }
+
+    /// Fills the limit inputs, for limits given on the command line
+    pub fn set_solve_limits(&mut self, limits: &SolveLimits) {
+        let input = |limit: Option<i32>| limit.map_or(String::new(), |limit| limit.to_string());
+        self.time_limit_input = input(limits.seconds);
+        self.conflict_limit_input = input(limits.conflicts);
+        self.decision_limit_input = input(limits.decisions);
+        self.propagation_limit_input = input(limits.propagations);
+    }
+
     /// Get the filtered and paged list of constraints as CNF variables
     /// Get the filtered and paged list of trails as a single Trail struct
     /// Updates data that should be refreshed when constraints may have changed
@@ -161,6 +296,9 @@ impl AppState {
             .get_filtered(self.page_number as usize, self.page_length);
 
         self.filtered_length = length;
+        self.rendered_indexes = self
+            .filter
+            .get_filtered_indexes(self.page_number as usize, self.page_length);
 
         self.count_pages();
 
@@ -179,6 +317,11 @@ impl AppState {
         (enum_constraints, trail)
     }
 
+    /// Position of a constraint in the filtered list, None if the filters hide it
+    pub fn filtered_position(&self, index: usize) -> Option<usize> {
+        self.filter.filtered_position(index)
+    }
+
     /// Resets filtering and paging data
     pub fn reinit(&mut self) {
         self.clear_filters();
@@ -216,7 +359,7 @@ impl AppState {
 
     fn count_pages(&mut self) {
         self.page_count = (self.filtered_length / (self.page_length)) as i32;
-        self.page_count += if self.filtered_length % self.page_length == 0 {
+        self.page_count += if self.filtered_length.is_multiple_of(self.page_length) {
             0
         } else {
             1
@@ -299,6 +442,12 @@ impl AppState {
         self.trail_var_is_propagated = Some(var_is_propagated);
     }
 
+    /// Index of the selected constraint in the full list of constraints
+    pub fn selected_constraint_index(&self) -> Option<usize> {
+        self.clicked_constraint_index
+            .and_then(|row| self.rendered_indexes.get(row).copied())
+    }
+
     pub fn get_encoding_type(&mut self) -> &str {
         match self.encoding {
             EncodingType::Decimal { .. } => "Decimal",
@@ -315,7 +464,72 @@ impl AppState {
 mod tests {
     use super::*;
     use crate::app_state::AppState;
+    use crate::sudoku::{get_sudoku, solve_sudoku};
+    use crate::{CadicalCallbackWrapper, Solver};
     use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn test_candidate_clauses() {
+        let encodings = [
+            EncodingType::Decimal {
+                cell_at_least_one: true,
+                cell_at_most_one: false,
+                sudoku_has_all_values: false,
+                sudoku_has_unique_values: true,
+            },
+            EncodingType::Binary,
+        ];
+        for encoding in encodings {
+            let clues = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+            let solved = solve_sudoku(
+                &clues,
+                &mut Solver::<CadicalCallbackWrapper>::new(),
+                &encoding,
+            )
+            .unwrap();
+            let value = solved[0][0].unwrap();
+
+            // The solution stays the same if the correct value is a candidate
+            let mut candidates = vec![vec![Vec::new(); 9]; 9];
+            candidates[0][0] = vec![value, value % 9 + 1];
+            let mut solver: Solver<CadicalCallbackWrapper> = Solver::new();
+            for clause in encoding.candidate_clauses(&candidates) {
+                solver.add_clause(clause);
+            }
+            assert_eq!(solve_sudoku(&clues, &mut solver, &encoding), Ok(solved));
+
+            candidates[0][0] = vec![value % 9 + 1];
+            let mut solver: Solver<CadicalCallbackWrapper> = Solver::new();
+            for clause in encoding.candidate_clauses(&candidates) {
+                solver.add_clause(clause);
+            }
+            assert!(solve_sudoku(&clues, &mut solver, &encoding).is_err());
+        }
+    }
+
+    #[test]
+    fn test_solve_limits() {
+        let mut state = AppState::new(ConstraintList::new(), Trail::new());
+        assert_eq!(state.solve_limits(), SolveLimits::default());
+
+        state.time_limit_input = "5".to_string();
+        state.conflict_limit_input = "abc".to_string();
+        state.propagation_limit_input = "0".to_string();
+        let limits = state.solve_limits();
+        assert_eq!(limits.seconds, Some(5));
+        assert_eq!(limits.conflicts, None);
+        assert_eq!(limits.propagations, None);
+
+        let limits = SolveLimits {
+            decisions: Some(100),
+            ..Default::default()
+        };
+        state.set_solve_limits(&limits);
+        assert_eq!(state.decision_limit_input, "100");
+        assert_eq!(state.time_limit_input, "");
+        assert_eq!(state.solve_limits(), limits);
+    }
+
     #[test]
     fn test_reinit() {
         let constraints = ConstraintList::_new(Rc::new(RefCell::new(vec![
@@ -0,0 +1,291 @@
+//! The operations of a SAT solver that solving a sudoku needs, so that the app can use
+//! either cadical or the CDCL solver written in Rust
+
+use cadical::{CadicalStats, Callbacks, Solver};
+
+use crate::{cadical_wrapper::CadicalCallbackWrapper, cdcl::CdclSolver, parse_numeric_input};
+
+pub trait SolverBackend {
+    /// Type of the callbacks the solver reports its search to
+    type Callbacks;
+
+    /// Adds a clause, which stays in the solver for all later solves
+    fn add_clause(&mut self, clause: Vec<i32>);
+
+    /// Solves the formula with the assumptions, which only hold for this solve.
+    /// Returns None if the solver was terminated.
+    fn solve_with(&mut self, assumptions: Vec<i32>) -> Option<bool>;
+
+    fn solve(&mut self) -> Option<bool> {
+        self.solve_with(Vec::new())
+    }
+
+    /// Result of the latest solve, None if there is none
+    fn status(&self) -> Option<bool>;
+
+    /// Value of the literal in the solution of the latest solve
+    fn value(&self, literal: i32) -> Option<bool>;
+
+    /// Returns 1 if the literal is implied by the formula, -1 if its negation is and 0 otherwise
+    fn fixed(&self, literal: i32) -> i32;
+
+    fn stats(&mut self) -> CadicalStats;
+
+    /// Sets the callbacks that are called when the solver learns a clause, restarts and so on
+    fn set_callbacks(&mut self, callbacks: Option<Self::Callbacks>);
+
+    /// Sets the limits of the next solve. A solve that reaches a limit returns None.
+    fn set_limits(&mut self, limits: &SolveLimits);
+}
+
+/// Limits of a single solve, None if there is no limit
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SolveLimits {
+    pub seconds: Option<i32>, // Wall-clock time
+    pub conflicts: Option<i32>,
+    pub decisions: Option<i32>,
+    pub propagations: Option<i32>,
+}
+
+impl SolveLimits {
+    /// The limits with the names `Solver::set_limit` of cadical uses
+    pub fn named(&self) -> [(&'static str, Option<i32>); 4] {
+        [
+            ("time", self.seconds),
+            ("conflicts", self.conflicts),
+            ("decisions", self.decisions),
+            ("propagations", self.propagations),
+        ]
+    }
+
+    /// Parses the limits from command line arguments of the form `--conflicts 1000` or
+    /// `--conflicts=1000`. The time limit is given with `--time` in seconds.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut limits = SolveLimits::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+            let limit = match name.as_str() {
+                "--time" => &mut limits.seconds,
+                "--conflicts" => &mut limits.conflicts,
+                "--decisions" => &mut limits.decisions,
+                "--propagations" => &mut limits.propagations,
+                _ => {
+                    return Err(format!(
+                        "Unknown argument {}. The arguments are --time, --conflicts, \
+                        --decisions and --propagations, each followed by a limit.",
+                        arg
+                    ))
+                }
+            };
+            let value = value
+                .or_else(|| args.next())
+                .ok_or(format!("{} needs a limit", name))?;
+            *limit = Some(
+                parse_numeric_input(&value)
+                    .ok_or(format!("The limit of {} has to be a positive number", name))?,
+            );
+        }
+        Ok(limits)
+    }
+}
+
+/// A solver of any backend that reports to the app's callbacks
+pub type BoxedSolver = Box<dyn SolverBackend<Callbacks = CadicalCallbackWrapper>>;
+
+impl<C: Callbacks> SolverBackend for Solver<C> {
+    type Callbacks = C;
+
+    fn add_clause(&mut self, clause: Vec<i32>) {
+        Solver::add_clause(self, clause);
+    }
+
+    fn solve_with(&mut self, assumptions: Vec<i32>) -> Option<bool> {
+        Solver::solve_with(self, assumptions.into_iter())
+    }
+
+    fn status(&self) -> Option<bool> {
+        Solver::status(self)
+    }
+
+    fn value(&self, literal: i32) -> Option<bool> {
+        Solver::value(self, literal)
+    }
+
+    fn fixed(&self, literal: i32) -> i32 {
+        Solver::fixed(self, literal)
+    }
+
+    fn stats(&mut self) -> CadicalStats {
+        Solver::stats(self)
+    }
+
+    fn set_callbacks(&mut self, callbacks: Option<C>) {
+        Solver::set_callbacks(self, callbacks);
+    }
+
+    fn set_limits(&mut self, limits: &SolveLimits) {
+        for (name, limit) in limits.named() {
+            // All the names are known to cadical
+            Solver::set_limit(self, name, limit.unwrap_or(-1)).unwrap();
+        }
+    }
+}
+
+impl<C: Callbacks> SolverBackend for CdclSolver<C> {
+    type Callbacks = C;
+
+    fn add_clause(&mut self, clause: Vec<i32>) {
+        CdclSolver::add_clause(self, clause);
+    }
+
+    fn solve_with(&mut self, assumptions: Vec<i32>) -> Option<bool> {
+        CdclSolver::solve_with(self, assumptions)
+    }
+
+    fn status(&self) -> Option<bool> {
+        CdclSolver::status(self)
+    }
+
+    fn value(&self, literal: i32) -> Option<bool> {
+        CdclSolver::value(self, literal)
+    }
+
+    fn fixed(&self, literal: i32) -> i32 {
+        CdclSolver::fixed(self, literal)
+    }
+
+    fn stats(&mut self) -> CadicalStats {
+        CdclSolver::stats(self)
+    }
+
+    fn set_callbacks(&mut self, callbacks: Option<C>) {
+        CdclSolver::set_callbacks(self, callbacks);
+    }
+
+    fn set_limits(&mut self, limits: &SolveLimits) {
+        CdclSolver::set_limits(self, *limits);
+    }
+}
+
+/// The solvers the app can use
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    Cadical,
+    Cdcl,
+}
+
+impl Backend {
+    pub fn all() -> [Backend; 2] {
+        [Backend::Cadical, Backend::Cdcl]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Cadical => "CaDiCaL",
+            Backend::Cdcl => "Rust CDCL",
+        }
+    }
+
+    /// A new solver of the backend. Cadical uses the plain configuration like elsewhere in the app.
+    pub fn solver(&self) -> BoxedSolver {
+        match self {
+            Backend::Cadical => Box::new(Solver::with_config("plain").unwrap()),
+            Backend::Cdcl => Box::new(CdclSolver::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app_state::EncodingType,
+        sudoku::{get_sudoku, solve_sudoku, SolveFailure},
+        ConstraintList, Trail,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_backends_agree() {
+        let clues = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        let encoding = EncodingType::Decimal {
+            cell_at_least_one: true,
+            cell_at_most_one: false,
+            sudoku_has_all_values: false,
+            sudoku_has_unique_values: true,
+        };
+
+        let mut solutions = Vec::new();
+        for backend in Backend::all() {
+            let constraints = ConstraintList::new();
+            let mut solver = backend.solver();
+            solver.set_callbacks(Some(CadicalCallbackWrapper::new(
+                constraints.clone(),
+                Trail::new(),
+            )));
+            let solved = solve_sudoku(&clues, solver.as_mut(), &encoding).unwrap();
+
+            assert_eq!(solver.status(), Some(true));
+            let stats = solver.stats();
+            assert_eq!(stats.learned_clauses, constraints.len() as i64);
+            assert!(stats.decisions > 0);
+            // The clues are unit clauses, so they are fixed
+            let (row, col) = (1, 8);
+            let clue = clues[row - 1][col - 1].unwrap();
+            assert!(encoding.fixed(solver.as_ref(), row as i32, col as i32, clue));
+            solutions.push(solved);
+        }
+        assert_eq!(solutions[0], solutions[1]);
+    }
+
+    #[test]
+    fn test_limits() {
+        let clues = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        let encoding = EncodingType::Decimal {
+            cell_at_least_one: true,
+            cell_at_most_one: false,
+            sudoku_has_all_values: false,
+            sudoku_has_unique_values: true,
+        };
+        let limits = SolveLimits {
+            decisions: Some(1),
+            ..Default::default()
+        };
+
+        for backend in Backend::all() {
+            let mut solver = backend.solver();
+            solver.set_limits(&limits);
+            assert_eq!(
+                solve_sudoku(&clues, solver.as_mut(), &encoding),
+                Err(SolveFailure::LimitReached)
+            );
+            assert_eq!(solver.status(), None);
+            assert!(solver.stats().decisions <= 1);
+
+            // The limits only hold for one solve
+            assert!(solver.solve().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_limits_from_args() {
+        let args = ["--time", "10", "--conflicts=500", "--propagations", "20000"];
+        assert_eq!(
+            SolveLimits::from_args(args.iter().map(|arg| arg.to_string())),
+            Ok(SolveLimits {
+                seconds: Some(10),
+                conflicts: Some(500),
+                decisions: None,
+                propagations: Some(20000),
+            })
+        );
+
+        assert!(SolveLimits::from_args(["--decisions".to_string()]).is_err());
+        assert!(SolveLimits::from_args(["--decisions=-1".to_string()]).is_err());
+        assert!(SolveLimits::from_args(["--restarts=3".to_string()]).is_err());
+    }
+}
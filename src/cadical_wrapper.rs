@@ -2,13 +2,17 @@
 
 use cadical::Callbacks;
 
-use crate::{ConstraintList, Trail};
+use crate::{
+    events::{EventLog, SolverEvent},
+    ConstraintList, Trail,
+};
 
 /// Wrapper for cadical callbacks. Handles communication between cadical and the app.
 #[derive(Clone)]
 pub struct CadicalCallbackWrapper {
     pub learned_clauses: ConstraintList,
     pub trail: Trail,
+    pub events: EventLog,
 }
 
 impl CadicalCallbackWrapper {
@@ -16,6 +20,7 @@ impl CadicalCallbackWrapper {
         Self {
             learned_clauses,
             trail,
+            events: EventLog::new(),
         }
     }
 }
@@ -34,7 +39,12 @@ impl Callbacks for CadicalCallbackWrapper {
     /// Returns the maximum length of clauses to be passed to `learn`. This
     /// methods will be called only once when `set_callbacks` is called.
     fn max_length(&self) -> i32 {
-        i32::max_value()
+        i32::MAX
+    }
+
+    /// Follow the trail of the solver for `learn_trail`, `on_decision` and `on_backtrack`
+    fn observe_trail(&self) -> bool {
+        true
     }
 
     /// Called by the solver when a new derived clause is learnt
@@ -43,6 +53,9 @@ impl Callbacks for CadicalCallbackWrapper {
         let tmp_vector: Vec<i32> = clause.to_vec();
         if !clause.is_empty() {
             self.learned_clauses.push(tmp_vector);
+            self.events.push(SolverEvent::Learn {
+                index: self.learned_clauses.len() - 1,
+            });
         }
     }
 
@@ -55,4 +68,40 @@ impl Callbacks for CadicalCallbackWrapper {
             is_propagated_vec,
         )
     }
+
+    /// Called after `learn_trail` with the reasons resolved in conflict analysis and minimization
+    fn learn_resolution(&mut self, steps: &[(i32, Vec<i32>)], minimization: &[(i32, Vec<i32>)]) {
+        self.trail
+            .push_resolution(steps.to_vec(), minimization.to_vec());
+    }
+
+    /// Called after the solver restarts
+    fn on_restart(&mut self) {
+        self.trail.push_restart();
+        self.events.push(SolverEvent::Restart);
+    }
+
+    /// Called after the solver decides a literal
+    fn on_decision(&mut self, lit: i32, level: i32) {
+        self.events.push(SolverEvent::Decision {
+            literal: lit,
+            level,
+        });
+    }
+
+    /// Called after the solver backtracks
+    fn on_backtrack(&mut self, level: i32) {
+        self.events.push(SolverEvent::Backtrack { level });
+    }
+
+    /// Called when the solver deletes learned clauses
+    fn on_reduce(&mut self, deleted_clauses: &[Vec<i32>]) {
+        let matched = self
+            .events
+            .mark_deleted(deleted_clauses, &self.learned_clauses);
+        self.events.push(SolverEvent::Reduce {
+            deleted: deleted_clauses.len(),
+            matched,
+        });
+    }
 }
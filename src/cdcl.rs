@@ -0,0 +1,912 @@
+//! A small CDCL solver written for teaching. It has two watched literals, 1st UIP learning with
+//! local minimization, VSIDS, Luby restarts and reduction of learned clauses, and nothing else.
+//! Every event is reported through the same `Callbacks` as in cadical, so `CadicalCallbackWrapper`
+//! works with both. Without inprocessing, the trails and derivations follow the textbook algorithm.
+
+use std::{
+    cmp::Reverse,
+    time::{Duration, Instant},
+};
+
+use cadical::{CadicalStats, Callbacks};
+
+use crate::backend::SolveLimits;
+
+/// Conflicts between restarts are this times the next number in the Luby sequence
+const RESTART_INTERVAL: i64 = 100;
+/// Conflicts before the first reduction of the learned clauses
+const REDUCE_INTERVAL: i64 = 2000;
+/// The conflicts between reductions grow by this after each reduction
+const REDUCE_INCREMENT: i64 = 300;
+/// Learned clauses with at most this many decision levels are never deleted
+const KEPT_GLUE: usize = 2;
+const ACTIVITY_DECAY: f64 = 0.95;
+const ACTIVITY_LIMIT: f64 = 1e100;
+
+struct Clause {
+    literals: Vec<i32>, // The first two literals are watched
+    learned: bool,
+    glue: usize, // Number of decision levels in a learned clause when it was learned
+    deleted: bool,
+}
+
+/// Binary max-heap of variables by activity, for picking the next decision without scanning
+/// every variable. Assigned variables are only removed when they reach the top, like in MiniSat,
+/// so every unassigned variable is in the heap.
+#[derive(Default)]
+struct VariableHeap {
+    heap: Vec<usize>,
+    positions: Vec<Option<usize>>, // Index of each variable in `heap`, None if it is not there
+}
+
+impl VariableHeap {
+    fn push(&mut self, var: usize, activity: &[f64]) {
+        if var >= self.positions.len() {
+            self.positions.resize(var + 1, None);
+        }
+        if self.positions[var].is_some() {
+            return;
+        }
+        self.heap.push(var);
+        self.sift_up(self.heap.len() - 1, activity);
+    }
+
+    /// Removes the variable with the highest activity
+    fn pop(&mut self, activity: &[f64]) -> Option<usize> {
+        let top = *self.heap.first()?;
+        let last = self.heap.pop().unwrap();
+        self.positions[top] = None;
+        if !self.heap.is_empty() {
+            self.heap[0] = last;
+            self.sift_down(0, activity);
+        }
+        Some(top)
+    }
+
+    /// Moves the variable up after its activity was bumped
+    fn bumped(&mut self, var: usize, activity: &[f64]) {
+        if let Some(index) = self.positions.get(var).copied().flatten() {
+            self.sift_up(index, activity);
+        }
+    }
+
+    fn sift_up(&mut self, mut index: usize, activity: &[f64]) {
+        let var = self.heap[index];
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if !decided_before(var, self.heap[parent], activity) {
+                break;
+            }
+            self.heap[index] = self.heap[parent];
+            self.positions[self.heap[index]] = Some(index);
+            index = parent;
+        }
+        self.heap[index] = var;
+        self.positions[var] = Some(index);
+    }
+
+    fn sift_down(&mut self, mut index: usize, activity: &[f64]) {
+        let var = self.heap[index];
+        loop {
+            let left = 2 * index + 1;
+            let right = left + 1;
+            if left >= self.heap.len() {
+                break;
+            }
+            let child = if right < self.heap.len()
+                && decided_before(self.heap[right], self.heap[left], activity)
+            {
+                right
+            } else {
+                left
+            };
+            if !decided_before(self.heap[child], var, activity) {
+                break;
+            }
+            self.heap[index] = self.heap[child];
+            self.positions[self.heap[index]] = Some(index);
+            index = child;
+        }
+        self.heap[index] = var;
+        self.positions[var] = Some(index);
+    }
+}
+
+/// Whether the first variable is decided before the second. Of variables with the same activity,
+/// the lower one is decided first.
+fn decided_before(first: usize, second: usize, activity: &[f64]) -> bool {
+    activity[first] > activity[second] || (activity[first] == activity[second] && first < second)
+}
+
+/// Limits of the current solve as totals of the solver, None if there is no limit
+struct SearchLimits {
+    deadline: Option<Instant>,
+    conflicts: Option<i64>,
+    decisions: Option<i64>,
+    propagations: Option<i64>,
+}
+
+/// The CDCL solver. The literals are integers as in the DIMACS format, like in cadical.
+pub struct CdclSolver<C: Callbacks> {
+    clauses: Vec<Clause>,
+    watches: Vec<Vec<usize>>, // Clauses watching each literal, indexed with `watch_index`
+    values: Vec<Option<bool>>, // Value of each variable, indexed with the variable
+    levels: Vec<usize>,       // Decision level of each assigned variable
+    reasons: Vec<Option<usize>>, // Clause that propagated each variable, None for decisions and units
+    phases: Vec<bool>,           // Last value of each variable, used when the variable is decided
+    activity: Vec<f64>,
+    activity_increment: f64,
+    heap: VariableHeap, // Variables to decide, by activity
+    trail: Vec<i32>,
+    trail_limits: Vec<usize>, // Length of the trail before each decision
+    propagated: usize,        // Literals of the trail before this have been propagated
+    inconsistent: bool,       // The empty clause has been derived
+    status: Option<bool>,
+    conflicts: i64,
+    decisions: i64,
+    propagations: i64,
+    restarts: i64,
+    reductions: i64,
+    reduce_limit: i64, // Number of conflicts at the next reduction
+    learned_clauses: i64,
+    learned_literals: i64,
+    solve_time: f64,     // Seconds spent in all solves
+    limits: SolveLimits, // Limits of the next solve
+    callbacks: Option<C>,
+}
+
+impl<C: Callbacks> CdclSolver<C> {
+    pub fn new() -> Self {
+        Self {
+            clauses: Vec::new(),
+            watches: vec![Vec::new(); 2],
+            values: vec![None],
+            levels: vec![0],
+            reasons: vec![None],
+            phases: vec![false],
+            activity: vec![0.0],
+            activity_increment: 1.0,
+            heap: VariableHeap::default(),
+            trail: Vec::new(),
+            trail_limits: Vec::new(),
+            propagated: 0,
+            inconsistent: false,
+            status: None,
+            conflicts: 0,
+            decisions: 0,
+            propagations: 0,
+            restarts: 0,
+            reductions: 0,
+            reduce_limit: REDUCE_INTERVAL,
+            learned_clauses: 0,
+            learned_literals: 0,
+            solve_time: 0.0,
+            limits: SolveLimits::default(),
+            callbacks: None,
+        }
+    }
+
+    /// Sets the callbacks that are called on the events of the search
+    pub fn set_callbacks(&mut self, callbacks: Option<C>) {
+        self.callbacks = callbacks;
+    }
+
+    /// Sets the limits of the next solve, like `set_limit` of cadical
+    pub fn set_limits(&mut self, limits: SolveLimits) {
+        self.limits = limits;
+    }
+
+    /// Adds the given clause to the solver. Clauses can be added between solves.
+    pub fn add_clause<I>(&mut self, clause: I)
+    where
+        I: IntoIterator<Item = i32>,
+    {
+        self.backtrack(0);
+        self.status = None;
+
+        let clause: Vec<i32> = clause.into_iter().collect();
+        for &literal in &clause {
+            debug_assert!(literal != 0 && literal != i32::MIN);
+            self.ensure_variable(variable(literal));
+        }
+        let satisfied = clause
+            .iter()
+            .any(|&literal| clause.contains(&-literal) || self.value_of(literal) == Some(true));
+        if self.inconsistent || satisfied {
+            return;
+        }
+
+        // Literals that are already false can never satisfy the clause
+        let mut literals: Vec<i32> = Vec::with_capacity(clause.len());
+        for literal in clause {
+            if self.value_of(literal).is_none() && !literals.contains(&literal) {
+                literals.push(literal);
+            }
+        }
+        match literals.len() {
+            0 => self.inconsistent = true,
+            1 => self.assign(literals[0], None),
+            _ => {
+                self.attach(literals, false, 0);
+            }
+        }
+    }
+
+    /// Solves the formula with the assumptions decided first, in the given order. Returns
+    /// `Some(true)` if it is satisfiable, `Some(false)` if it is not, and `None` if the search
+    /// was terminated or reached a limit.
+    pub fn solve_with<I>(&mut self, assumptions: I) -> Option<bool>
+    where
+        I: IntoIterator<Item = i32>,
+    {
+        if let Some(callbacks) = &mut self.callbacks {
+            callbacks.started();
+        }
+
+        let start = Instant::now();
+        let assumptions: Vec<i32> = assumptions.into_iter().collect();
+        for &literal in &assumptions {
+            self.ensure_variable(variable(literal));
+        }
+        self.backtrack(0);
+
+        let limits = std::mem::take(&mut self.limits);
+        let limits = SearchLimits {
+            deadline: limits
+                .seconds
+                .map(|seconds| start + Duration::from_secs(seconds as u64)),
+            conflicts: limits.conflicts.map(|limit| self.conflicts + limit as i64),
+            decisions: limits.decisions.map(|limit| self.decisions + limit as i64),
+            propagations: limits
+                .propagations
+                .map(|limit| self.propagations + limit as i64),
+        };
+        self.status = self.search(&assumptions, &limits);
+        self.solve_time += start.elapsed().as_secs_f64();
+        self.status
+    }
+
+    /// Returns the result of the latest solve, None if clauses have been added after it
+    pub fn status(&self) -> Option<bool> {
+        self.status
+    }
+
+    /// Returns the value of the literal in the solution found by the latest solve
+    pub fn value(&self, literal: i32) -> Option<bool> {
+        debug_assert!(self.status == Some(true));
+        if variable(literal) >= self.values.len() {
+            return None;
+        }
+        self.value_of(literal)
+    }
+
+    /// Returns 1 if the literal is true on the root level, -1 if it is false and 0 otherwise
+    pub fn fixed(&self, literal: i32) -> i32 {
+        let var = variable(literal);
+        match self.values.get(var) {
+            Some(Some(_)) if self.levels[var] == 0 => {
+                if self.value_of(literal) == Some(true) {
+                    1
+                } else {
+                    -1
+                }
+            }
+            _ => 0,
+        }
+    }
+
+    /// Statistics in the same form as cadical's. The memory usage is not measured,
+    /// and the time spent solving is reported as both the process time and the real time.
+    pub fn stats(&self) -> CadicalStats {
+        CadicalStats {
+            process_time: self.solve_time,
+            real_time: self.solve_time,
+            max_resident_set_size_mb: 0.0,
+            conflicts: self.conflicts,
+            learned_clauses: self.learned_clauses,
+            learned_literals: self.learned_literals,
+            decisions: self.decisions,
+            restarts: self.restarts,
+            propagations: self.propagations,
+        }
+    }
+
+    /// Main loop of the search: propagate, analyze conflicts and decide until all
+    /// variables are assigned or the empty clause is derived
+    fn search(&mut self, assumptions: &[i32], limits: &SearchLimits) -> Option<bool> {
+        if self.inconsistent {
+            return Some(false);
+        }
+
+        let mut restart_limit = self.conflicts + RESTART_INTERVAL * luby(self.restarts + 1);
+        loop {
+            if let Some(conflict) = self.propagate() {
+                self.conflicts += 1;
+                if self.decision_level() == 0 {
+                    self.inconsistent = true;
+                    if let Some(callbacks) = &mut self.callbacks {
+                        callbacks.learn(&[]);
+                    }
+                    return Some(false);
+                }
+                self.analyze(conflict);
+
+                if let Some(callbacks) = &mut self.callbacks {
+                    if callbacks.terminate() {
+                        return None;
+                    }
+                }
+                continue;
+            }
+
+            if self.conflicts >= restart_limit {
+                self.restart();
+                restart_limit = self.conflicts + RESTART_INTERVAL * luby(self.restarts + 1);
+                continue;
+            }
+            if self.conflicts >= self.reduce_limit {
+                self.reduce();
+            }
+            if self.limit_reached(limits) {
+                return None;
+            }
+
+            // The assumptions are decided first, one on each level
+            let level = self.decision_level();
+            let decision = match assumptions.get(level) {
+                Some(&assumption) => match self.value_of(assumption) {
+                    Some(true) => {
+                        self.trail_limits.push(self.trail.len());
+                        continue;
+                    }
+                    Some(false) => return Some(false),
+                    None => assumption,
+                },
+                None => match self.pick_branch() {
+                    Some(literal) => literal,
+                    None => return Some(true),
+                },
+            };
+
+            self.decisions += 1;
+            self.trail_limits.push(self.trail.len());
+            self.assign(decision, None);
+            if let Some(callbacks) = &mut self.callbacks {
+                callbacks.on_decision(decision, level as i32 + 1);
+            }
+        }
+    }
+
+    /// Checks the limits before each decision, like cadical does
+    fn limit_reached(&self, limits: &SearchLimits) -> bool {
+        limits
+            .conflicts
+            .is_some_and(|limit| self.conflicts >= limit)
+            || limits
+                .decisions
+                .is_some_and(|limit| self.decisions >= limit)
+            || limits
+                .propagations
+                .is_some_and(|limit| self.propagations >= limit)
+            || limits
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Propagates the literals of the trail. Returns the index of a falsified clause if there is one.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let false_literal = -self.trail[self.propagated];
+            self.propagated += 1;
+            self.propagations += 1;
+
+            // Each clause watching the literal either finds a new watch, propagates or is falsified
+            let mut watchers = std::mem::take(&mut self.watches[watch_index(false_literal)]);
+            let mut conflict = None;
+            let mut i = 0;
+            while i < watchers.len() {
+                let clause_index = watchers[i];
+                let clause = &mut self.clauses[clause_index];
+                if clause.deleted {
+                    watchers.swap_remove(i);
+                    continue;
+                }
+                if clause.literals[0] == false_literal {
+                    clause.literals.swap(0, 1);
+                }
+                let first = clause.literals[0];
+                if literal_value(&self.values, first) == Some(true) {
+                    i += 1;
+                    continue;
+                }
+
+                let new_watch = (2..clause.literals.len())
+                    .find(|&k| literal_value(&self.values, clause.literals[k]) != Some(false));
+                if let Some(k) = new_watch {
+                    clause.literals.swap(1, k);
+                    self.watches[watch_index(clause.literals[1])].push(clause_index);
+                    watchers.swap_remove(i);
+                    continue;
+                }
+
+                i += 1;
+                if literal_value(&self.values, first) == Some(false) {
+                    conflict = Some(clause_index);
+                    break;
+                }
+                self.assign(first, Some(clause_index));
+            }
+            self.watches[watch_index(false_literal)] = watchers;
+
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    /// Learns the 1st UIP clause of the conflict, backjumps and asserts the clause
+    fn analyze(&mut self, conflict: usize) {
+        let level = self.decision_level();
+        let mut seen = vec![false; self.values.len()];
+        let mut learned = vec![0]; // The UIP is set as the first literal at the end
+        let mut steps: Vec<(i32, Vec<i32>)> = Vec::new();
+        let mut open = 0; // Seen literals of the conflict level that have not been resolved
+        let mut pivot = 0;
+        let mut reason = conflict;
+        let mut index = self.trail.len();
+        loop {
+            let literals = self.clauses[reason].literals.clone();
+            steps.push(self.resolution_step(pivot, reason));
+            for literal in literals {
+                let var = variable(literal);
+                if literal == pivot || seen[var] || self.levels[var] == 0 {
+                    continue;
+                }
+                seen[var] = true;
+                self.bump(var);
+                if self.levels[var] == level {
+                    open += 1;
+                } else {
+                    learned.push(literal);
+                }
+            }
+
+            // The next literal to resolve is the latest seen one on the trail
+            loop {
+                index -= 1;
+                if seen[variable(self.trail[index])] {
+                    break;
+                }
+            }
+            pivot = self.trail[index];
+            seen[variable(pivot)] = false;
+            open -= 1;
+            if open == 0 {
+                break;
+            }
+            reason = self.reasons[variable(pivot)].expect("propagated literal has a reason");
+        }
+        learned[0] = -pivot;
+
+        // A literal is redundant if the other literals of its reason are in the clause
+        let mut minimized = vec![learned[0]];
+        let mut removed = Vec::new();
+        for &literal in &learned[1..] {
+            let redundant = self.reasons[variable(literal)].is_some_and(|reason| {
+                self.clauses[reason].literals.iter().all(|&other| {
+                    other == -literal || seen[variable(other)] || self.levels[variable(other)] == 0
+                })
+            });
+            if redundant {
+                removed.push(variable(literal));
+            } else {
+                minimized.push(literal);
+            }
+        }
+        // The latest assigned literal is resolved first, so no reason brings back a resolved one
+        let minimization: Vec<(i32, Vec<i32>)> = self
+            .trail
+            .iter()
+            .rev()
+            .filter(|&&literal| removed.contains(&variable(literal)))
+            .map(|&pivot| {
+                let reason = self.reasons[variable(pivot)].expect("removed literal has a reason");
+                self.resolution_step(pivot, reason)
+            })
+            .collect();
+        let mut learned = minimized;
+
+        // The literal of the highest remaining level is watched, so the clause propagates after backjumping
+        let mut backjump_level = 0;
+        if let Some((highest, _)) = learned
+            .iter()
+            .enumerate()
+            .skip(1)
+            .max_by_key(|(_, &literal)| self.levels[variable(literal)])
+        {
+            learned.swap(1, highest);
+            backjump_level = self.levels[variable(learned[1])];
+        }
+        let mut clause_levels: Vec<usize> = learned
+            .iter()
+            .map(|&literal| self.levels[variable(literal)])
+            .collect();
+        clause_levels.sort();
+        clause_levels.dedup();
+
+        self.learned_clauses += 1;
+        self.learned_literals += learned.len() as i64;
+        self.export_learned(&learned, conflict, &steps, &minimization);
+
+        self.backtrack(backjump_level);
+        if learned.len() == 1 {
+            self.assign(learned[0], None);
+        } else {
+            let uip = learned[0];
+            let clause_index = self.attach(learned, true, clause_levels.len());
+            self.assign(uip, Some(clause_index));
+        }
+        self.activity_increment /= ACTIVITY_DECAY;
+    }
+
+    /// The pivot and the literals of the reason clause. Root level literals are left out like in
+    /// cadical, they are never in a learned clause.
+    fn resolution_step(&self, pivot: i32, reason: usize) -> (i32, Vec<i32>) {
+        let literals = self.clauses[reason]
+            .literals
+            .iter()
+            .filter(|&&literal| literal == pivot || self.levels[variable(literal)] > 0)
+            .copied()
+            .collect();
+        (pivot, literals)
+    }
+
+    /// Reports the learned clause, the trail at the conflict and the resolved reasons
+    fn export_learned(
+        &mut self,
+        learned: &[i32],
+        conflict: usize,
+        steps: &[(i32, Vec<i32>)],
+        minimization: &[(i32, Vec<i32>)],
+    ) {
+        let Some(callbacks) = &mut self.callbacks else {
+            return;
+        };
+        if learned.len() as i32 > callbacks.max_length() {
+            return;
+        }
+        let is_propagated: Vec<i32> = self
+            .trail
+            .iter()
+            .map(|&literal| self.reasons[variable(literal)].is_some() as i32)
+            .collect();
+        callbacks.learn(learned);
+        callbacks.learn_trail(
+            &self.clauses[conflict].literals,
+            &is_propagated,
+            &self.trail,
+        );
+        callbacks.learn_resolution(steps, minimization);
+    }
+
+    /// Unassigns the literals above the level, saving their values as phases
+    fn backtrack(&mut self, level: usize) {
+        if self.decision_level() <= level {
+            return;
+        }
+        let limit = self.trail_limits[level];
+        for &literal in &self.trail[limit..] {
+            let var = variable(literal);
+            self.phases[var] = literal > 0;
+            self.values[var] = None;
+            self.reasons[var] = None;
+            self.heap.push(var, &self.activity);
+        }
+        self.trail.truncate(limit);
+        self.trail_limits.truncate(level);
+        self.propagated = self.propagated.min(limit);
+
+        if let Some(callbacks) = &mut self.callbacks {
+            callbacks.on_backtrack(level as i32);
+        }
+    }
+
+    fn restart(&mut self) {
+        self.restarts += 1;
+        self.backtrack(0);
+        if let Some(callbacks) = &mut self.callbacks {
+            callbacks.on_restart();
+        }
+    }
+
+    /// Deletes half of the learned clauses, the ones with the most decision levels first.
+    /// Clauses that are reasons on the trail or have a low glue are kept.
+    fn reduce(&mut self) {
+        self.reductions += 1;
+        self.reduce_limit = self.conflicts + REDUCE_INTERVAL + REDUCE_INCREMENT * self.reductions;
+
+        let mut candidates: Vec<usize> = (0..self.clauses.len())
+            .filter(|&index| {
+                let clause = &self.clauses[index];
+                clause.learned
+                    && !clause.deleted
+                    && clause.glue > KEPT_GLUE
+                    && !self.is_reason(index)
+            })
+            .collect();
+        candidates.sort_by_key(|&index| {
+            let clause = &self.clauses[index];
+            (Reverse(clause.glue), Reverse(clause.literals.len()))
+        });
+        candidates.truncate(candidates.len() / 2);
+
+        // The deleted clauses are removed from the watch lists during propagation
+        let mut deleted = Vec::with_capacity(candidates.len());
+        for index in candidates {
+            let clause = &mut self.clauses[index];
+            clause.deleted = true;
+            deleted.push(std::mem::take(&mut clause.literals));
+        }
+        if let Some(callbacks) = &mut self.callbacks {
+            if !deleted.is_empty() {
+                callbacks.on_reduce(&deleted);
+            }
+        }
+    }
+
+    fn is_reason(&self, index: usize) -> bool {
+        self.clauses[index]
+            .literals
+            .iter()
+            .any(|&literal| self.reasons[variable(literal)] == Some(index))
+    }
+
+    /// Unassigned variable with the highest activity, with its saved phase
+    fn pick_branch(&mut self) -> Option<i32> {
+        while let Some(var) = self.heap.pop(&self.activity) {
+            if self.values[var].is_none() {
+                return Some(if self.phases[var] {
+                    var as i32
+                } else {
+                    -(var as i32)
+                });
+            }
+        }
+        None
+    }
+
+    fn bump(&mut self, var: usize) {
+        self.activity[var] += self.activity_increment;
+        if self.activity[var] > ACTIVITY_LIMIT {
+            for activity in self.activity.iter_mut() {
+                *activity /= ACTIVITY_LIMIT;
+            }
+            self.activity_increment /= ACTIVITY_LIMIT;
+        }
+        self.heap.bumped(var, &self.activity);
+    }
+
+    fn assign(&mut self, literal: i32, reason: Option<usize>) {
+        let var = variable(literal);
+        let level = self.decision_level();
+        self.values[var] = Some(literal > 0);
+        self.levels[var] = level;
+        // Like in cadical, literals on the root level have no reason
+        self.reasons[var] = if level == 0 { None } else { reason };
+        self.trail.push(literal);
+    }
+
+    fn attach(&mut self, literals: Vec<i32>, learned: bool, glue: usize) -> usize {
+        let index = self.clauses.len();
+        self.watches[watch_index(literals[0])].push(index);
+        self.watches[watch_index(literals[1])].push(index);
+        self.clauses.push(Clause {
+            literals,
+            learned,
+            glue,
+            deleted: false,
+        });
+        index
+    }
+
+    fn ensure_variable(&mut self, var: usize) {
+        if var < self.values.len() {
+            return;
+        }
+        let size = var + 1;
+        let old_size = self.values.len();
+        self.values.resize(size, None);
+        self.levels.resize(size, 0);
+        self.reasons.resize(size, None);
+        self.phases.resize(size, false);
+        self.activity.resize(size, 0.0);
+        self.watches.resize(2 * size, Vec::new());
+        for var in old_size..size {
+            self.heap.push(var, &self.activity);
+        }
+    }
+
+    fn decision_level(&self) -> usize {
+        self.trail_limits.len()
+    }
+
+    fn value_of(&self, literal: i32) -> Option<bool> {
+        literal_value(&self.values, literal)
+    }
+}
+
+impl<C: Callbacks> Default for CdclSolver<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn variable(literal: i32) -> usize {
+    literal.unsigned_abs() as usize
+}
+
+/// Index of the literal's watch list. The two literals of a variable are next to each other.
+fn watch_index(literal: i32) -> usize {
+    2 * variable(literal) + (literal < 0) as usize
+}
+
+fn literal_value(values: &[Option<bool>], literal: i32) -> Option<bool> {
+    values[variable(literal)].map(|value| value == (literal > 0))
+}
+
+/// The element at the 1-based index of the Luby sequence 1, 1, 2, 1, 1, 2, 4, 1, ...
+fn luby(mut index: i64) -> i64 {
+    loop {
+        let mut size = 1;
+        while (1 << size) - 1 < index {
+            size += 1;
+        }
+        if (1 << size) - 1 == index {
+            return 1 << (size - 1);
+        }
+        index -= (1 << (size - 1)) - 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app_state::EncodingType,
+        backend::SolverBackend,
+        cadical_wrapper::CadicalCallbackWrapper,
+        resolution::Derivation,
+        sudoku::{get_sudoku, solve_sudoku},
+        ConstraintList, Trail,
+    };
+
+    use super::*;
+
+    /// Clauses that put `holes + 1` pigeons in `holes` holes
+    fn pigeon_hole(holes: i32) -> Vec<Vec<i32>> {
+        let pigeon = |pigeon: i32, hole: i32| pigeon * holes + hole + 1;
+        let mut clauses = Vec::new();
+        for i in 0..=holes {
+            clauses.push((0..holes).map(|hole| pigeon(i, hole)).collect());
+        }
+        for hole in 0..holes {
+            for i in 0..=holes {
+                for j in (i + 1)..=holes {
+                    clauses.push(vec![-pigeon(i, hole), -pigeon(j, hole)]);
+                }
+            }
+        }
+        clauses
+    }
+
+    #[test]
+    fn test_luby() {
+        let sequence: Vec<i64> = (1..=15).map(luby).collect();
+        assert_eq!(sequence, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn test_variable_heap() {
+        let mut activity = vec![0.0, 3.0, 1.0, 5.0, 1.0, 2.0];
+        let mut heap = VariableHeap::default();
+        for var in 1..activity.len() {
+            heap.push(var, &activity);
+        }
+        heap.push(3, &activity);
+
+        activity[4] = 4.0;
+        heap.bumped(4, &activity);
+        let order: Vec<usize> = std::iter::from_fn(|| heap.pop(&activity)).collect();
+        // Variables 2 and 4 had the same activity before 4 was bumped
+        assert_eq!(order, vec![3, 4, 1, 5, 2]);
+
+        // The lower variable comes first on ties, like in a scan of the variables
+        let activity = vec![0.0; 6];
+        for var in [5, 3, 1, 4, 2] {
+            heap.push(var, &activity);
+        }
+        assert_eq!(heap.pop(&activity), Some(1));
+        assert_eq!(heap.pop(&activity), Some(2));
+    }
+
+    #[test]
+    fn test_solve() {
+        let mut solver: CdclSolver<CadicalCallbackWrapper> = CdclSolver::new();
+        solver.add_clause([1, 2]);
+        solver.add_clause([-1, 2]);
+        solver.add_clause([-2, 3, 4]);
+        assert_eq!(solver.solve(), Some(true));
+        assert_eq!(solver.value(2), Some(true));
+        assert_eq!(solver.fixed(2), 0);
+
+        // Assumptions only hold for one solve
+        assert_eq!(solver.solve_with([-3, -4]), Some(false));
+        assert_eq!(solver.solve_with([-3]), Some(true));
+        assert_eq!(solver.value(4), Some(true));
+
+        solver.add_clause([-2]);
+        assert_eq!(solver.status(), None);
+        assert_eq!(solver.solve(), Some(false));
+        assert_eq!(solver.solve(), Some(false));
+    }
+
+    #[test]
+    fn test_pigeon_hole() {
+        let mut solver: CdclSolver<CadicalCallbackWrapper> = CdclSolver::new();
+        for clause in pigeon_hole(6) {
+            solver.add_clause(clause);
+        }
+        assert_eq!(solver.solve(), Some(false));
+        let stats = solver.stats();
+        assert!(stats.conflicts > 0);
+        assert_eq!(stats.learned_clauses, stats.conflicts - 1);
+    }
+
+    #[test]
+    fn test_sudoku_with_callbacks() {
+        let constraints = ConstraintList::new();
+        let trails = Trail::new();
+        let wrapper = CadicalCallbackWrapper::new(constraints.clone(), trails.clone());
+        let events = wrapper.events.clone();
+        let mut solver = CdclSolver::new();
+        solver.set_callbacks(Some(wrapper));
+        let clues = get_sudoku("data/hardest-ever-sudoku.txt".to_string()).unwrap();
+        let encoding = EncodingType::Decimal {
+            cell_at_least_one: true,
+            cell_at_most_one: false,
+            sudoku_has_all_values: false,
+            sudoku_has_unique_values: true,
+        };
+        let solved = solve_sudoku(&clues, &mut solver, &encoding).unwrap();
+
+        let mut cadical: cadical::Solver<CadicalCallbackWrapper> =
+            cadical::Solver::with_config("plain").unwrap();
+        assert_eq!(solve_sudoku(&clues, &mut cadical, &encoding), Ok(solved));
+
+        let stats = solver.stats();
+        assert_eq!(constraints.len() as i64, stats.learned_clauses);
+        assert_eq!(trails.len(), constraints.len());
+        assert!(events.len() as i64 >= stats.decisions + stats.conflicts);
+        for (index, learned) in constraints.borrow().iter().enumerate() {
+            // The trail ends with the literals of the conflict level, and the conflict is falsified
+            let trail = trails.trail_at_index(index);
+            for literal in trails.literals_at_index(index) {
+                assert!(trail.contains(&-literal));
+            }
+
+            let (steps, minimization) = trails.resolution_at_index(index).unwrap();
+            let derivation = Derivation::new(&steps, &minimization, learned);
+            assert_eq!(
+                derivation.first_uip_clause().len(),
+                learned.len() + derivation.minimized.len()
+            );
+            assert_eq!(derivation.minimization.len(), derivation.minimized.len());
+            let resolvent = derivation
+                .minimization
+                .last()
+                .map_or(derivation.first_uip_clause(), |step| &step.resolvent);
+            assert_eq!(resolvent.len(), learned.len());
+            assert!(learned.iter().all(|literal| resolvent.contains(literal)));
+        }
+    }
+}
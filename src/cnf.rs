@@ -6,10 +6,13 @@ pub mod decimal_encoding;
 
 use std::collections::HashSet;
 
+use serde::{Deserialize, Serialize};
+
 use crate::app_state::EncodingType;
 
 /// Enum that enables the app to handle different types of CNF variables
-#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum CnfVariable {
     Decimal {
         row: i32,
@@ -100,6 +103,20 @@ impl CnfVariable {
         }
     }
 
+    /// Cells the variable is about. Equality variables are about two cells, others about one.
+    pub fn cells(&self) -> Vec<(i32, i32)> {
+        match *self {
+            Self::Decimal { row, col, .. } | Self::Bit { row, col, .. } => vec![(row, col)],
+            Self::Equality {
+                row,
+                col,
+                row2,
+                col2,
+                ..
+            } => vec![(row, col), (row2, col2)],
+        }
+    }
+
     /// Returns HashSet of possible numbers, empty if self is equality variable, since
     /// the concept of possible values does not work for equality constraints.
     /// Used in drawing little numbers.
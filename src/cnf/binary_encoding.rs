@@ -1,7 +1,6 @@
 //! Functions for binary based CNF encoding
 
-use crate::cadical_wrapper::CadicalCallbackWrapper;
-use cadical::Solver;
+use crate::backend::SolverBackend;
 
 /// Returns a Vec of CNF clauses (stored as `Vec<i32>`) which fully
 /// encodes the rules of sudoku, and the clues given as an argument.
@@ -90,16 +89,9 @@ pub fn sudoku_to_cnf(clues: &[Vec<Option<i32>>]) -> Vec<Vec<i32>> {
     // Adds a unit clause (single variable clause) for each bit of each clue
     for (row, line) in clues.iter().enumerate() {
         for (col, val) in line.iter().enumerate() {
-            if let Some(mut val) = val {
-                val -= 1;
-                let mut mask = 1;
-                for index in 0..4 {
-                    if (val & mask) != 0 {
-                        clauses.push(vec![cnf_identifier(row as i32 + 1, col as i32 + 1, index)]);
-                    } else {
-                        clauses.push(vec![-cnf_identifier(row as i32 + 1, col as i32 + 1, index)]);
-                    }
-                    mask *= 2;
+            if let Some(val) = val {
+                for literal in clue_literals(row as i32 + 1, col as i32 + 1, *val) {
+                    clauses.push(vec![literal]);
                 }
             }
         }
@@ -143,9 +135,40 @@ fn eq_variable_init(row: i32, col: i32, row2: i32, col2: i32) -> Vec<Vec<i32>> {
     clauses
 }
 
+/// Returns the literals that together set the value of a cell, one for each bit
+pub fn clue_literals(row: i32, col: i32, val: i32) -> Vec<i32> {
+    let val = val - 1;
+    let mut mask = 1;
+    let mut literals = Vec::with_capacity(4);
+    for index in 0..4 {
+        if (val & mask) != 0 {
+            literals.push(cnf_identifier(row, col, index));
+        } else {
+            literals.push(-cnf_identifier(row, col, index));
+        }
+        mask *= 2;
+    }
+    literals
+}
+
+/// Returns clauses stating that the value of a cell is one of the candidates.
+/// A value can not be given as a single clause with bits, so each non-candidate value is excluded
+/// with a clause that requires at least one bit to differ from it.
+pub fn candidate_clauses(row: i32, col: i32, candidates: &[i32]) -> Vec<Vec<i32>> {
+    (1..=9)
+        .filter(|val| !candidates.contains(val))
+        .map(|val| {
+            clue_literals(row, col, val)
+                .iter()
+                .map(|literal| -literal)
+                .collect()
+        })
+        .collect()
+}
+
 /// Gets all bit values of a cell from the solver, and converts thet to a decimal value,
 /// which is returned.
-pub fn get_cell_value(solver: &Solver<CadicalCallbackWrapper>, row: i32, col: i32) -> i32 {
+pub fn get_cell_value(solver: &(impl SolverBackend + ?Sized), row: i32, col: i32) -> i32 {
     let mut value: i32 = 1;
     for bit in 0..4 {
         // Add 2^(bit) to the value for each 1-bit of the cell
@@ -210,7 +233,7 @@ mod tests {
     use std::collections::HashSet;
 
     use crate::{app_state::EncodingType, sudoku::clues_from_string, sudoku::solve_sudoku};
-    use crate::{ConstraintList, Trail};
+    use crate::{cadical_wrapper::CadicalCallbackWrapper, ConstraintList, Trail};
 
     use super::*;
 
@@ -313,7 +336,7 @@ mod tests {
         );
         assert_eq!(
             (6, 2, 0, false),
-            identifier_to_tuple(-1 * cnf_identifier(6, 2, 0))
+            identifier_to_tuple(-cnf_identifier(6, 2, 0))
         );
     }
 
@@ -333,7 +356,7 @@ mod tests {
         );
         assert_eq!(
             (6, 2, 8, 2, 0, false),
-            eq_identifier_to_tuple(-1 * eq_cnf_identifier(6, 2, 8, 2, 0))
+            eq_identifier_to_tuple(-eq_cnf_identifier(6, 2, 8, 2, 0))
         );
     }
 }
@@ -1,7 +1,6 @@
 //! Functions for decimal based CNF encoding
 
-use crate::cadical_wrapper::CadicalCallbackWrapper;
-use cadical::Solver;
+use crate::backend::SolverBackend;
 
 /// Returns a Vec of CNF clauses (stored as `Vec<i32>`) which fully
 /// encodes the rules of sudoku, and the clues given as an argument.
@@ -145,7 +144,7 @@ pub fn sudoku_to_cnf(
     for (row, line) in clues.iter().enumerate() {
         for (col, val) in line.iter().enumerate() {
             if let Some(val) = val {
-                clauses.push(vec![cnf_identifier(row as i32 + 1, col as i32 + 1, *val)]);
+                clauses.push(clue_literals(row as i32 + 1, col as i32 + 1, *val));
             }
         }
     }
@@ -153,6 +152,19 @@ pub fn sudoku_to_cnf(
     clauses
 }
 
+/// Returns the literals that together set the value of a cell
+pub fn clue_literals(row: i32, col: i32, val: i32) -> Vec<i32> {
+    vec![cnf_identifier(row, col, val)]
+}
+
+/// Returns the clause stating that the value of a cell is one of the candidates
+pub fn candidate_clauses(row: i32, col: i32, candidates: &[i32]) -> Vec<Vec<i32>> {
+    vec![candidates
+        .iter()
+        .map(|&val| cnf_identifier(row, col, val))
+        .collect()]
+}
+
 #[inline(always)]
 /// Gives every variable (row, column and value combination) a unique identifier > 0
 pub fn cnf_identifier(row: i32, col: i32, val: i32) -> i32 {
@@ -175,7 +187,7 @@ pub fn identifier_to_tuple(mut identifier: i32) -> (i32, i32, i32) {
 
 /// Returns the value of a cell from the solver. If the cell has no value, -1 is returned.
 /// This should only happen if chosen encoding is not adequate.
-pub fn get_cell_value(solver: &Solver<CadicalCallbackWrapper>, row: i32, col: i32) -> i32 {
+pub fn get_cell_value(solver: &(impl SolverBackend + ?Sized), row: i32, col: i32) -> i32 {
     let mut value = -1;
     for val in 1..=9 {
         if solver.value(cnf_identifier(row, col, val)).unwrap_or(false) {
@@ -186,6 +198,13 @@ pub fn get_cell_value(solver: &Solver<CadicalCallbackWrapper>, row: i32, col: i3
     value
 }
 
+/// Gets all values of a cell that are true in the solver's model
+pub fn get_cell_values(solver: &(impl SolverBackend + ?Sized), row: i32, col: i32) -> Vec<i32> {
+    (1..=9)
+        .filter(|&val| solver.value(cnf_identifier(row, col, val)).unwrap_or(false))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -217,10 +236,7 @@ mod tests {
         assert_eq!((1, 1, 1), identifier_to_tuple(cnf_identifier(1, 1, 1)));
         assert_eq!((1, 2, 3), identifier_to_tuple(cnf_identifier(1, 2, 3)));
         assert_eq!((9, 9, 9), identifier_to_tuple(cnf_identifier(9, 9, 9)));
-        assert_eq!(
-            (6, 2, -8),
-            identifier_to_tuple(-1 * cnf_identifier(6, 2, 8))
-        );
+        assert_eq!((6, 2, -8), identifier_to_tuple(-cnf_identifier(6, 2, 8)));
     }
 
     #[test]
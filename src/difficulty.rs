@@ -0,0 +1,161 @@
+//! Difficulty rating of sudokus. The rating combines how hard the sudoku is for CaDiCaL
+//! with the hardest technique a human needs to solve it.
+
+use std::fmt;
+
+use cadical::Solver;
+
+use crate::{
+    app_state::EncodingType,
+    cadical_wrapper::CadicalCallbackWrapper,
+    error::AppError,
+    human_solver::{solve_human, Technique},
+    sudoku::solve_sudoku,
+};
+
+/// Number of solver seeds the solver metrics are averaged over
+pub const RATING_SEEDS: i32 = 5;
+
+/// The encoding used for rating, so that ratings are comparable regardless of the chosen encoding
+const RATING_ENCODING: EncodingType = EncodingType::Decimal {
+    cell_at_least_one: true,
+    cell_at_most_one: false,
+    sudoku_has_all_values: false,
+    sudoku_has_unique_values: true,
+};
+
+/// Score given when the human techniques are not enough to solve the sudoku
+const UNSOLVED_HUMAN_SCORE: f64 = 9.0;
+
+/// Solver metrics averaged over the seeds, and the result of the human solver
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difficulty {
+    pub score: f64,
+    pub conflicts: f64,
+    pub decisions: f64,
+    pub propagations: f64,
+    pub hardest_technique: Option<Technique>,
+    pub human_solvable: bool,
+}
+
+impl Difficulty {
+    pub fn label(&self) -> &'static str {
+        match self.score {
+            score if score < 3.0 => "Easy",
+            score if score < 5.0 => "Medium",
+            score if score < 7.0 => "Hard",
+            score if score < 10.0 => "Expert",
+            _ => "Extreme",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:.1})", self.label(), self.score)
+    }
+}
+
+/// Rates the difficulty of a sudoku. The sudoku is solved with the plain configuration
+/// and a different seed on each run, and the metrics are averaged over the runs.
+pub fn rate_difficulty(clues: &[Vec<Option<i32>>]) -> Result<Difficulty, AppError> {
+    let (mut conflicts, mut decisions, mut propagations) = (0, 0, 0);
+    for seed in 0..RATING_SEEDS {
+        let mut solver: Solver<CadicalCallbackWrapper> = Solver::with_config("plain").unwrap();
+        solver
+            .set_option("seed", seed)
+            .and_then(|_| solver.set_option("shuffle", 1))?;
+        solve_sudoku(clues, &mut solver, &RATING_ENCODING)?;
+
+        let stats = solver.stats();
+        conflicts += stats.conflicts;
+        decisions += stats.decisions;
+        propagations += stats.propagations;
+    }
+
+    let runs = RATING_SEEDS as f64;
+    let (conflicts, decisions, propagations) = (
+        conflicts as f64 / runs,
+        decisions as f64 / runs,
+        propagations as f64 / runs,
+    );
+    let human_solution = solve_human(clues);
+    let hardest_technique = human_solution.hardest_technique();
+    let human_solvable = human_solution.is_solved();
+
+    Ok(Difficulty {
+        score: difficulty_score(
+            conflicts,
+            decisions,
+            propagations,
+            hardest_technique,
+            human_solvable,
+        ),
+        conflicts,
+        decisions,
+        propagations,
+        hardest_technique,
+        human_solvable,
+    })
+}
+
+/// Combines the metrics into a single score. The solver metrics grow exponentially with the
+/// difficulty, so their logarithms are used. Conflicts weigh the most, as an easy sudoku can be
+/// solved by propagation alone.
+pub fn difficulty_score(
+    conflicts: f64,
+    decisions: f64,
+    propagations: f64,
+    hardest_technique: Option<Technique>,
+    human_solvable: bool,
+) -> f64 {
+    let solver_score = (1.0 + conflicts).log10()
+        + 0.5 * (1.0 + decisions).log10()
+        + 0.25 * (1.0 + propagations).log10();
+    let human_score = if !human_solvable {
+        UNSOLVED_HUMAN_SCORE
+    } else {
+        hardest_technique.map_or(0.0, |technique| technique as i32 as f64 + 1.0)
+    };
+    solver_score + human_score
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sudoku::get_sudoku;
+
+    use super::*;
+
+    #[test]
+    fn test_difficulty_score() {
+        let easy = difficulty_score(0.0, 10.0, 700.0, Some(Technique::NakedSingle), true);
+        let harder_technique = difficulty_score(0.0, 10.0, 700.0, Some(Technique::XWing), true);
+        let more_conflicts =
+            difficulty_score(100.0, 10.0, 700.0, Some(Technique::NakedSingle), true);
+        let unsolved = difficulty_score(0.0, 10.0, 700.0, Some(Technique::XWing), false);
+
+        assert!(easy < harder_technique);
+        assert!(easy < more_conflicts);
+        assert!(harder_technique < unsolved);
+    }
+
+    #[test]
+    fn test_rate_difficulty() {
+        let sample = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        let hardest = get_sudoku("data/hardest-ever-sudoku.txt".to_string()).unwrap();
+
+        let sample_rating = rate_difficulty(&sample).unwrap();
+        let hardest_rating = rate_difficulty(&hardest).unwrap();
+
+        assert!(hardest_rating.propagations > 0.0);
+        assert!(!hardest_rating.human_solvable);
+        assert!(sample_rating.score < hardest_rating.score);
+        assert_eq!(hardest_rating.label(), "Extreme");
+
+        // A sudoku without a solution can not be rated
+        let mut invalid = vec![vec![None; 9]; 9];
+        invalid[0][0] = Some(1);
+        invalid[0][1] = Some(1);
+        assert!(rate_difficulty(&invalid).is_err());
+    }
+}
@@ -1,4 +1,218 @@
+use std::{fmt, io, path::PathBuf};
+
+use crate::{schema::Variant, sudoku::SolveFailure};
+
+/// Errors that are shown to the user in the error window
 #[derive(Debug)]
-pub struct GenericError {
-    pub msg: String,
+pub enum AppError {
+    /// Reading a file failed
+    Read { path: PathBuf, source: io::Error },
+    /// Writing a file failed
+    Write { path: PathBuf, source: io::Error },
+    /// The sudoku does not have 9 rows
+    RowCount { found: usize },
+    /// A row of the sudoku does not have 9 cells, nor the 81 cells of a whole puzzle. Lines are
+    /// numbered from 1.
+    RowLength {
+        line: usize,
+        found: usize,
+        content: String,
+    },
+    /// A character of the sudoku is neither a digit, an empty cell nor a box separator. Lines
+    /// and columns are numbered from 1.
+    InvalidCell {
+        line: usize,
+        column: usize,
+        found: char,
+        content: String,
+    },
+    /// A line of a DRAT proof is not a clause terminated by 0
+    InvalidProof { line: usize, content: String },
+    /// The proof contains a step that the export format can not express
+    ProofExport { reason: &'static str },
+    /// The sudoku could not be solved
+    Solve(SolveFailure),
+    /// CaDiCaL rejected an option or could not open a file
+    Solver(cadical::Error),
+    /// An external solver could not be started or waited for
+    ExternalSolver { solver: PathBuf, source: io::Error },
+    /// An external solver finished without printing a result
+    NoSolverResult { solver: String },
+    /// A JSON document could not be read
+    Json(serde_json::Error),
+    /// A JSON document was written with a schema version this app can not read
+    SchemaVersion { found: u32 },
+    /// A puzzle document is a sudoku variant the app can not solve
+    UnsupportedVariant { found: Variant },
+    /// A JSON document contains something else than what was asked for
+    DocumentKind {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// An input field does not contain a valid value
+    InvalidInput {
+        field: &'static str,
+        expected: &'static str,
+    },
+}
+
+impl AppError {
+    /// Short title of the error, shown above the details in the error window
+    pub fn title(&self) -> &'static str {
+        match self {
+            AppError::Read { .. } => "Reading the file failed",
+            AppError::Write { .. } => "Saving the file failed",
+            AppError::RowCount { .. }
+            | AppError::RowLength { .. }
+            | AppError::InvalidCell { .. } => "Invalid sudoku format!",
+            AppError::InvalidProof { .. } => "Invalid DRAT proof",
+            AppError::ProofExport { .. } => "Exporting the proof failed",
+            AppError::Solve(SolveFailure::Unsatisfiable) => "The sudoku is unsolvable",
+            AppError::Solve(SolveFailure::LimitReached) => "Solve limit reached",
+            AppError::Solver(_) => "CaDiCaL error",
+            AppError::ExternalSolver { .. } | AppError::NoSolverResult { .. } => {
+                "External solver failed"
+            }
+            AppError::Json(_) | AppError::SchemaVersion { .. } | AppError::DocumentKind { .. } => {
+                "Invalid JSON document"
+            }
+            AppError::UnsupportedVariant { .. } => "Unsupported sudoku variant",
+            AppError::InvalidInput { .. } => "Invalid input",
+        }
+    }
+
+    /// The offending line of the input and the column of the offending character on it,
+    /// if the error points at one
+    pub fn location(&self) -> Option<(usize, &str, Option<usize>)> {
+        match self {
+            AppError::RowLength { line, content, .. }
+            | AppError::InvalidProof { line, content } => Some((*line, content, None)),
+            AppError::InvalidCell {
+                line,
+                column,
+                content,
+                ..
+            } => Some((*line, content, Some(*column))),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppError::Read { path, source } => {
+                write!(f, "Reading {} failed: {}", path.display(), source)
+            }
+            AppError::Write { path, source } => {
+                write!(f, "Saving {} failed: {}", path.display(), source)
+            }
+            AppError::RowCount { found } => {
+                write!(f, "A sudoku has 9 rows, but {} were found", found)
+            }
+            AppError::RowLength { line, found, .. } => {
+                write!(
+                    f,
+                    "Line {} has {} cells, but a row has 9 and a puzzle on one line 81",
+                    line, found
+                )
+            }
+            AppError::InvalidCell {
+                line,
+                column,
+                found,
+                ..
+            } => write!(
+                f,
+                "Invalid character '{}' on line {}, column {}",
+                found, line, column
+            ),
+            AppError::InvalidProof { line, .. } => {
+                write!(f, "Line {} is not a clause terminated by 0", line)
+            }
+            AppError::ProofExport { reason } => reason.fmt(f),
+            AppError::Solve(SolveFailure::Unsatisfiable) => write!(
+                f,
+                "The clues and the rules of the encoding can not all be true at the same time"
+            ),
+            AppError::Solve(failure) => write!(f, "{}. Raise or clear the limits.", failure),
+            AppError::Solver(error) => error.fmt(f),
+            AppError::ExternalSolver { solver, source } => {
+                write!(f, "Failed to run {}: {}", solver.display(), source)
+            }
+            AppError::NoSolverResult { solver } => write!(f, "{} printed no result", solver),
+            AppError::Json(error) => error.fmt(f),
+            AppError::SchemaVersion { found } => write!(
+                f,
+                "The document has schema version {}, but only version {} is supported",
+                found,
+                crate::schema::SCHEMA_VERSION
+            ),
+            AppError::UnsupportedVariant { found } => write!(
+                f,
+                "The puzzle is a {} sudoku, but only classic sudokus are supported",
+                found.name()
+            ),
+            AppError::DocumentKind { expected, found } => {
+                write!(f, "Expected a {} document, but found {}", expected, found)
+            }
+            AppError::InvalidInput { field, expected } => {
+                write!(f, "{} has to be {}", field, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Read { source, .. }
+            | AppError::Write { source, .. }
+            | AppError::ExternalSolver { source, .. } => Some(source),
+            AppError::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<SolveFailure> for AppError {
+    fn from(failure: SolveFailure) -> Self {
+        AppError::Solve(failure)
+    }
+}
+
+impl From<cadical::Error> for AppError {
+    fn from(error: cadical::Error) -> Self {
+        AppError::Solver(error)
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        AppError::Json(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location() {
+        let error = AppError::InvalidCell {
+            line: 3,
+            column: 5,
+            found: 'x',
+            content: "1234x6789".to_string(),
+        };
+        assert_eq!(error.location(), Some((3, "1234x6789", Some(5))));
+        assert_eq!(
+            error.to_string(),
+            "Invalid character 'x' on line 3, column 5"
+        );
+
+        let error = AppError::RowCount { found: 2 };
+        assert_eq!(error.location(), None);
+        assert_eq!(error.title(), "Invalid sudoku format!");
+    }
 }
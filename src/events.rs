@@ -0,0 +1,271 @@
+//! Log of the solver's search events, recorded through the cadical callbacks
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt,
+    rc::Rc,
+};
+
+use crate::ConstraintList;
+
+/// A single event of the search
+#[derive(Clone, Debug, PartialEq)]
+pub enum SolverEvent {
+    Decision { literal: i32, level: i32 },
+    Learn { index: usize }, // Index of the learned constraint
+    Backtrack { level: i32 },
+    Restart,
+    Reduce { deleted: usize, matched: usize }, // Deleted learned clauses, and how many of them are in the constraint list
+}
+
+impl SolverEvent {
+    /// Name of the kind of the event, used for filtering the log
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SolverEvent::Decision { .. } => "Decision",
+            SolverEvent::Learn { .. } => "Learn",
+            SolverEvent::Backtrack { .. } => "Backtrack",
+            SolverEvent::Restart => "Restart",
+            SolverEvent::Reduce { .. } => "Reduce",
+        }
+    }
+
+    pub fn kinds() -> [&'static str; 5] {
+        ["Decision", "Learn", "Backtrack", "Restart", "Reduce"]
+    }
+}
+
+impl fmt::Display for SolverEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverEvent::Decision { literal, level } => {
+                write!(f, "Decided {} on level {}", literal, level)
+            }
+            SolverEvent::Learn { index } => write!(f, "Learned constraint #{}", index + 1),
+            SolverEvent::Backtrack { level } => write!(f, "Backtracked to level {}", level),
+            SolverEvent::Restart => write!(f, "Restarted"),
+            SolverEvent::Reduce { deleted, matched } => write!(
+                f,
+                "Deleted {} learned clauses, {} of them in the list",
+                deleted, matched
+            ),
+        }
+    }
+}
+
+/// Maximum number of events kept in the log, the oldest events are dropped after this
+pub const MAX_EVENTS: usize = 100_000;
+
+/// Events of the search, and the learned constraints deleted by reductions.
+/// Shared between the callback wrapper and the GUI like `ConstraintList`.
+#[derive(Clone, Default)]
+pub struct EventLog {
+    pub events: Rc<RefCell<VecDeque<SolverEvent>>>,
+    pub dropped: Rc<Cell<usize>>, // Number of the oldest events dropped to keep the log within `MAX_EVENTS`
+    pub deleted: Rc<RefCell<BTreeMap<usize, usize>>>, // Index of a deleted constraint and the number of constraints learned before the deletion
+    learned: Rc<RefCell<LearnedIndex>>,
+}
+
+/// Indexes of the learned constraints that have not been deleted, by their sorted literals.
+/// Kept up to date across reductions instead of rebuilding it from the whole list every time.
+#[derive(Default)]
+struct LearnedIndex {
+    indexed: usize, // Number of constraints of the list added to the index
+    undeleted: HashMap<Vec<i32>, VecDeque<usize>>,
+}
+
+impl LearnedIndex {
+    /// Adds the constraints learned since the last update
+    fn update(&mut self, constraints: &ConstraintList) {
+        let constraints = constraints.borrow();
+        if constraints.len() < self.indexed {
+            // The list was cleared without clearing the log
+            *self = Self::default();
+        }
+        for (index, constraint) in constraints.iter().enumerate().skip(self.indexed) {
+            let mut literals = constraint.clone();
+            literals.sort();
+            self.undeleted.entry(literals).or_default().push_back(index);
+        }
+        self.indexed = constraints.len();
+    }
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, event: SolverEvent) {
+        let mut events = self.events.borrow_mut();
+        if events.len() == MAX_EVENTS {
+            events.pop_front();
+            self.dropped.set(self.dropped.get() + 1);
+        }
+        events.push_back(event);
+    }
+
+    pub fn clear(&mut self) {
+        self.events.borrow_mut().clear();
+        self.dropped.set(0);
+        self.deleted.borrow_mut().clear();
+        *self.learned.borrow_mut() = LearnedIndex::default();
+    }
+
+    /// Number of events kept in the log
+    pub fn len(&self) -> usize {
+        self.events.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.borrow().is_empty()
+    }
+
+    /// Number of constraints learned before the constraint was deleted, None if it has not been deleted
+    pub fn deleted_after(&self, index: usize) -> Option<usize> {
+        self.deleted.borrow().get(&index).copied()
+    }
+
+    /// Marks the learned constraints with the same literals as the deleted clauses as deleted.
+    /// The solver may have strengthened a clause after learning it, in which case it is not found.
+    /// Returns the number of constraints marked.
+    pub fn mark_deleted(
+        &mut self,
+        deleted_clauses: &[Vec<i32>],
+        constraints: &ConstraintList,
+    ) -> usize {
+        let mut deleted = self.deleted.borrow_mut();
+        let mut learned = self.learned.borrow_mut();
+        learned.update(constraints);
+
+        let learned_count = constraints.len();
+        let mut matched = 0;
+        for clause in deleted_clauses {
+            let mut literals = clause.clone();
+            literals.sort();
+            // The same clause can be learned more than once, the earliest copy is deleted first
+            if let Some(index) = learned
+                .undeleted
+                .get_mut(&literals)
+                .and_then(|indexes| indexes.pop_front())
+            {
+                deleted.insert(index, learned_count);
+                matched += 1;
+            }
+        }
+        matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cadical::Solver;
+
+    use crate::{
+        app_state::EncodingType,
+        cadical_wrapper::CadicalCallbackWrapper,
+        sudoku::{get_sudoku, solve_sudoku},
+        Trail,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_mark_deleted() {
+        let mut constraints = ConstraintList::new();
+        constraints.push(vec![1, 2, 3]);
+        constraints.push(vec![-4, 5]);
+        constraints.push(vec![3, 2, 1]);
+        let mut log = EventLog::new();
+
+        // Literal order does not matter, and an unknown clause is not matched
+        let matched = log.mark_deleted(&[vec![2, 1, 3], vec![6, 7]], &constraints);
+        assert_eq!(matched, 1);
+        assert_eq!(log.deleted_after(0), Some(3));
+        assert_eq!(log.deleted_after(2), None);
+
+        // The second copy is deleted next
+        assert_eq!(log.mark_deleted(&[vec![1, 2, 3]], &constraints), 1);
+        assert_eq!(log.deleted_after(2), Some(3));
+        assert_eq!(log.mark_deleted(&[vec![1, 2, 3]], &constraints), 0);
+
+        // Constraints learned after the previous reduction are matched too
+        constraints.push(vec![-5, 4]);
+        assert_eq!(log.mark_deleted(&[vec![4, -5]], &constraints), 1);
+        assert_eq!(log.deleted_after(3), Some(4));
+
+        log.push(SolverEvent::Restart);
+        assert_eq!(log.len(), 1);
+        log.clear();
+        assert!(log.is_empty());
+        assert_eq!(log.deleted_after(0), None);
+
+        // A cleared list is indexed again from the start
+        let mut constraints = ConstraintList::new();
+        constraints.push(vec![6, 7]);
+        assert_eq!(log.mark_deleted(&[vec![7, 6]], &constraints), 1);
+        assert_eq!(log.deleted_after(0), Some(1));
+    }
+
+    #[test]
+    fn test_log_is_capped() {
+        let mut log = EventLog::new();
+        for level in 0..MAX_EVENTS + 10 {
+            log.push(SolverEvent::Backtrack {
+                level: level as i32,
+            });
+        }
+        assert_eq!(log.len(), MAX_EVENTS);
+        assert_eq!(log.dropped.get(), 10);
+        assert_eq!(
+            log.events.borrow().front(),
+            Some(&SolverEvent::Backtrack { level: 10 })
+        );
+        log.clear();
+        assert_eq!(log.dropped.get(), 0);
+    }
+
+    #[test]
+    fn test_event_display() {
+        assert_eq!(
+            SolverEvent::Learn { index: 0 }.to_string(),
+            "Learned constraint #1"
+        );
+        assert_eq!(
+            SolverEvent::Backtrack { level: 2 }.to_string(),
+            "Backtracked to level 2"
+        );
+        assert!(SolverEvent::kinds().contains(&SolverEvent::Restart.kind()));
+    }
+
+    #[test]
+    fn test_events_of_solve() {
+        let constraints = ConstraintList::new();
+        let wrapper = CadicalCallbackWrapper::new(constraints.clone(), Trail::new());
+        let events = wrapper.events.clone();
+        let mut solver = Solver::with_config("plain").unwrap();
+        solver.set_callbacks(Some(wrapper));
+        let clues = get_sudoku("data/hardest-ever-sudoku.txt".to_string()).unwrap();
+        let encoding = EncodingType::Decimal {
+            cell_at_least_one: true,
+            cell_at_most_one: false,
+            sudoku_has_all_values: false,
+            sudoku_has_unique_values: true,
+        };
+        solve_sudoku(&clues, &mut solver, &encoding).unwrap();
+
+        let count = |kind: &str| {
+            events
+                .events
+                .borrow()
+                .iter()
+                .filter(|event| event.kind() == kind)
+                .count()
+        };
+        assert_eq!(count("Decision") as i64, solver.stats().decisions);
+        assert_eq!(count("Learn"), constraints.len());
+        assert_eq!(count("Restart") as i64, solver.stats().restarts);
+        assert!(count("Backtrack") > 0);
+    }
+}
@@ -0,0 +1,508 @@
+//! Running solver executables, for example kissat or minisat, on the encoded sudoku.
+//! The clauses are written to a DIMACS file, the solver is run on it with a timeout and the
+//! result, the model and the statistics are parsed from the lines it prints in the format of
+//! the SAT competition:
+//! - `s SATISFIABLE`, `s UNSATISFIABLE` or `s UNKNOWN`
+//! - `v` lines with the values of the variables, ending in 0
+//! - comment lines with statistics, like `c conflicts: 123` of kissat and cadical or
+//!   `conflicts : 123` of minisat
+
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use cadical::CadicalStats;
+
+use crate::{
+    backend::{SolveLimits, SolverBackend},
+    cadical_wrapper::CadicalCallbackWrapper,
+    AppError,
+};
+
+/// Counter for the names of the DIMACS files, so that solvers running at the same time do not
+/// share a file
+static DIMACS_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// A solver executable, that is run on the clauses collected with `add_clause`
+pub struct ExternalSolver {
+    path: PathBuf,
+    timeout: Duration,
+    time_limit: Option<Duration>, // Time limit of the next solve, if it is shorter than the timeout
+    limit_arguments: Vec<String>, // Options for the other limits of the next solve
+    clauses: Vec<Vec<i32>>,
+    status: Option<bool>,
+    model: Vec<bool>, // Value of each variable in the latest model, index 0 is unused
+    output: SolverOutput,
+    real_time: f64,
+    error: Option<AppError>, // Why the latest solve failed, None if it did not
+}
+
+impl ExternalSolver {
+    pub fn new(path: PathBuf, timeout: Duration) -> Self {
+        Self {
+            path,
+            timeout,
+            time_limit: None,
+            limit_arguments: Vec::new(),
+            clauses: Vec::new(),
+            status: None,
+            model: Vec::new(),
+            output: SolverOutput::default(),
+            real_time: 0.0,
+            error: None,
+        }
+    }
+
+    /// Name of the executable, used to tag its statistics
+    pub fn name(&self) -> String {
+        solver_name(&self.path)
+    }
+
+    /// Takes the error of the latest solve, if it failed because the solver could not be run
+    /// or printed no result
+    pub fn take_error(&mut self) -> Option<AppError> {
+        self.error.take()
+    }
+
+    /// Writes the clauses and the assumptions as unit clauses to a DIMACS file and runs the
+    /// solver on it. Returns the result the solver printed, None for `s UNKNOWN` and if the
+    /// solver did not finish in time.
+    pub fn run(&mut self, assumptions: &[i32]) -> Result<Option<bool>, AppError> {
+        self.status = None;
+        self.model.clear();
+
+        let mut clauses = self.clauses.clone();
+        clauses.extend(assumptions.iter().map(|&literal| vec![literal]));
+        let file = dimacs_file_path();
+        fs::write(&file, dimacs(&clauses)).map_err(|source| AppError::Write {
+            path: file.clone(),
+            source,
+        })?;
+
+        let start = Instant::now();
+        let timeout = self
+            .time_limit
+            .take()
+            .map_or(self.timeout, |limit| limit.min(self.timeout));
+        let arguments = std::mem::take(&mut self.limit_arguments);
+        let result = run_with_timeout(&self.path, &arguments, &file, timeout);
+        self.real_time = start.elapsed().as_secs_f64();
+        let _ = fs::remove_file(&file);
+
+        let (stdout, timed_out) = result?;
+        if timed_out {
+            self.output = SolverOutput::default();
+            return Ok(None);
+        }
+
+        self.output = parse_output(&stdout);
+        let Some(status) = self.output.status else {
+            return Err(AppError::NoSolverResult {
+                solver: self.name(),
+            });
+        };
+        if let Some(true) = status {
+            self.model = self.output.model.clone();
+        }
+        self.status = status;
+        Ok(status)
+    }
+}
+
+impl SolverBackend for ExternalSolver {
+    /// The solver runs in another process, so it can not report its search
+    type Callbacks = CadicalCallbackWrapper;
+
+    fn add_clause(&mut self, clause: Vec<i32>) {
+        self.clauses.push(clause);
+    }
+
+    fn solve_with(&mut self, assumptions: Vec<i32>) -> Option<bool> {
+        match self.run(&assumptions) {
+            Ok(status) => status,
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+
+    fn status(&self) -> Option<bool> {
+        self.status
+    }
+
+    fn value(&self, literal: i32) -> Option<bool> {
+        self.model
+            .get(literal.unsigned_abs() as usize)
+            .map(|&value| value == (literal > 0))
+    }
+
+    /// The solver does not tell which literals it fixed
+    fn fixed(&self, _literal: i32) -> i32 {
+        0
+    }
+
+    fn stats(&mut self) -> CadicalStats {
+        CadicalStats {
+            process_time: self.output.process_time.unwrap_or(self.real_time),
+            real_time: self.real_time,
+            max_resident_set_size_mb: self.output.memory_mb.unwrap_or(0.0),
+            conflicts: self.output.conflicts,
+            learned_clauses: self.output.learned_clauses,
+            learned_literals: 0,
+            decisions: self.output.decisions,
+            restarts: self.output.restarts,
+            propagations: self.output.propagations,
+        }
+    }
+
+    fn set_callbacks(&mut self, _callbacks: Option<Self::Callbacks>) {}
+
+    /// The time limit is kept by stopping the solver. The other limits are passed in the
+    /// options of the solver, if it is known to have them.
+    fn set_limits(&mut self, limits: &SolveLimits) {
+        self.time_limit = limits
+            .seconds
+            .map(|seconds| Duration::from_secs(seconds as u64));
+        self.limit_arguments = limit_arguments(&self.name(), limits).0;
+    }
+}
+
+/// Name of the solver executable, used to tag its statistics and to find its options
+pub fn solver_name(path: &Path) -> String {
+    path.file_stem().map_or(path.display().to_string(), |stem| {
+        stem.to_string_lossy().to_string()
+    })
+}
+
+/// Command line options for the conflict, decision and propagation limits, and the names of the
+/// limits the solver has no option for. The options are known for kissat and cadical.
+pub fn limit_arguments(solver: &str, limits: &SolveLimits) -> (Vec<String>, Vec<&'static str>) {
+    let solver = solver.to_lowercase();
+    let mut arguments = Vec::new();
+    let mut unsupported = Vec::new();
+    for (name, limit) in limits.named() {
+        let Some(limit) = limit else {
+            continue;
+        };
+        match name {
+            "time" => {}
+            "conflicts" | "decisions" if solver.starts_with("kissat") => {
+                arguments.push(format!("--{}={}", name, limit))
+            }
+            "conflicts" if solver.starts_with("cadical") => {
+                arguments.extend(["-c".to_string(), limit.to_string()])
+            }
+            "decisions" if solver.starts_with("cadical") => {
+                arguments.extend(["-d".to_string(), limit.to_string()])
+            }
+            _ => unsupported.push(name),
+        }
+    }
+    (arguments, unsupported)
+}
+
+/// What a solver printed. Statistics it did not print are 0 or None.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolverOutput {
+    pub status: Option<Option<bool>>, // None if there was no `s` line, Some(None) for `s UNKNOWN`
+    pub model: Vec<bool>,             // Value of each variable, index 0 is unused
+    pub process_time: Option<f64>,
+    pub memory_mb: Option<f64>,
+    pub conflicts: i64,
+    pub learned_clauses: i64,
+    pub decisions: i64,
+    pub restarts: i64,
+    pub propagations: i64,
+}
+
+/// Parses the result, the model and the statistics from the output of a solver
+pub fn parse_output(output: &str) -> SolverOutput {
+    let mut parsed = SolverOutput::default();
+    for line in output.lines().map(str::trim) {
+        if let Some(result) = line.strip_prefix("s ") {
+            parsed.status = match result.trim() {
+                "SATISFIABLE" => Some(Some(true)),
+                "UNSATISFIABLE" => Some(Some(false)),
+                _ => Some(None),
+            };
+        } else if let Some(values) = line.strip_prefix("v ") {
+            for literal in values
+                .split_whitespace()
+                .filter_map(|v| v.parse::<i32>().ok())
+            {
+                let variable = literal.unsigned_abs() as usize;
+                if variable == 0 {
+                    continue;
+                }
+                if parsed.model.len() <= variable {
+                    parsed.model.resize(variable + 1, false);
+                }
+                parsed.model[variable] = literal > 0;
+            }
+        } else {
+            parse_statistic(&mut parsed, line.strip_prefix("c ").unwrap_or(line));
+        }
+    }
+    parsed
+}
+
+/// Parses a statistics line of the form `name: value unit ...`. Lines of other forms are ignored.
+fn parse_statistic(parsed: &mut SolverOutput, line: &str) {
+    let Some((name, value)) = line.split_once(':') else {
+        return;
+    };
+    let name = name.trim().to_lowercase().replace('-', " ");
+    let tokens: Vec<&str> = value.split_whitespace().collect();
+    let Some(number) = tokens.first().and_then(|token| token.parse::<f64>().ok()) else {
+        return;
+    };
+
+    match name.as_str() {
+        "conflicts" => parsed.conflicts = number as i64,
+        "learned" => parsed.learned_clauses = number as i64,
+        "decisions" => parsed.decisions = number as i64,
+        "restarts" => parsed.restarts = number as i64,
+        "propagations" => parsed.propagations = number as i64,
+        "process time" | "cpu time" | "total process time since initialization" => {
+            parsed.process_time = Some(number)
+        }
+        "maximum resident set size" | "maximum resident set size of process" | "memory used" => {
+            // The size is printed in bytes, in MB or both
+            parsed.memory_mb = tokens
+                .windows(2)
+                .find(|pair| pair[1] == "MB")
+                .and_then(|pair| pair[0].parse().ok())
+                .or_else(|| match tokens.get(1) {
+                    Some(&"bytes") => Some(number / (1024.0 * 1024.0)),
+                    _ => None,
+                });
+        }
+        _ => {}
+    }
+}
+
+/// The clauses in DIMACS CNF format
+pub fn dimacs(clauses: &[Vec<i32>]) -> String {
+    let variables = clauses
+        .iter()
+        .flatten()
+        .map(|literal| literal.abs())
+        .max()
+        .unwrap_or(0);
+    let mut dimacs = format!("p cnf {} {}\n", variables, clauses.len());
+    for clause in clauses {
+        for literal in clause {
+            dimacs.push_str(&format!("{} ", literal));
+        }
+        dimacs.push_str("0\n");
+    }
+    dimacs
+}
+
+fn dimacs_file_path() -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "sat-step-{}-{}.cnf",
+        std::process::id(),
+        DIMACS_FILES.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Runs the solver with the arguments on the file and returns what it printed and whether it
+/// was killed because it did not finish in time
+fn run_with_timeout(
+    solver: &Path,
+    arguments: &[String],
+    file: &Path,
+    timeout: Duration,
+) -> Result<(String, bool), AppError> {
+    let mut child = Command::new(solver)
+        .args(arguments)
+        .arg(file)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|source| AppError::ExternalSolver {
+            solver: solver.to_path_buf(),
+            source,
+        })?;
+
+    // The output is read in another thread, so that the solver does not block on a full pipe
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stdout.read_to_string(&mut output);
+        output
+    });
+
+    let start = Instant::now();
+    let mut timed_out = false;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if start.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                timed_out = true;
+                break;
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(e) => {
+                return Err(AppError::ExternalSolver {
+                    solver: solver.to_path_buf(),
+                    source: e,
+                })
+            }
+        }
+    }
+
+    Ok((reader.join().unwrap_or_default(), timed_out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dimacs() {
+        let clauses = vec![vec![1, -3], vec![2], vec![]];
+        assert_eq!(dimacs(&clauses), "p cnf 3 3\n1 -3 0\n2 0\n0\n");
+    }
+
+    #[test]
+    fn test_parse_kissat_output() {
+        let output = "c ---- [ result ] ----\n\
+            s SATISFIABLE\n\
+            v 1 -2 3\n\
+            v -5 0\n\
+            c ---- [ statistics ] ----\n\
+            c conflicts:                               12      1200.00 per second\n\
+            c decisions:                               40      3.33 per conflict\n\
+            c learned:                                 11     92 % conflicts\n\
+            c propagations:                          1234      0.12 M per second\n\
+            c restarts:                                 1     12.00 interval\n\
+            c maximum-resident-set-size:          8388608 bytes          8 MB\n\
+            c process-time:                             0.01    seconds\n";
+        let parsed = parse_output(output);
+
+        assert_eq!(parsed.status, Some(Some(true)));
+        assert_eq!(parsed.model, vec![false, true, false, true, false, false]);
+        assert_eq!(parsed.conflicts, 12);
+        assert_eq!(parsed.decisions, 40);
+        assert_eq!(parsed.learned_clauses, 11);
+        assert_eq!(parsed.propagations, 1234);
+        assert_eq!(parsed.restarts, 1);
+        assert_eq!(parsed.memory_mb, Some(8.0));
+        assert_eq!(parsed.process_time, Some(0.01));
+    }
+
+    #[test]
+    fn test_parse_minisat_output() {
+        let output = "restarts              : 2\n\
+            conflicts             : 6              (inf /sec)\n\
+            decisions             : 41             (0.00 % random) (inf /sec)\n\
+            propagations          : 1123           (inf /sec)\n\
+            Memory used           : 9.00 MB\n\
+            CPU time              : 0.004 s\n\
+            \n\
+            s UNSATISFIABLE\n";
+        let parsed = parse_output(output);
+
+        assert_eq!(parsed.status, Some(Some(false)));
+        assert!(parsed.model.is_empty());
+        assert_eq!(parsed.restarts, 2);
+        assert_eq!(parsed.conflicts, 6);
+        assert_eq!(parsed.decisions, 41);
+        assert_eq!(parsed.propagations, 1123);
+        assert_eq!(parsed.memory_mb, Some(9.0));
+        assert_eq!(parsed.process_time, Some(0.004));
+
+        assert_eq!(parse_output("s UNKNOWN\n").status, Some(None));
+        assert_eq!(parse_output("c nothing\n").status, None);
+    }
+
+    #[test]
+    fn test_limit_arguments() {
+        let limits = SolveLimits {
+            seconds: Some(5),
+            conflicts: Some(100),
+            decisions: None,
+            propagations: Some(1000),
+        };
+        assert_eq!(
+            limit_arguments("kissat", &limits),
+            (vec!["--conflicts=100".to_string()], vec!["propagations"])
+        );
+        assert_eq!(
+            limit_arguments("cadical", &limits),
+            (
+                vec!["-c".to_string(), "100".to_string()],
+                vec!["propagations"]
+            )
+        );
+        assert_eq!(
+            limit_arguments("minisat", &limits),
+            (Vec::new(), vec!["conflicts", "propagations"])
+        );
+        assert_eq!(
+            limit_arguments("minisat", &SolveLimits::default()),
+            (Vec::new(), Vec::new())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_script() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let write_script = |name: &str, body: &str| {
+            let path = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+            fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+            path
+        };
+
+        let path = write_script(
+            "sat-step-fake-solver",
+            "echo 's SATISFIABLE'; echo 'v -1 2 0'",
+        );
+        let mut solver = ExternalSolver::new(path.clone(), Duration::from_secs(10));
+        solver.add_clause(vec![-1]);
+        solver.add_clause(vec![1, 2]);
+        assert_eq!(solver.solve(), Some(true));
+        assert_eq!(solver.value(1), Some(false));
+        assert_eq!(solver.value(-1), Some(true));
+        assert_eq!(solver.value(2), Some(true));
+        assert!(solver.take_error().is_none());
+        assert!(solver.name().starts_with("sat-step-fake-solver"));
+        fs::remove_file(path).unwrap();
+
+        // The limits are passed before the file to a solver that has options for them
+        let path = write_script(
+            "kissat-fake",
+            "[ \"$1\" = --conflicts=10 ] && echo 's UNKNOWN' || echo 's SATISFIABLE'",
+        );
+        let mut solver = ExternalSolver::new(path.clone(), Duration::from_secs(10));
+        solver.set_limits(&SolveLimits {
+            conflicts: Some(10),
+            ..Default::default()
+        });
+        assert_eq!(solver.solve(), None);
+        // The limits apply to a single solve
+        assert_eq!(solver.solve(), Some(true));
+        fs::remove_file(path).unwrap();
+
+        // A solver that does not finish in time is stopped without a result
+        let path = write_script("sat-step-slow-solver", "exec sleep 5");
+        let mut solver = ExternalSolver::new(path.clone(), Duration::from_millis(100));
+        assert_eq!(solver.solve(), None);
+        assert!(solver.take_error().is_none());
+        fs::remove_file(path).unwrap();
+    }
+}
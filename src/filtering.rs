@@ -59,6 +59,19 @@ impl ListFilter {
         )
     }
 
+    /// Get the indexes of the filtered and paged constraints in the full constraint list
+    pub fn get_filtered_indexes(&self, page_number: usize, page_length: usize) -> Vec<usize> {
+        let index_list = self.get_filtered_index_list();
+        let begin: usize = std::cmp::min(index_list.len(), page_number * page_length);
+        let stop: usize = std::cmp::min(index_list.len(), (page_number + 1) * page_length);
+        index_list[begin..stop].to_vec()
+    }
+
+    /// Position of a constraint in the filtered list, None if the filters hide it
+    pub fn filtered_position(&self, index: usize) -> Option<usize> {
+        self.get_filtered_index_list().binary_search(&index).ok()
+    }
+
     /// Kept in case there is a need to reinit more things in future
     pub fn reinit(&mut self, encoding: &EncodingType) {
         self.create_cell_map(encoding);
@@ -74,30 +87,9 @@ impl ListFilter {
         for (index, list) in self.constraints.borrow().iter().enumerate() {
             for identifier in list {
                 let var = CnfVariable::from_cnf(*identifier, encoding);
-                match var {
-                    CnfVariable::Bit { row, col, .. } => {
-                        if let Some(cell_set) = self.cell_constraints.get_mut(&(row, col)) {
-                            cell_set.insert(index);
-                        }
-                    }
-                    CnfVariable::Decimal { row, col, .. } => {
-                        if let Some(cell_set) = self.cell_constraints.get_mut(&(row, col)) {
-                            cell_set.insert(index);
-                        }
-                    }
-                    CnfVariable::Equality {
-                        row,
-                        col,
-                        row2,
-                        col2,
-                        ..
-                    } => {
-                        if let Some(cell_set) = self.cell_constraints.get_mut(&(row, col)) {
-                            cell_set.insert(index);
-                        }
-                        if let Some(cell_set) = self.cell_constraints.get_mut(&(row2, col2)) {
-                            cell_set.insert(index);
-                        }
+                for cell in var.cells() {
+                    if let Some(cell_set) = self.cell_constraints.get_mut(&cell) {
+                        cell_set.insert(index);
                     }
                 }
             }
@@ -196,6 +188,8 @@ mod tests {
         assert_eq!(filtered_trails.len(), filtered_constraints.len());
         assert_eq!(filtered_length, filtered_constraints.len());
 
+        assert_eq!(filter.get_filtered_indexes(0, 50), vec![1]);
+
         filter.by_max_length(5);
         let (filtered_constraints2, filtered_trails2, filtered_length2) =
             filter.get_filtered(0, 50);
@@ -341,7 +335,7 @@ mod tests {
         assert_eq!(cleared_trails2.len(), cleared_constraints2.len());
         assert_eq!(cleared_length2, cleared_constraints2.len());
 
-        let _ = filter.by_cell(1, 1);
+        filter.by_cell(1, 1);
         filter.by_max_length(3);
         let (filtered_constraints3, filtered_trails3, filtered_length3) =
             filter.get_filtered(0, 50);
@@ -432,6 +426,23 @@ mod tests {
         assert_eq!(index_list, vec![0, 1, 2, 3, 4]);
     }
 
+    #[test]
+    fn test_filtered_position() {
+        let constraints = ConstraintList::_new(Rc::new(RefCell::new(vec![
+            vec![0; 10],
+            vec![0; 3],
+            vec![0; 5],
+            vec![0; 2],
+        ])));
+        let mut filter: ListFilter = ListFilter::new(constraints, Trail::new());
+        assert_eq!(filter.filtered_position(2), Some(2));
+
+        filter.by_max_length(4);
+        assert_eq!(filter.filtered_position(0), None);
+        assert_eq!(filter.filtered_position(1), Some(0));
+        assert_eq!(filter.filtered_position(3), Some(1));
+    }
+
     #[test]
     fn test_get_filtered_index_list() {
         let constraints = ConstraintList::_new(Rc::new(RefCell::new(vec![
@@ -461,6 +472,6 @@ mod tests {
         filter.by_max_length(1);
 
         let index_list = filter.get_filtered_index_list();
-        assert_eq!(index_list, Vec::new());
+        assert!(index_list.is_empty());
     }
 }
@@ -1,10 +1,22 @@
 //! High-level GUI code. Most of the actual GUI is done is sub-modules under src/gui/
 
+mod activity;
 mod controllable_list;
 mod controls;
+mod events;
+mod heatmap;
+mod human_solver;
+mod proof;
+mod replay;
+mod resolution;
 mod statistics;
 pub mod sudoku_cell;
 mod sudoku_grid;
+mod timeline;
+mod trail_diff;
+mod trail_panel;
+
+use std::thread::JoinHandle;
 
 use cadical::Solver;
 use eframe::egui;
@@ -12,11 +24,26 @@ use egui::containers;
 use egui::Color32;
 use egui::Margin;
 use egui::RichText;
+use egui::Ui;
 
+use crate::activity::VariableActivity;
+use crate::backend::{SolveLimits, SolverBackend};
+use crate::cdcl::CdclSolver;
+use crate::difficulty::Difficulty;
 use crate::get_cell;
+use crate::history::EditHistory;
+use crate::human_solver::HumanSolution;
+use crate::sudoku::conflicting_clues;
+use crate::validation::{model_values, validate_model, RuleViolation};
 use crate::{
-    app_state::AppState, cadical_wrapper::CadicalCallbackWrapper, cnf::CnfVariable,
-    error::GenericError, gui::sudoku_cell::SudokuCell, warning::Warning, ConstraintList, Trail,
+    app_state::{AppState, EncodingType},
+    cadical_wrapper::CadicalCallbackWrapper,
+    cnf::CnfVariable,
+    error::AppError,
+    gui::sudoku_cell::SudokuCell,
+    proof::Proof,
+    warning::Warning,
+    ConstraintList, Trail,
 };
 
 /// Main app struct
@@ -26,10 +53,19 @@ pub struct SATApp {
     trails: Trail,
     callback_wrapper: CadicalCallbackWrapper,
     solver: Solver<CadicalCallbackWrapper>,
+    cdcl_solver: Option<CdclSolver<CadicalCallbackWrapper>>, // Solver of the latest solve, if it was the Rust solver instead of cadical
     rendered_constraints: Vec<Vec<CnfVariable>>,
     rendered_trails: Trail,
     state: AppState,
-    current_error: Option<GenericError>,
+    current_error: Option<AppError>,
+    proof: Option<Proof>,
+    rule_violations: Vec<RuleViolation>,
+    incremental_encoding: Option<EncodingType>, // Encoding of the rules in an incrementally used solver
+    edit_history: EditHistory,
+    human_solution: Option<HumanSolution>,
+    difficulty: Option<Difficulty>,
+    variable_activity: Option<VariableActivity>, // Decision counts and activities after the latest solve
+    external_run: Option<JoinHandle<Result<(), AppError>>>, // Worker thread of the external solver, while it runs
 }
 
 impl SATApp {
@@ -47,13 +83,32 @@ impl SATApp {
             trails,
             callback_wrapper,
             solver,
+            cdcl_solver: None,
             rendered_constraints: Vec::new(),
             rendered_trails: Trail::new(),
             state,
             current_error,
+            proof: None,
+            rule_violations: Vec::new(),
+            incremental_encoding: None,
+            edit_history: EditHistory::default(),
+            human_solution: None,
+            difficulty: None,
+            variable_activity: None,
+            external_run: None,
         }
     }
 
+    /// Sets the solve limits from command line arguments, see `SolveLimits::from_args`
+    pub fn set_limits_from_args(
+        &mut self,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<(), String> {
+        let limits = SolveLimits::from_args(args)?;
+        self.state.set_solve_limits(&limits);
+        Ok(())
+    }
+
     /// Converts self.sudoku to a `Vec<Vec<i32>>`. This format is sometimes easier to handle.
     pub fn get_option_value_sudoku(&self) -> Vec<Vec<Option<i32>>> {
         let mut sudoku = Vec::new();
@@ -67,6 +122,18 @@ impl SATApp {
         sudoku
     }
 
+    /// Like `get_option_value_sudoku`, but only the clues are included
+    pub fn get_clues(&self) -> Vec<Vec<Option<i32>>> {
+        self.sudoku
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| if cell.clue { cell.value } else { None })
+                    .collect()
+            })
+            .collect()
+    }
+
     /// Converts a sudoku in `Vec<Vec<i32>>` format to a sudoku in `Vec<Vec<SudokuCell>>` format,
     /// and sets it as the current sudoku.
     pub fn sudoku_from_option_values(&mut self, sudoku: &[Vec<Option<i32>>], add_new_clues: bool) {
@@ -84,14 +151,26 @@ impl SATApp {
 
     /// Set a value to specific cell using row and column (1-9 indexed)
     fn set_cell(&mut self, row: i32, col: i32, value: Option<i32>, add_new_clue: bool) {
+        let fixed =
+            value.is_some_and(|val| self.state.encoding.fixed(self.backend(), row, col, val));
         let target_cell = get_cell(&mut self.sudoku, row, col);
+
+        // If the clues change, the human solution and the rating no longer hold
+        let clue_before = target_cell.clue.then_some(target_cell.value);
+        let clue_after = (value.is_some() && (add_new_clue || target_cell.clue)).then_some(value);
+        if clue_before != clue_after {
+            self.human_solution = None;
+            self.difficulty = None;
+        }
         target_cell.value = value;
+        target_cell.conflicting_clue = false;
+        target_cell.rule_violations.clear();
 
-        if let Some(val) = value {
+        if value.is_some() {
             if add_new_clue {
                 target_cell.clue = true;
             }
-            if self.state.encoding.fixed(&self.solver, row, col, val) {
+            if fixed {
                 target_cell.fixed = true;
             }
         } else {
@@ -100,18 +179,114 @@ impl SATApp {
         }
     }
 
-    /// Resets the solver so that it can be used again. Using the same solver twice does not work.
+    /// Candidates (pencil marks) of each cell that is not a clue
+    pub fn get_candidates(&self) -> Vec<Vec<Vec<i32>>> {
+        self.sudoku
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| {
+                        if cell.clue {
+                            Vec::new()
+                        } else {
+                            cell.candidates.iter().cloned().collect()
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Adds the value as a candidate of the cell, or removes it if it already is one
+    fn toggle_candidate(&mut self, row: i32, col: i32, value: i32) {
+        let cell = get_cell(&mut self.sudoku, row, col);
+        if !cell.candidates.remove(&value) {
+            cell.candidates.insert(value);
+        }
+    }
+
+    fn clear_candidates(&mut self) {
+        for cell in self.sudoku.iter_mut().flatten() {
+            cell.candidates.clear();
+        }
+    }
+
+    /// Set a clue through the editor, and record the change in the edit history
+    fn edit_cell(&mut self, row: i32, col: i32, value: Option<i32>) {
+        let before = self.get_clues();
+        self.set_cell(row, col, value, value.is_some());
+        self.edit_history.record(before, &self.get_clues());
+    }
+
+    /// Reverts the latest edit of the clues
+    fn undo_edit(&mut self) {
+        if let Some(previous) = self.edit_history.undo(self.get_clues()) {
+            self.restore_clues(&previous);
+        }
+    }
+
+    /// Reapplies the latest reverted edit of the clues
+    fn redo_edit(&mut self) {
+        if let Some(next) = self.edit_history.redo(self.get_clues()) {
+            self.restore_clues(&next);
+        }
+    }
+
+    /// Replaces the clues with ones from the edit history. The old solution is no longer valid.
+    fn restore_clues(&mut self, clues: &[Vec<Option<i32>>]) {
+        if self.state.incremental {
+            self.clear_solved_sudoku();
+        } else {
+            self.reset_cadical_and_solved_sudoku();
+        }
+        self.sudoku_from_option_values(clues, true);
+    }
+
+    /// Resets the solver so that it can be used again. Using the same solver twice does not work,
+    /// unless the clues are given as assumptions (see `prepare_incremental_solve`).
     fn reset_cadical_and_solved_sudoku(&mut self) {
         self.constraints.clear();
         self.trails.clear();
         self.rendered_constraints.clear();
         self.state.reinit();
+        self.state.reused_constraint_count = 0;
+        self.state.compared_constraint_index = None;
+        self.incremental_encoding = None;
+        self.cdcl_solver = None;
+        self.state.limit_reached = false;
         self.solver = Solver::with_config("plain").unwrap();
         self.callback_wrapper =
             CadicalCallbackWrapper::new(self.constraints.clone(), self.trails.clone());
         self.solver
             .set_callbacks(Some(self.callback_wrapper.clone()));
 
+        self.clear_solved_sudoku();
+    }
+
+    /// Prepares the solver for an incremental solve. A new solver is only created if the encoding
+    /// has changed, otherwise the rules and the constraints learned in earlier solves are kept.
+    /// Returns true if the rules still have to be added to the solver.
+    fn prepare_incremental_solve(&mut self) -> bool {
+        if self.incremental_encoding == Some(self.state.encoding) {
+            self.clear_solved_sudoku();
+            self.state.reused_constraint_count = self.constraints.len();
+            false
+        } else {
+            self.reset_cadical_and_solved_sudoku();
+            self.incremental_encoding = Some(self.state.encoding);
+            true
+        }
+    }
+
+    /// Returns the sudoku to an unsolved state, without touching the solver
+    fn clear_solved_sudoku(&mut self) {
+        self.proof = None;
+        self.variable_activity = None;
+        self.state.replay.stop();
+        self.rule_violations.clear();
+        self.state.show_proof = false;
+        self.state.selected_proof_step = None;
+
         // We want to keep the sudoku, but return it to an unsolved state
         for row in self.sudoku.iter_mut() {
             for cell in row.iter_mut() {
@@ -119,6 +294,44 @@ impl SATApp {
                     cell.value = None;
                 }
                 cell.fixed = false;
+                cell.conflicting_clue = false;
+                cell.rule_violations.clear();
+            }
+        }
+    }
+
+    /// Solver of the latest solve, cadical unless the sudoku was solved with the Rust solver
+    fn backend(&self) -> &dyn SolverBackend<Callbacks = CadicalCallbackWrapper> {
+        match &self.cdcl_solver {
+            Some(solver) => solver,
+            None => &self.solver,
+        }
+    }
+
+    fn backend_mut(&mut self) -> &mut dyn SolverBackend<Callbacks = CadicalCallbackWrapper> {
+        match &mut self.cdcl_solver {
+            Some(solver) => solver,
+            None => &mut self.solver,
+        }
+    }
+
+    /// Checks the solver's model against the rules of sudoku, and marks the cells that break them
+    fn highlight_rule_violations(&mut self) {
+        self.rule_violations = validate_model(&model_values(self.backend(), &self.state.encoding));
+        for violation in &self.rule_violations {
+            for (row, col) in violation.cells() {
+                get_cell(&mut self.sudoku, row, col)
+                    .rule_violations
+                    .push(violation.to_string());
+            }
+        }
+    }
+
+    /// Highlights a minimal set of clues that make the sudoku unsolvable
+    fn highlight_conflicting_clues(&mut self, clues: &[Vec<Option<i32>>]) {
+        if let Some(conflict) = conflicting_clues(clues, &self.state.encoding) {
+            for (row, col) in conflict {
+                get_cell(&mut self.sudoku, row, col).conflicting_clue = true;
             }
         }
     }
@@ -140,10 +353,19 @@ impl Default for SATApp {
             trails,
             callback_wrapper,
             solver,
+            cdcl_solver: None,
             rendered_constraints: Vec::new(),
             rendered_trails: Trail::new(),
             state,
             current_error,
+            proof: None,
+            rule_violations: Vec::new(),
+            incremental_encoding: None,
+            edit_history: EditHistory::default(),
+            human_solution: None,
+            difficulty: None,
+            variable_activity: None,
+            external_run: None,
         }
     }
 }
@@ -160,6 +382,7 @@ impl eframe::App for SATApp {
         } else {
             ctx.set_visuals(egui::Visuals::dark());
         }
+        self.trail_panel(ctx);
         egui::CentralPanel::default().show(ctx, |ui| {
             // per column
             let height = ui.available_height();
@@ -169,8 +392,27 @@ impl eframe::App for SATApp {
 
             // If the solver's status is false, the solving has failed
             // unwrap's default is true, because if the solver has no status, we don't want to show a warning
-            if !self.solver.status().unwrap_or(true) {
-                self.state.show_warning.set(Some("Solving failed. This may be because the sudoku is unsolveable, or because of an error.".to_string()), 1);
+            if !self.backend().status().unwrap_or(true) {
+                let has_conflicting_clues = self
+                    .sudoku
+                    .iter()
+                    .flatten()
+                    .any(|cell| cell.conflicting_clue);
+                let message = match &self.proof {
+                    _ if has_conflicting_clues => "The sudoku is unsolvable. The clues highlighted in red can not all be true at the same time.",
+                    Some(proof) if proof.is_valid() => "The sudoku is unsolvable. See the proof for the reason.",
+                    _ => "Solving failed. This may be because the sudoku is unsolveable, or because of an error.",
+                };
+                self.state.show_warning.set(Some(message.to_string()), 1);
+            } else if self.state.limit_reached {
+                self.state.show_warning.set(
+                    Some(
+                        "A solve limit was reached before the sudoku was solved. \
+                        Raise or clear the limits to solve it."
+                            .to_string(),
+                    ),
+                    1,
+                );
             }
 
             let mut error_open = true;
@@ -192,13 +434,7 @@ impl eframe::App for SATApp {
                 egui::Window::new(error_window_title)
                     .frame(errorwindow)
                     .open(&mut error_open)
-                    .show(ctx, |ui| {
-                        ui.label(
-                            egui::RichText::new(&e.msg)
-                                .heading()
-                                .color(Color32::from_rgb(255, 0, 0)),
-                        );
-                    });
+                    .show(ctx, |ui| error_details(ui, e));
                 if !error_open {
                     self.current_error = None;
                 }
@@ -216,3 +452,27 @@ impl eframe::App for SATApp {
         });
     }
 }
+
+/// Shows the title and the cause of the error. Errors in sudoku and proof files also show the
+/// offending line, with a caret under the offending character if there is one.
+fn error_details(ui: &mut Ui, error: &AppError) {
+    ui.label(
+        RichText::new(error.title())
+            .heading()
+            .color(Color32::from_rgb(255, 0, 0)),
+    );
+    ui.label(RichText::new(error.to_string()).color(Color32::from_rgb(255, 0, 0)));
+
+    if let Some((line, content, column)) = error.location() {
+        let prefix = format!("{:>4} | ", line);
+        ui.label(RichText::new(format!("{}{}", prefix, content)).monospace());
+        if let Some(column) = column {
+            let padding = " ".repeat(prefix.len() + column - 1);
+            ui.label(
+                RichText::new(format!("{}^", padding))
+                    .monospace()
+                    .color(Color32::from_rgb(255, 0, 0)),
+            );
+        }
+    }
+}
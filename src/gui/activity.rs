@@ -0,0 +1,154 @@
+//! GUI code for the table of the solver's decisions and variable activities
+
+use egui::{text::LayoutJob, Color32, FontId, Label, RichText, TextStyle, Ui};
+use egui_extras::{Column, TableBuilder};
+
+use crate::heatmap::HeatmapMetric;
+
+use super::SATApp;
+
+impl SATApp {
+    /// Button for opening the decision table
+    pub fn activity_controls(&mut self, ui: &mut Ui, ctx: &egui::Context, text_scale: f32) {
+        ui.horizontal(|ui| {
+            let decisions_button = ui.add_enabled(
+                self.variable_activity.is_some(),
+                egui::Button::new(RichText::new("Decisions").size(text_scale)),
+            );
+            if decisions_button.clicked() {
+                self.state.show_activity = true;
+            }
+        });
+
+        self.show_activity(ctx);
+    }
+
+    /// Decision table, works as a separate window from the main app
+    fn show_activity(&mut self, ctx: &egui::Context) {
+        if !self.state.show_activity {
+            return;
+        }
+
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("immediate_viewport_activity"),
+            egui::ViewportBuilder::default()
+                .with_title("Decisions")
+                .with_inner_size([450.0, 500.0]),
+            |ctx, _class| {
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.state.show_activity = false;
+                }
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let width = ui.available_width();
+                    let height = ui.available_height();
+                    let text_scale = (width / 45.0).max(9.0);
+                    self.activity_table(ui, text_scale, height);
+                });
+            },
+        )
+    }
+
+    /// Table of the decided variables, the most decided first
+    fn activity_table(&mut self, ui: &mut Ui, text_scale: f32, height: f32) {
+        let activity = match &self.variable_activity {
+            Some(activity) => activity,
+            None => return,
+        };
+        let most_decided = activity.most_decided();
+
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(format!("Decided variables: {}", most_decided.len()))
+                    .size(text_scale),
+            );
+            let mut show_overlay = self.state.heatmap == Some(HeatmapMetric::SolverDecisions);
+            if ui
+                .checkbox(
+                    &mut show_overlay,
+                    RichText::new("Show on grid").size(text_scale),
+                )
+                .changed()
+            {
+                self.state.heatmap = show_overlay.then_some(HeatmapMetric::SolverDecisions);
+            }
+        });
+        ui.separator();
+
+        let font_id = TextStyle::Body.resolve(ui.style());
+        let large_font = FontId::new(text_scale * 1.5, font_id.family.clone());
+        let small_font = FontId::new(text_scale, font_id.family.clone());
+        let text_color = ui.visuals().text_color();
+        let row_height = ui.fonts(|f| f.row_height(&large_font));
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .columns(Column::auto().clip(false), 3)
+            .column(Column::remainder())
+            .auto_shrink([false, false])
+            .max_scroll_height(height)
+            .header(text_scale * 1.5, |mut header| {
+                for (title, hover) in [
+                    (
+                        "Variable",
+                        "The variable, shown like in the constraint list",
+                    ),
+                    ("Cells", "Cells the variable is about"),
+                    (
+                        "Decisions",
+                        "How many times the solver branched on the variable",
+                    ),
+                    (
+                        "Activity",
+                        "Score in the decision heuristic, relative to the most active variable",
+                    ),
+                ] {
+                    header.col(|ui| {
+                        ui.add(Label::new(RichText::new(title).size(text_scale)).wrap(false))
+                            .on_hover_text(RichText::new(hover).size(text_scale));
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, most_decided.len(), |index, mut row| {
+                    let entry = most_decided[index];
+                    row.col(|ui| {
+                        let mut text_job = LayoutJob::default();
+                        Self::append_var_to_layout_job(
+                            self.sudoku.clone(),
+                            &entry.variable,
+                            &mut text_job,
+                            &large_font,
+                            &small_font,
+                            text_color,
+                        );
+                        ui.add(Label::new(text_job).wrap(false));
+                    });
+                    row.col(|ui| {
+                        let cells = entry
+                            .variable
+                            .cells()
+                            .iter()
+                            .map(|(cell_row, cell_col)| format!("({}, {})", cell_row, cell_col))
+                            .collect::<Vec<String>>()
+                            .join(" ");
+                        ui.label(RichText::new(cells).size(text_scale));
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(entry.decisions.to_string()).size(text_scale));
+                    });
+                    row.col(|ui| {
+                        let relative = activity.relative_activity(entry) as f32;
+                        ui.add(
+                            egui::ProgressBar::new(relative)
+                                .text(
+                                    RichText::new(format!("{:.0}%", relative * 100.0))
+                                        .size(text_scale),
+                                )
+                                .fill(Color32::from_rgb(255, 140, 0)),
+                        );
+                    });
+                });
+            });
+    }
+}
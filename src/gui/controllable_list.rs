@@ -10,6 +10,8 @@ use std::ops::Add;
 use crate::cnf::CnfVariable;
 use crate::ctrl_obj::{ConstraintList, ControllableObj};
 use crate::gui::SudokuCell;
+use crate::schema::{ClauseLog, Content, Document};
+use crate::sudoku::write_sudoku;
 
 use super::SATApp;
 
@@ -27,6 +29,7 @@ impl SATApp {
                 self.learned_constraints_labels(ui, text_scale);
                 ui.end_row();
             });
+        self.trail_diff_labels(ui, text_scale);
 
         self.list_of_constraints(ui, text_scale, ctx).response
     }
@@ -44,6 +47,19 @@ impl SATApp {
                 )
                 .wrap(false),
             );
+            if ui
+                .add_enabled(
+                    !self.constraints.is_empty(),
+                    egui::Button::new(RichText::new("Export json").size(text_scale)),
+                )
+                .on_hover_text(
+                    RichText::new("Save the learned constraints with their decoded variables")
+                        .size(text_scale),
+                )
+                .clicked()
+            {
+                self.export_learned_constraints();
+            }
             ui.separator();
             ui.add(
                 Label::new(
@@ -55,9 +71,36 @@ impl SATApp {
                 )
                 .wrap(false),
             );
+            if self.state.reused_constraint_count > 0 {
+                ui.separator();
+                ui.add(
+                    Label::new(
+                        RichText::new(format!(
+                            "From earlier solves: {}",
+                            self.state.reused_constraint_count
+                        ))
+                        .size(text_scale),
+                    )
+                    .wrap(false),
+                );
+            }
         })
     }
 
+    /// Save the learned constraints to a json file as a learned clauses document
+    fn export_learned_constraints(&mut self) {
+        if let Some(file_path) = rfd::FileDialog::new()
+            .set_file_name("learned_constraints.json")
+            .save_file()
+        {
+            let log = ClauseLog::new(self.state.encoding, &self.constraints.clone_constraints());
+            let document = Document::new(Content::LearnedClauses(log));
+            if let Err(e) = write_sudoku(document.to_json(), &file_path) {
+                self.current_error = Some(e);
+            }
+        }
+    }
+
     fn list_of_constraints(
         &mut self,
         ui: &mut Ui,
@@ -79,6 +122,8 @@ impl SATApp {
                     let spacing = 2.0;
                     let top_margin = 5.0;
                     let bg_color = ui.visuals().faint_bg_color;
+                    let reused_bg_color = Color32::from_rgba_unmultiplied(80, 140, 255, 60);
+                    let compared_bg_color = Color32::from_rgba_unmultiplied(230, 90, 200, 120);
 
                     let large_font = FontId::new(large_font_size, font_id.family.clone());
                     let small_font = FontId::new(small_font_size, font_id.family.clone());
@@ -132,6 +177,18 @@ impl SATApp {
                                 }
                             }
 
+                            // Constraints deleted by the solver's reductions are struck through
+                            let global_index = self.state.rendered_indexes.get(i).copied();
+                            let deleted_after = global_index.and_then(|index| {
+                                self.callback_wrapper.events.deleted_after(index)
+                            });
+                            if deleted_after.is_some() {
+                                for section in text_job.sections.iter_mut() {
+                                    section.format.strikethrough =
+                                        Stroke::new(small_font_size * 0.1, Color32::DARK_GRAY);
+                                }
+                            }
+
                             // Galley is the text element that is actually ready to display
                             let galley = ui.fonts(|f| f.layout_job(text_job));
 
@@ -144,11 +201,42 @@ impl SATApp {
                             );
 
                             // Background and click-detection
-                            ui.painter().rect_filled(galley_rect, 0.0, bg_color);
+                            // Constraints learned in earlier solves and the constraint pinned for
+                            // trail comparison have a different background
+                            let reused = global_index
+                                .is_some_and(|index| index < self.state.reused_constraint_count);
+                            if global_index.is_some()
+                                && global_index == self.state.compared_constraint_index
+                            {
+                                ui.painter()
+                                    .rect_filled(galley_rect, 0.0, compared_bg_color);
+                            } else if reused {
+                                ui.painter().rect_filled(galley_rect, 0.0, reused_bg_color);
+                            } else {
+                                ui.painter().rect_filled(galley_rect, 0.0, bg_color);
+                            }
 
                             //Add binding for reacting to clicks
-                            let rect_action = ui.allocate_rect(galley_rect, egui::Sense::click());
-                            if rect_action.clicked() {
+                            let mut rect_action =
+                                ui.allocate_rect(galley_rect, egui::Sense::click());
+                            if let Some(learned_count) = deleted_after {
+                                rect_action = rect_action.on_hover_text(
+                                    RichText::new(format!(
+                                        "Deleted by the solver after {} learned constraints",
+                                        learned_count
+                                    ))
+                                    .size(text_scale),
+                                );
+                            }
+                            if rect_action.clicked() && ui.input(|input| input.modifiers.shift) {
+                                // Shift-click pins the constraint for trail comparison
+                                self.state.compared_constraint_index =
+                                    if self.state.compared_constraint_index == global_index {
+                                        None
+                                    } else {
+                                        global_index
+                                    };
+                            } else if rect_action.clicked() {
                                 clauses.clicked(&mut self.state, i);
                                 (self.rendered_constraints, self.rendered_trails) =
                                     self.state.get_filtered();
@@ -178,7 +266,10 @@ impl SATApp {
 
                     // Check number of the rows on the last page
                     if self.state.page_number + 1 == self.state.page_count
-                        && self.state.filtered_length % self.state.page_length != 0
+                        && !self
+                            .state
+                            .filtered_length
+                            .is_multiple_of(self.state.page_length)
                     {
                         current_page_length = self.state.filtered_length
                             - ((self.state.page_count as usize - 1) * self.state.page_length)
@@ -221,6 +312,21 @@ impl SATApp {
                             }
                         }
                     }
+
+                    // Scroll to a constraint selected outside the list, even if it is not rendered yet
+                    if self.state.scroll_to_constraint {
+                        if let Some(row) = clauses.get_clicked(&self.state) {
+                            let y = ui.min_rect().top() + top_margin + row as f32 * row_height;
+                            ui.scroll_to_rect(
+                                Rect::from_min_size(
+                                    egui::pos2(ui.min_rect().left(), y),
+                                    Vec2::new(1.0, row_height),
+                                ),
+                                Some(egui::Align::Center),
+                            );
+                        }
+                        self.state.scroll_to_constraint = false;
+                    }
                 });
         })
     }
@@ -2,17 +2,22 @@
 
 use super::SATApp;
 use cadical::Solver;
-use egui::{vec2, FontId, Key, Label, Response, RichText, TextStyle, Ui};
+use egui::{vec2, FontId, Key, Label, Modifiers, Response, RichText, TextStyle, Ui};
 
 use crate::{
+    activity::VariableActivity,
     app_state::EncodingType,
+    backend::{Backend, SolverBackend},
     cadical_wrapper::CadicalCallbackWrapper,
+    cdcl::CdclSolver,
     cnf::cnf_encoding_rules_ok,
+    get_cell,
+    schema::{Content, Document, Puzzle},
     statistics::Statistics,
     string_from_grid,
     sudoku::get_sudoku,
     sudoku::write_sudoku,
-    sudoku::{get_empty_sudoku, solve_sudoku},
+    sudoku::{get_empty_sudoku, solve_sudoku, solve_sudoku_incremental, SolveFailure},
     Trail,
 };
 
@@ -35,15 +40,48 @@ impl SATApp {
                 self.trail_view(ui, text_scale);
                 ui.end_row();
 
+                self.replay_controls(ui, ctx, text_scale);
+                ui.end_row();
+
+                self.trail_comparison_controls(ui, text_scale);
+                ui.end_row();
+
+                self.analysis_controls(ui, ctx, text_scale);
+                ui.end_row();
+
                 self.statistics(ui, ctx, text_scale);
                 ui.end_row();
 
+                self.timeline_controls(ui, ctx, text_scale);
+                ui.end_row();
+
+                self.activity_controls(ui, ctx, text_scale);
+                ui.end_row();
+
+                self.event_log_controls(ui, ctx, text_scale);
+                ui.end_row();
+
+                self.proof_controls(ui, ctx, text_scale);
+                ui.end_row();
+
+                self.human_solver_controls(ui, ctx, text_scale);
+                ui.end_row();
+
                 self.encoding_selection(ui, text_scale);
                 ui.end_row();
 
+                self.limit_controls(ui, text_scale);
+                ui.end_row();
+
                 self.encoding_rules(ui, text_scale);
                 ui.end_row();
 
+                self.candidate_controls(ui, text_scale);
+                ui.end_row();
+
+                self.heatmap_controls(ui, text_scale);
+                ui.end_row();
+
                 self.filters(ui, text_scale, ctx);
                 ui.end_row();
 
@@ -74,18 +112,27 @@ impl SATApp {
             {
                 self.state.editor_active = false;
                 if let Some(file_path) = rfd::FileDialog::new()
-                    .add_filter("text", &["txt"])
+                    .add_filter("sudoku", &["txt", "sdk", "sdm", "json"])
                     .pick_file()
                 {
                     let sudoku_result = get_sudoku(file_path.display().to_string());
                     match sudoku_result {
                         Ok(sudoku_vec) => {
+                            let before = self.get_clues();
                             self.sudoku_from_option_values(&sudoku_vec, true);
+                            self.clear_candidates();
+                            self.edit_history.record(before, &self.get_clues());
                             self.constraints.clear();
                             self.trails.clear();
                             self.rendered_constraints = Vec::new();
                             self.rendered_trails = Trail::new();
                             self.state.reinit();
+                            self.proof = None;
+                            self.rule_violations.clear();
+                            self.incremental_encoding = None;
+                            self.state.show_proof = false;
+                            self.state.selected_proof_step = None;
+                            self.cdcl_solver = None;
                             self.solver = Solver::with_config("plain").unwrap();
                             self.callback_wrapper = CadicalCallbackWrapper::new(
                                 self.constraints.clone(),
@@ -106,38 +153,7 @@ impl SATApp {
                 .clicked()
                 || ctx.input(|i| i.key_pressed(Key::P))
             {
-                self.state.editor_active = false;
-                self.reset_cadical_and_solved_sudoku();
-
-                let clues = self.get_option_value_sudoku();
-
-                let solve_result = solve_sudoku(
-                    &self.get_option_value_sudoku(),
-                    &mut self.solver,
-                    &self.state.encoding,
-                );
-
-                match solve_result {
-                    Ok(solved) => {
-                        self.sudoku_from_option_values(&solved, false);
-                        // Reinitialize filtering for a new sudoku
-                        self.state.reinit();
-                        (self.rendered_constraints, self.rendered_trails) =
-                            self.state.get_filtered();
-                        let cadical_stats = self.solver.stats();
-                        let stats = Statistics::from_cadical_stats(
-                            cadical_stats,
-                            self.state.encoding,
-                            clues,
-                            solved,
-                        );
-                        let mut history = self.state.history.lock().unwrap();
-                        history.push(stats);
-                    }
-                    Err(err) => {
-                        println!("{}", err);
-                    }
-                }
+                self.process_sudoku();
             }
 
             if ui
@@ -146,12 +162,16 @@ impl SATApp {
                 || ctx.input(|i| i.key_pressed(Key::N))
             {
                 self.state.editor_active = true;
+                let before = self.get_clues();
                 self.reset_cadical_and_solved_sudoku();
 
                 let sudoku = get_empty_sudoku();
                 match sudoku {
                     Ok(sudoku_vec) => {
                         self.sudoku_from_option_values(&sudoku_vec, true);
+                        self.clear_candidates();
+                        self.edit_history.record(before, &self.get_clues());
+                        self.cdcl_solver = None;
                         self.solver = Solver::with_config("plain").unwrap();
                         self.solver
                             .set_callbacks(Some(self.callback_wrapper.clone()));
@@ -169,11 +189,33 @@ impl SATApp {
                 .clicked()
                 || ctx.input(|i| i.key_pressed(Key::E))
             {
-                self.reset_cadical_and_solved_sudoku();
+                if self.state.incremental {
+                    self.clear_solved_sudoku();
+                } else {
+                    self.reset_cadical_and_solved_sudoku();
+                }
                 self.state.selected_cell = Some((1, 1));
                 self.state.editor_active = true;
             }
 
+            // Undo and redo edits of the clues
+            let undo_button = ui.add_enabled(
+                self.edit_history.can_undo(),
+                egui::Button::new(RichText::new("Undo").size(text_scale)),
+            );
+            if undo_button.clicked() || ctx.input_mut(|i| i.consume_key(Modifiers::COMMAND, Key::Z))
+            {
+                self.undo_edit();
+            }
+            let redo_button = ui.add_enabled(
+                self.edit_history.can_redo(),
+                egui::Button::new(RichText::new("Redo").size(text_scale)),
+            );
+            if redo_button.clicked() || ctx.input_mut(|i| i.consume_key(Modifiers::COMMAND, Key::Y))
+            {
+                self.redo_edit();
+            }
+
             // Handle key inputs for inputting/editing a sudoku
             if self.state.editor_active {
                 let keys = ctx.input(|i| i.events.clone());
@@ -186,7 +228,11 @@ impl SATApp {
                                 }
                                 if self.state.selected_cell.is_some() {
                                     if let Some((row, col)) = self.state.selected_cell {
-                                        self.set_cell(row, col, Some(n), true);
+                                        if self.state.candidate_mode {
+                                            self.toggle_candidate(row, col, n);
+                                        } else {
+                                            self.edit_cell(row, col, Some(n));
+                                        }
                                     }
                                 }
                             }
@@ -194,9 +240,13 @@ impl SATApp {
                         egui::Event::Key {
                             key, pressed: true, ..
                         } => match *key {
-                            Key::Backspace => {
+                            Key::Backspace | Key::Delete => {
                                 if let Some((row, col)) = self.state.selected_cell {
-                                    self.set_cell(row, col, None, false);
+                                    if self.state.candidate_mode {
+                                        get_cell(&mut self.sudoku, row, col).candidates.clear();
+                                    } else {
+                                        self.edit_cell(row, col, None);
+                                    }
                                 }
                             }
                             Key::ArrowLeft => {
@@ -240,7 +290,12 @@ impl SATApp {
                 || ctx.input(|i| i.key_pressed(Key::S))
             {
                 if let Some(save_path) = rfd::FileDialog::new().save_file() {
-                    let sudoku_string = string_from_grid(self.get_option_value_sudoku());
+                    let sudoku = self.get_option_value_sudoku();
+                    let sudoku_string = if save_path.extension() == Some("json".as_ref()) {
+                        Document::new(Content::Puzzle(Puzzle::new(sudoku))).to_json()
+                    } else {
+                        string_from_grid(sudoku)
+                    };
                     let save_result = write_sudoku(sudoku_string, &save_path);
                     if let Err(e) = save_result {
                         self.current_error = Some(e);
@@ -258,6 +313,106 @@ impl SATApp {
         })
     }
 
+    /// Solves the clues of the grid with the selected encoding and solver, and records the
+    /// statistics of the solve
+    fn process_sudoku(&mut self) {
+        self.state.editor_active = false;
+        let clues = self.get_clues();
+        let mut original_clauses = self.state.encoding.sudoku_to_cnf(&clues);
+        let limits = self.state.solve_limits();
+        self.state.limit_reached = false;
+
+        let solve_result = if self.state.incremental {
+            let add_rules = self.prepare_incremental_solve();
+            self.solver.set_limits(&limits);
+            solve_sudoku_incremental(&clues, &mut self.solver, &self.state.encoding, add_rules)
+        } else {
+            self.reset_cadical_and_solved_sudoku();
+
+            // Candidates are not supported in incremental mode, as they would stay in the solver
+            let candidate_clauses = if self.state.encode_candidates {
+                self.state
+                    .encoding
+                    .candidate_clauses(&self.get_candidates())
+            } else {
+                Vec::new()
+            };
+            original_clauses.extend(candidate_clauses.iter().cloned());
+
+            if self.state.backend == Backend::Cdcl {
+                let mut solver = CdclSolver::new();
+                solver.set_callbacks(Some(self.callback_wrapper.clone()));
+                for clause in candidate_clauses {
+                    solver.add_clause(clause);
+                }
+                solver.set_limits(limits);
+                let result = solve_sudoku(&clues, &mut solver, &self.state.encoding);
+                self.cdcl_solver = Some(solver);
+                result
+            } else {
+                for clause in candidate_clauses {
+                    self.solver.add_clause(clause);
+                }
+                self.solver.set_limits(&limits);
+                solve_sudoku(&clues, &mut self.solver, &self.state.encoding)
+            }
+        };
+
+        // Decision counts and activities are only available from cadical
+        if self.cdcl_solver.is_none() {
+            self.variable_activity = Some(VariableActivity::from_solver(
+                &self.solver,
+                &self.state.encoding,
+            ));
+        }
+
+        match solve_result {
+            Ok(solved) => {
+                self.sudoku_from_option_values(&solved, false);
+                self.highlight_rule_violations();
+                // Reinitialize filtering for a new sudoku
+                self.state.reinit();
+                (self.rendered_constraints, self.rendered_trails) = self.state.get_filtered();
+                let cadical_stats = self.backend_mut().stats();
+                let mut stats = Statistics::from_cadical_stats(
+                    cadical_stats,
+                    self.state.encoding,
+                    clues,
+                    solved,
+                );
+                stats.difficulty = self.difficulty.as_ref().map(|rating| rating.score);
+                if self.cdcl_solver.is_some() {
+                    stats.solver = Backend::Cdcl.name().to_string();
+                }
+                let mut history = self.state.history.lock().unwrap();
+                history.push(stats);
+            }
+            Err(SolveFailure::LimitReached) => {
+                self.state.limit_reached = true;
+                (self.rendered_constraints, self.rendered_trails) = self.state.get_filtered();
+                let mut stats = Statistics::from_failure(
+                    self.backend_mut().stats(),
+                    self.state.encoding,
+                    clues,
+                    SolveFailure::LimitReached,
+                );
+                if self.cdcl_solver.is_some() {
+                    stats.solver = Backend::Cdcl.name().to_string();
+                }
+                let mut history = self.state.history.lock().unwrap();
+                history.push(stats);
+                self.current_error = Some(SolveFailure::LimitReached.into());
+            }
+            Err(failure) => {
+                self.current_error = Some(failure.into());
+                if self.cdcl_solver.is_none() {
+                    self.prove_unsatisfiable(&original_clauses, &limits);
+                }
+                self.highlight_conflicting_clues(&clues);
+            }
+        }
+    }
+
     /// Controls for showing conflict literals and trails
     fn trail_view(&mut self, ui: &mut Ui, text_scale: f32) {
         ui.horizontal(|ui| {
@@ -288,6 +443,15 @@ impl SATApp {
             ui.add(Label::new(
                 RichText::new("Trail with conflict literals").size(text_scale),
             ));
+
+            ui.checkbox(
+                &mut self.state.show_trail_panel,
+                RichText::new("Trail panel").size(text_scale),
+            )
+            .on_hover_text(
+                RichText::new("List the trail of the selected constraint by decision level")
+                    .size(text_scale),
+            );
         });
     }
 
@@ -319,6 +483,45 @@ impl SATApp {
                         RichText::new("Binary based CNF encoding").size(text_scale),
                     );
                 });
+
+            ui.checkbox(
+                &mut self.state.incremental,
+                RichText::new("Incremental").size(text_scale),
+            )
+            .on_hover_text(
+                RichText::new(
+                    "Keep the solver between solves.\nThe clues are given as assumptions, \
+                    so the constraints learned earlier can be reused.\n\
+                    Constraints from earlier solves are highlighted in the list.",
+                )
+                .size(text_scale),
+            );
+
+            ui.add_enabled_ui(!self.state.incremental, |ui| {
+                egui::ComboBox::from_id_source("solver_backend")
+                    .selected_text(RichText::new(self.state.backend.name()).size(text_scale))
+                    .show_ui(ui, |ui| {
+                        for backend in Backend::all() {
+                            ui.selectable_value(
+                                &mut self.state.backend,
+                                backend,
+                                RichText::new(backend.name()).size(text_scale),
+                            );
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        RichText::new(
+                            "Solver used for solving. The CDCL solver written in Rust has no\n\
+                            inprocessing, so every learned constraint and trail comes straight\n\
+                            from conflict analysis. Proofs are only traced by cadical.",
+                        )
+                        .size(text_scale),
+                    )
+                    .on_disabled_hover_text(
+                        RichText::new("Incremental solving always uses cadical.").size(text_scale),
+                    );
+            });
         });
 
         if old_encoding != self.state.encoding {
@@ -326,6 +529,41 @@ impl SATApp {
         }
     }
 
+    /// Checkboxes for entering candidates (pencil marks) and using them in the encoding
+    fn candidate_controls(&mut self, ui: &mut Ui, text_scale: f32) -> egui::InnerResponse<()> {
+        ui.horizontal(|ui| {
+            ui.checkbox(
+                &mut self.state.candidate_mode,
+                RichText::new("Pencil marks").size(text_scale),
+            )
+            .on_hover_text(
+                RichText::new(
+                    "In the editor, digits toggle candidates of the selected cell\n\
+                    instead of setting a clue. Backspace clears the candidates.",
+                )
+                .size(text_scale),
+            );
+
+            ui.add_enabled(
+                !self.state.incremental,
+                egui::Checkbox::new(
+                    &mut self.state.encode_candidates,
+                    RichText::new("Encode candidates").size(text_scale),
+                ),
+            )
+            .on_hover_text(
+                RichText::new(
+                    "Limit each cell with candidates to one of them, by adding clauses.\n\
+                    Not available with incremental solving.",
+                )
+                .size(text_scale),
+            )
+            .on_disabled_hover_text(
+                RichText::new("Not available with incremental solving.").size(text_scale),
+            );
+        })
+    }
+
     /// Checkboxes for enabling/disabling CNF Encoding rules
     fn encoding_rules(&mut self, ui: &mut Ui, text_scale: f32) -> egui::InnerResponse<()> {
         // Veery ugly but I couldn't find a better alternative
@@ -581,6 +819,39 @@ impl SATApp {
         })
     }
 
+    /// Inputs for the limits of each solve
+    fn limit_controls(&mut self, ui: &mut Ui, text_scale: f32) -> egui::InnerResponse<()> {
+        ui.horizontal(|ui| {
+            let font_id = TextStyle::Body.resolve(ui.style());
+            let font = FontId::new(text_scale, font_id.family.clone());
+
+            ui.label(RichText::new("Limits:").size(text_scale))
+                .on_hover_text(
+                    RichText::new(
+                        "Each solve stops when it reaches a limit, and the result is unknown. \
+                        Empty fields are no limit.",
+                    )
+                    .size(text_scale),
+                );
+
+            for (name, input) in [
+                ("Time (s)", &mut self.state.time_limit_input),
+                ("Conflicts", &mut self.state.conflict_limit_input),
+                ("Decisions", &mut self.state.decision_limit_input),
+                ("Propagations", &mut self.state.propagation_limit_input),
+            ] {
+                let label = ui.label(RichText::new(name).size(text_scale));
+                ui.add(
+                    egui::TextEdit::singleline(input)
+                        .desired_width(4.0 * text_scale)
+                        .font(font.clone())
+                        .horizontal_align(egui::Align::RIGHT),
+                )
+                .labelled_by(label.id);
+            }
+        })
+    }
+
     /// Warning triangle for incomplete set of encoding rules
     fn warning_triangle(&mut self, ui: &mut Ui, text_scale: f32) -> egui::InnerResponse<()> {
         match self.state.encoding {
@@ -609,6 +880,20 @@ impl SATApp {
             EncodingType::Binary => {}
         }
 
+        if !self.rule_violations.is_empty() {
+            let mut text = format!(
+                "The solution breaks {} sudoku rules, highlighted in red:",
+                self.rule_violations.len()
+            );
+            for violation in self.rule_violations.iter().take(5) {
+                text.push_str(&format!("\n{}", violation));
+            }
+            if self.rule_violations.len() > 5 {
+                text.push_str("\n...");
+            }
+            self.state.show_warning.set(Some(text), 1);
+        }
+
         ui.horizontal(|ui| {
             if self.state.show_warning.is() {
                 let image_size = text_scale * 1.25; // 1.5 chosen with manual testing
@@ -627,3 +912,113 @@ impl SATApp {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{difficulty::rate_difficulty, error::AppError, gui::sudoku_cell::SudokuCell};
+
+    fn empty_cells() -> Vec<Vec<SudokuCell>> {
+        (1..=9)
+            .map(|row| {
+                (1..=9)
+                    .map(|col| SudokuCell::new(row, col, None, false))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_process_twice_keeps_clues() {
+        let clues = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        for incremental in [false, true] {
+            let mut app = SATApp::new(empty_cells());
+            app.sudoku_from_option_values(&clues, true);
+            app.state.incremental = incremental;
+
+            app.process_sudoku();
+            app.process_sudoku();
+
+            assert_eq!(app.get_clues(), clues);
+            let history = app.state.history.lock().unwrap();
+            assert_eq!(history.len(), 2);
+            assert!(history.iter().all(|stats| stats.clues == clues));
+        }
+    }
+
+    #[test]
+    fn test_process_keeps_rating() {
+        let clues = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        let mut app = SATApp::new(empty_cells());
+        app.sudoku_from_option_values(&clues, true);
+        app.difficulty = rate_difficulty(&clues).ok();
+        let score = app.difficulty.as_ref().map(|rating| rating.score);
+        assert!(score.is_some());
+
+        // Filling in the solution does not change the clues, so the rating still holds
+        app.process_sudoku();
+        assert_eq!(app.difficulty.as_ref().map(|rating| rating.score), score);
+        assert_eq!(app.state.history.lock().unwrap()[0].difficulty, score);
+
+        app.set_cell(1, 1, Some(5), true);
+        assert!(app.difficulty.is_none());
+    }
+
+    #[test]
+    fn test_limit_shows_error() {
+        let clues = get_sudoku("data/hardest-ever-sudoku.txt".to_string()).unwrap();
+        let mut app = SATApp::new(empty_cells());
+        app.sudoku_from_option_values(&clues, true);
+        app.state.conflict_limit_input = "1".to_string();
+        app.process_sudoku();
+
+        assert!(app.state.limit_reached);
+        assert!(matches!(
+            app.current_error,
+            Some(AppError::Solve(SolveFailure::LimitReached))
+        ));
+        assert!(app.proof.is_none());
+    }
+
+    /// Proof files of this process left in the temporary directory
+    fn proof_files() -> Vec<std::path::PathBuf> {
+        let prefix = format!("sat-step-{}-", std::process::id());
+        std::fs::read_dir(std::env::temp_dir())
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".drat"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_proof_only_for_unsolvable() {
+        let clues = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        let mut app = SATApp::new(empty_cells());
+        app.sudoku_from_option_values(&clues, true);
+        app.process_sudoku();
+        assert!(app.proof.is_none());
+        assert!(proof_files().is_empty());
+
+        // Two fives in the first row
+        let mut unsolvable = clues.clone();
+        unsolvable[0][2] = Some(5);
+        for incremental in [false, true] {
+            let mut app = SATApp::new(empty_cells());
+            app.sudoku_from_option_values(&unsolvable, true);
+            app.state.incremental = incremental;
+            app.process_sudoku();
+
+            assert!(matches!(
+                app.current_error,
+                Some(AppError::Solve(SolveFailure::Unsatisfiable))
+            ));
+            assert!(app.proof.as_ref().is_some_and(|proof| proof.is_valid()));
+            assert!(proof_files().is_empty());
+        }
+    }
+}
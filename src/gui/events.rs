@@ -0,0 +1,174 @@
+//! GUI code for the log of the solver's search events
+
+use egui::{text::LayoutJob, FontId, Label, RichText, TextStyle, Ui};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{
+    cnf::CnfVariable,
+    events::{SolverEvent, MAX_EVENTS},
+};
+
+use super::SATApp;
+
+impl SATApp {
+    /// Button for opening the event log, and the number of deleted learned constraints
+    pub fn event_log_controls(&mut self, ui: &mut Ui, ctx: &egui::Context, text_scale: f32) {
+        ui.horizontal(|ui| {
+            let events = &self.callback_wrapper.events;
+            let log_button = ui.add_enabled(
+                !events.is_empty(),
+                egui::Button::new(RichText::new("Event log").size(text_scale)),
+            );
+            if log_button.clicked() {
+                self.state.show_event_log = true;
+            }
+
+            let deleted = events.deleted.borrow().len();
+            if deleted > 0 {
+                ui.label(RichText::new(format!("Deleted constraints: {}", deleted)).size(text_scale))
+                    .on_hover_text(
+                        RichText::new(
+                            "Learned constraints the solver deleted when reducing its clause database. \
+                            They are struck through in the list.",
+                        )
+                        .size(text_scale),
+                    );
+            }
+        });
+
+        self.show_event_log(ctx);
+    }
+
+    /// Event log, works as a separate window from the main app
+    fn show_event_log(&mut self, ctx: &egui::Context) {
+        if !self.state.show_event_log {
+            return;
+        }
+
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("immediate_viewport_event_log"),
+            egui::ViewportBuilder::default()
+                .with_title("Event log")
+                .with_inner_size([550.0, 600.0]),
+            |ctx, _class| {
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.state.show_event_log = false;
+                }
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let width = ui.available_width();
+                    let text_scale = (width / 45.0).max(9.0);
+
+                    ui.horizontal(|ui| {
+                        for kind in SolverEvent::kinds() {
+                            let mut shown = self.state.event_kinds.contains(&kind);
+                            if ui
+                                .checkbox(&mut shown, RichText::new(kind).size(text_scale))
+                                .changed()
+                            {
+                                if shown {
+                                    self.state.event_kinds.push(kind);
+                                } else {
+                                    self.state
+                                        .event_kinds
+                                        .retain(|&shown_kind| shown_kind != kind);
+                                }
+                            }
+                        }
+                    });
+                    let dropped = self.callback_wrapper.events.dropped.get();
+                    if dropped > 0 {
+                        ui.label(
+                            RichText::new(format!(
+                                "The log keeps the last {} events, the first {} were dropped",
+                                MAX_EVENTS, dropped
+                            ))
+                            .size(text_scale),
+                        );
+                    }
+                    ui.separator();
+
+                    let height = ui.available_height();
+                    self.event_table(ui, text_scale, height);
+                });
+            },
+        )
+    }
+
+    /// Table of the events of the shown kinds, in the order they happened
+    fn event_table(&mut self, ui: &mut Ui, text_scale: f32, height: f32) {
+        let dropped = self.callback_wrapper.events.dropped.get();
+        let events = self
+            .callback_wrapper
+            .events
+            .events
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| self.state.event_kinds.contains(&event.kind()))
+            .map(|(number, event)| (dropped + number, event.clone()))
+            .collect::<Vec<(usize, SolverEvent)>>();
+
+        let font_id = TextStyle::Body.resolve(ui.style());
+        let large_font = FontId::new(text_scale * 1.5, font_id.family.clone());
+        let small_font = FontId::new(text_scale, font_id.family.clone());
+        let text_color = ui.visuals().text_color();
+        let row_height = ui.fonts(|f| f.row_height(&large_font));
+        let mut selected_constraint = None;
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .columns(Column::auto().clip(false), 2)
+            .column(Column::remainder())
+            .auto_shrink([false, false])
+            .max_scroll_height(height)
+            .header(text_scale * 1.5, |mut header| {
+                for title in ["#", "Event", "Details"] {
+                    header.col(|ui| {
+                        ui.add(Label::new(RichText::new(title).size(text_scale)).wrap(false));
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, events.len(), |index, mut row| {
+                    let (number, event) = &events[index];
+                    row.col(|ui| {
+                        ui.label(RichText::new((number + 1).to_string()).size(text_scale));
+                    });
+                    row.col(|ui| {
+                        ui.label(RichText::new(event.kind()).size(text_scale));
+                    });
+                    row.col(|ui| match event {
+                        SolverEvent::Decision { literal, level } => {
+                            let mut text_job = LayoutJob::default();
+                            Self::append_var_to_layout_job(
+                                self.sudoku.clone(),
+                                &CnfVariable::from_cnf(*literal, &self.state.encoding),
+                                &mut text_job,
+                                &large_font,
+                                &small_font,
+                                text_color,
+                            );
+                            ui.add(Label::new(text_job).wrap(false));
+                            ui.label(RichText::new(format!("on level {}", level)).size(text_scale));
+                        }
+                        SolverEvent::Learn { index } => {
+                            if ui
+                                .link(RichText::new(event.to_string()).size(text_scale))
+                                .clicked()
+                            {
+                                selected_constraint = Some(*index);
+                            }
+                        }
+                        _ => {
+                            ui.label(RichText::new(event.to_string()).size(text_scale));
+                        }
+                    });
+                });
+            });
+
+        if let Some(index) = selected_constraint {
+            self.select_constraint(index);
+        }
+    }
+}
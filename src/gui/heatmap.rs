@@ -0,0 +1,122 @@
+//! GUI code for the heatmap overlay of the sudoku grid
+
+use egui::{vec2, Align2, FontId, RichText, Sense, Ui};
+
+use crate::heatmap::{cell_counts, HeatmapMetric};
+
+use super::{sudoku_cell::heat_color, SATApp};
+
+/// Number of colour steps shown in the legend
+const LEGEND_STEPS: usize = 10;
+
+impl SATApp {
+    /// Selection of the heatmap metric, and a legend for the colours
+    pub fn heatmap_controls(&mut self, ui: &mut Ui, text_scale: f32) {
+        ui.horizontal(|ui| {
+            let selected_text = match self.state.heatmap {
+                Some(metric) => metric.to_string(),
+                None => "Off".to_string(),
+            };
+            ui.label(RichText::new("Heatmap:").size(text_scale));
+            egui::ComboBox::from_id_source("heatmap_metric")
+                .selected_text(RichText::new(selected_text).size(text_scale))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(
+                        &mut self.state.heatmap,
+                        None,
+                        RichText::new("Off").size(text_scale),
+                    );
+                    for metric in HeatmapMetric::all() {
+                        ui.selectable_value(
+                            &mut self.state.heatmap,
+                            Some(metric),
+                            RichText::new(metric.to_string()).size(text_scale),
+                        )
+                        .on_hover_text(RichText::new(metric.description()).size(text_scale));
+                    }
+                });
+
+            if let Some(metric) = self.state.heatmap {
+                let max_count = self.heatmap_counts(metric).into_iter().flatten().max();
+                self.heatmap_legend(ui, text_scale, max_count.unwrap_or(0))
+                    .on_hover_text(RichText::new(metric.description()).size(text_scale));
+            }
+        });
+    }
+
+    /// Colour scale from zero to the largest count
+    fn heatmap_legend(&self, ui: &mut Ui, text_scale: f32, max_count: usize) -> egui::Response {
+        let step_size = vec2(text_scale, text_scale);
+        let label_width = text_scale * 3.0;
+        let (rect, response) = ui.allocate_exact_size(
+            vec2(
+                step_size.x * LEGEND_STEPS as f32 + 2.0 * label_width,
+                step_size.y,
+            ),
+            Sense::hover(),
+        );
+        let painter = ui.painter_at(rect);
+        let font = FontId::proportional(text_scale);
+        let text_color = ui.visuals().text_color();
+
+        painter.text(
+            rect.left_center() + vec2(label_width, 0.0),
+            Align2::RIGHT_CENTER,
+            "0 ",
+            font.clone(),
+            text_color,
+        );
+        for step in 0..LEGEND_STEPS {
+            let top_left = rect.left_top() + vec2(label_width + step as f32 * step_size.x, 0.0);
+            let heat = step as f32 / (LEGEND_STEPS - 1) as f32;
+            painter.rect_filled(
+                egui::Rect::from_min_size(top_left, step_size),
+                0.0,
+                heat_color(heat),
+            );
+        }
+        painter.text(
+            rect.right_center() - vec2(label_width, 0.0),
+            Align2::LEFT_CENTER,
+            format!(" {}", max_count),
+            font,
+            text_color,
+        );
+
+        response
+    }
+
+    /// Sets the heat of each cell relative to the largest count, or clears it if the heatmap is off
+    pub fn update_heatmap(&mut self) {
+        let counts = self.state.heatmap.map(|metric| self.heatmap_counts(metric));
+        let max_count = counts
+            .iter()
+            .flatten()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or(0);
+
+        for (row, cells) in self.sudoku.iter_mut().enumerate() {
+            for (col, cell) in cells.iter_mut().enumerate() {
+                cell.heat = match &counts {
+                    Some(counts) if max_count > 0 => {
+                        Some(counts[row][col] as f32 / max_count as f32)
+                    }
+                    Some(_) => Some(0.0),
+                    None => None,
+                };
+            }
+        }
+    }
+
+    fn heatmap_counts(&self, metric: HeatmapMetric) -> Vec<Vec<usize>> {
+        cell_counts(
+            metric,
+            &self.constraints,
+            &self.trails,
+            self.variable_activity.as_ref(),
+            &self.state.encoding,
+        )
+    }
+}
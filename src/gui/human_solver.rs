@@ -0,0 +1,252 @@
+//! GUI code for comparing the human-technique solver to the unit clauses learned by CaDiCaL
+
+use std::collections::HashSet;
+
+use egui::{
+    text::{LayoutJob, TextFormat},
+    Color32, FontId, Label, RichText, ScrollArea, TextStyle, Ui,
+};
+
+use crate::{
+    cnf::CnfVariable,
+    difficulty::{rate_difficulty, RATING_SEEDS},
+    human_solver::{solve_human, UnitExplanation},
+};
+
+use super::SATApp;
+
+impl SATApp {
+    /// Button for opening the human solver view, and the difficulty rating of the sudoku.
+    /// Rating takes several solves, so it is only done when asked for.
+    pub fn human_solver_controls(&mut self, ui: &mut Ui, ctx: &egui::Context, text_scale: f32) {
+        ui.horizontal(|ui| {
+            if ui
+                .button(RichText::new("Human techniques").size(text_scale))
+                .clicked()
+            {
+                self.state.show_human_solver = true;
+            }
+
+            if let Some(difficulty) = &self.difficulty {
+                ui.label(RichText::new(format!("Difficulty: {}", difficulty)).size(text_scale))
+                    .on_hover_text(
+                        RichText::new(format!(
+                            "Average over {} seeds: {:.0} conflicts, {:.0} decisions, {:.0} propagations\nHardest technique: {}",
+                            RATING_SEEDS,
+                            difficulty.conflicts,
+                            difficulty.decisions,
+                            difficulty.propagations,
+                            match (difficulty.human_solvable, difficulty.hardest_technique) {
+                                (false, _) => "not solvable with the techniques".to_string(),
+                                (true, Some(technique)) => technique.to_string(),
+                                (true, None) => "none".to_string(),
+                            }
+                        ))
+                        .size(text_scale),
+                    );
+            } else if ui
+                .button(RichText::new("Rate difficulty").size(text_scale))
+                .on_hover_text(
+                    RichText::new(format!(
+                        "Solve the sudoku with {} seeds and the human techniques.
+                        Later statistics rows include the rating.",
+                        RATING_SEEDS
+                    ))
+                    .size(text_scale),
+                )
+                .clicked()
+            {
+                match rate_difficulty(&self.get_clues()) {
+                    Ok(difficulty) => self.difficulty = Some(difficulty),
+                    Err(e) => self.current_error = Some(e),
+                }
+            }
+        });
+
+        self.show_human_solver(ctx);
+    }
+
+    /// Human solver view, works as a separate window from the main app
+    fn show_human_solver(&mut self, ctx: &egui::Context) {
+        if !self.state.show_human_solver {
+            return;
+        }
+        if self.human_solution.is_none() {
+            self.human_solution = Some(solve_human(&self.get_clues()));
+        }
+
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("immediate_viewport_human_solver"),
+            egui::ViewportBuilder::default()
+                .with_title("Human techniques")
+                .with_inner_size([900.0, 500.0]),
+            |ctx, _class| {
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.state.show_human_solver = false;
+                }
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let width = ui.available_width();
+                    let text_scale = (width / 80.0).max(9.0);
+
+                    ui.columns(2, |columns| {
+                        self.human_steps(&mut columns[0], text_scale);
+                        self.learned_units(&mut columns[1], text_scale);
+                    });
+                });
+            },
+        )
+    }
+
+    /// Steps of the human solver with their explanations
+    fn human_steps(&mut self, ui: &mut Ui, text_scale: f32) {
+        let solution = match &self.human_solution {
+            Some(solution) => solution,
+            None => return,
+        };
+
+        ui.heading(RichText::new("Human solver").size(text_scale * 1.5));
+        let summary = if solution.is_solved() {
+            format!("Solved in {} steps", solution.steps.len())
+        } else {
+            format!(
+                "Stuck after {} steps, the techniques are not enough",
+                solution.steps.len()
+            )
+        };
+        ui.label(RichText::new(summary).size(text_scale));
+        if let Some(technique) = solution.hardest_technique() {
+            ui.label(RichText::new(format!("Hardest technique: {}", technique)).size(text_scale));
+        }
+        ui.separator();
+
+        ScrollArea::vertical()
+            .id_source("human_steps")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for (index, step) in solution.steps.iter().enumerate() {
+                    ui.label(
+                        RichText::new(format!(
+                            "{}. {}: {}",
+                            index + 1,
+                            step.technique,
+                            step.explanation
+                        ))
+                        .size(text_scale),
+                    );
+                }
+            });
+    }
+
+    /// Unit clauses learned by CaDiCaL, and the human solver step that gives the same result
+    fn learned_units(&mut self, ui: &mut Ui, text_scale: f32) {
+        let solution = match &self.human_solution {
+            Some(solution) => solution,
+            None => return,
+        };
+
+        let units = self
+            .constraints
+            .borrow()
+            .iter()
+            .filter(|constraint| constraint.len() == 1)
+            .map(|constraint| CnfVariable::from_cnf(constraint[0], &self.state.encoding))
+            .collect::<Vec<CnfVariable>>();
+        let explanations = units
+            .iter()
+            .map(|variable| {
+                unit_possible_values(variable)
+                    .map(|(row, col, possible)| solution.explaining_step(row, col, &possible))
+            })
+            .collect::<Vec<Option<UnitExplanation>>>();
+        let matching = explanations
+            .iter()
+            .filter(|explanation| !matches!(explanation, None | Some(UnitExplanation::Unexplained)))
+            .count();
+
+        ui.heading(RichText::new("Learned unit clauses").size(text_scale * 1.5));
+        ui.label(
+            RichText::new(format!(
+                "{} of {} correspond to a human technique",
+                matching,
+                units.len()
+            ))
+            .size(text_scale),
+        );
+        ui.separator();
+
+        let font_id = TextStyle::Body.resolve(ui.style());
+        let large_font = FontId::new(text_scale * 1.5, font_id.family.clone());
+        let small_font = FontId::new(text_scale, font_id.family.clone());
+        let text_color = ui.visuals().text_color();
+
+        ScrollArea::vertical()
+            .id_source("learned_units")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for (index, (variable, explanation)) in
+                    units.iter().zip(explanations.iter()).enumerate()
+                {
+                    let mut text_job = LayoutJob::default();
+                    text_job.append(
+                        &format!("{}. ", index + 1),
+                        0.0,
+                        TextFormat {
+                            font_id: small_font.clone(),
+                            color: Color32::DARK_GRAY,
+                            ..Default::default()
+                        },
+                    );
+                    Self::append_var_to_layout_job(
+                        self.sudoku.clone(),
+                        variable,
+                        &mut text_job,
+                        &large_font,
+                        &small_font,
+                        text_color,
+                    );
+
+                    let (text, color) = match explanation {
+                        Some(UnitExplanation::Clues) => {
+                            ("follows directly from the clues".to_string(), text_color)
+                        }
+                        Some(UnitExplanation::Step(step)) => (
+                            format!("step {}: {}", step + 1, solution.steps[*step].technique),
+                            text_color,
+                        ),
+                        Some(UnitExplanation::Unexplained) => {
+                            ("no human technique".to_string(), Color32::RED)
+                        }
+                        None => ("can not be compared".to_string(), Color32::DARK_GRAY),
+                    };
+                    text_job.append(
+                        &format!("  {}", text),
+                        0.0,
+                        TextFormat {
+                            font_id: small_font.clone(),
+                            color,
+                            ..Default::default()
+                        },
+                    );
+
+                    ui.add(Label::new(text_job).wrap(false));
+                }
+            });
+    }
+}
+
+/// The cell of a learned unit clause and the values the cell can have because of it
+fn unit_possible_values(variable: &CnfVariable) -> Option<(i32, i32, HashSet<i32>)> {
+    match *variable {
+        CnfVariable::Decimal { row, col, value } if value > 0 => {
+            Some((row, col, HashSet::from([value])))
+        }
+        CnfVariable::Decimal { row, col, value } => Some((
+            row,
+            col,
+            (1..=9).filter(|&possible| possible != -value).collect(),
+        )),
+        CnfVariable::Bit { row, col, .. } => Some((row, col, variable.get_possible_numbers())),
+        CnfVariable::Equality { .. } => None,
+    }
+}
@@ -0,0 +1,347 @@
+//! GUI code for viewing and exporting the proof of an unsolvable sudoku
+
+use std::{fs, path::Path};
+
+use egui::{
+    text::{LayoutJob, TextFormat},
+    Color32, FontId, Label, RichText, ScrollArea, TextStyle, Ui,
+};
+
+use cadical::Solver;
+
+use crate::{
+    backend::{SolveLimits, SolverBackend},
+    cnf::CnfVariable,
+    error::AppError,
+    proof::{proof_path, Proof, ProofFormat, ProofStep, StepStatus},
+    sudoku::{write_sudoku, SolveFailure},
+};
+
+use super::SATApp;
+
+/// Solves the clauses with a new cadical solver that writes a proof to the path, and checks it
+fn trace_and_check_proof(
+    clauses: &[Vec<i32>],
+    limits: &SolveLimits,
+    path: &Path,
+) -> Result<Proof, AppError> {
+    let mut solver: Solver = Solver::with_config("plain")?;
+    solver.trace_proof(path, false)?;
+    for clause in clauses {
+        solver.add_clause(clause.iter().copied());
+    }
+    SolverBackend::set_limits(&mut solver, limits);
+    let status = solver.solve();
+    solver.close_proof()?;
+    match status {
+        Some(false) => Proof::from_file(path, clauses),
+        // The clauses were found unsatisfiable before, so only a limit can stop the solve
+        _ => Err(AppError::Solve(SolveFailure::LimitReached)),
+    }
+}
+
+impl SATApp {
+    /// Button for opening the proof view, and a short summary of the proof
+    pub fn proof_controls(&mut self, ui: &mut Ui, ctx: &egui::Context, text_scale: f32) {
+        ui.horizontal(|ui| {
+            let proof_button = ui.add_enabled(
+                self.proof.is_some(),
+                egui::Button::new(RichText::new("Proof").size(text_scale)),
+            );
+            if proof_button.clicked() {
+                self.state.show_proof = true;
+            }
+
+            if let Some(proof) = &self.proof {
+                let summary = if proof.is_valid() {
+                    "No solution, proof verified"
+                } else {
+                    "No solution, proof not verified"
+                };
+                ui.label(RichText::new(summary).size(text_scale));
+            } else if self
+                .cdcl_solver
+                .as_ref()
+                .is_some_and(|solver| solver.status() == Some(false))
+            {
+                ui.label(
+                    RichText::new("No solution, the Rust CDCL solver does not write proofs")
+                        .size(text_scale),
+                );
+            }
+        });
+
+        self.show_proof(ctx);
+    }
+
+    /// Solves the original clauses of an unsolvable sudoku again with a new cadical solver that
+    /// traces a proof, and checks the proof against the clauses. The proof is only traced once
+    /// the sudoku is known to be unsolvable, so that solves with a solution write no file. The
+    /// proof file is removed after it has been read.
+    pub fn prove_unsatisfiable(&mut self, clauses: &[Vec<i32>], limits: &SolveLimits) {
+        let path = proof_path();
+        let result = trace_and_check_proof(clauses, limits, &path);
+        let _ = fs::remove_file(&path);
+
+        match result {
+            Ok(proof) => {
+                self.proof = Some(proof);
+                self.state.show_proof = true;
+            }
+            Err(e) => {
+                self.current_error = Some(e);
+            }
+        }
+    }
+
+    /// Proof view, works as a separate window from the main app
+    fn show_proof(&mut self, ctx: &egui::Context) {
+        if !self.state.show_proof {
+            return;
+        }
+
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("immediate_viewport_proof"),
+            egui::ViewportBuilder::default()
+                .with_title("Proof")
+                .with_inner_size([550.0, 400.0]),
+            |ctx, _class| {
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.state.show_proof = false;
+                    self.state.selected_proof_step = None;
+                }
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let width = ui.available_width();
+                    let text_scale = (width / 45.0).max(9.0);
+
+                    ui.vertical(|ui| {
+                        self.proof_summary(ui, text_scale);
+                        self.proof_buttons(ui, text_scale);
+                        ui.separator();
+                        self.proof_steps(ui, text_scale);
+                    });
+                });
+            },
+        )
+    }
+
+    fn proof_summary(&mut self, ui: &mut Ui, text_scale: f32) {
+        if let Some(proof) = &self.proof {
+            let (text, color) = if proof.is_valid() {
+                (
+                    "The proof is valid: the sudoku has no solution",
+                    Color32::DARK_GREEN,
+                )
+            } else {
+                ("The proof could not be verified", Color32::RED)
+            };
+            ui.label(RichText::new(text).size(text_scale).color(color));
+            ui.label(
+                RichText::new(format!(
+                    "Verified lemmas: {}/{}",
+                    proof.verified_count(),
+                    proof.lemma_count()
+                ))
+                .size(text_scale),
+            );
+        }
+    }
+
+    fn proof_buttons(&mut self, ui: &mut Ui, text_scale: f32) {
+        let step_count = self.proof.as_ref().map_or(0, |proof| proof.steps.len());
+
+        ui.horizontal(|ui| {
+            if ui
+                .button(RichText::new("Export DRAT").size(text_scale))
+                .clicked()
+            {
+                self.export_proof(ProofFormat::Drat);
+            }
+            if ui
+                .button(RichText::new("Export LRAT").size(text_scale))
+                .clicked()
+            {
+                self.export_proof(ProofFormat::Lrat);
+            }
+
+            ui.separator();
+
+            if ui
+                .button(RichText::new("Previous").size(text_scale))
+                .clicked()
+            {
+                self.state.selected_proof_step = match self.state.selected_proof_step {
+                    Some(index) if index > 0 => Some(index - 1),
+                    _ => Some(0),
+                };
+            }
+            if ui.button(RichText::new("Next").size(text_scale)).clicked() && step_count > 0 {
+                self.state.selected_proof_step = match self.state.selected_proof_step {
+                    Some(index) => Some((index + 1).min(step_count - 1)),
+                    None => Some(0),
+                };
+            }
+        });
+    }
+
+    /// List of proof steps, with the literals shown as sudoku variables
+    fn proof_steps(&mut self, ui: &mut Ui, text_scale: f32) {
+        let proof = match &self.proof {
+            Some(proof) => proof,
+            None => return,
+        };
+
+        let font_id = TextStyle::Body.resolve(ui.style());
+        let large_font = FontId::new(text_scale * 1.5, font_id.family.clone());
+        let small_font = FontId::new(text_scale, font_id.family.clone());
+        let row_height = ui.fonts(|f| f.row_height(&large_font)) + 2.0;
+        let text_color = ui.visuals().text_color();
+
+        let mut clicked = None;
+        ScrollArea::both().auto_shrink([false; 2]).show_rows(
+            ui,
+            row_height,
+            proof.steps.len(),
+            |ui, row_range| {
+                for index in row_range {
+                    let checked = &proof.steps[index];
+                    let mut text_job = LayoutJob::default();
+
+                    let (kind, status) = match (&checked.step, &checked.status) {
+                        (ProofStep::Delete(_), _) => ("del", "deleted".to_string()),
+                        (ProofStep::Add(_), StepStatus::Rup(hints)) => {
+                            ("add", format!("RUP, {} clauses", hints.len()))
+                        }
+                        (ProofStep::Add(_), StepStatus::Rat) => ("add", "RAT".to_string()),
+                        (ProofStep::Add(_), StepStatus::Failed) => ("add", "FAILED".to_string()),
+                        (ProofStep::Add(_), _) => ("add", "not checked".to_string()),
+                    };
+
+                    text_job.append(
+                        &format!("{}. {} ", index + 1, kind),
+                        0.0,
+                        TextFormat {
+                            font_id: small_font.clone(),
+                            color: Color32::DARK_GRAY,
+                            ..Default::default()
+                        },
+                    );
+
+                    let literals = checked.step.literals();
+                    if literals.is_empty() {
+                        text_job.append(
+                            "empty clause",
+                            0.0,
+                            TextFormat {
+                                font_id: large_font.clone(),
+                                color: text_color,
+                                ..Default::default()
+                            },
+                        );
+                    }
+                    let mut variables = literals
+                        .iter()
+                        .map(|&literal| CnfVariable::from_cnf(literal, &self.state.encoding))
+                        .peekable();
+                    while let Some(variable) = variables.next() {
+                        Self::append_var_to_layout_job(
+                            self.sudoku.clone(),
+                            &variable,
+                            &mut text_job,
+                            &large_font,
+                            &small_font,
+                            text_color,
+                        );
+                        if variables.peek().is_some() {
+                            text_job.append(
+                                "v",
+                                0.0,
+                                TextFormat {
+                                    font_id: large_font.clone(),
+                                    color: Color32::DARK_GRAY,
+                                    ..Default::default()
+                                },
+                            );
+                        }
+                    }
+
+                    text_job.append(
+                        &format!("  {}", status),
+                        0.0,
+                        TextFormat {
+                            font_id: small_font.clone(),
+                            color: if checked.status == StepStatus::Failed {
+                                Color32::RED
+                            } else {
+                                Color32::DARK_GRAY
+                            },
+                            ..Default::default()
+                        },
+                    );
+
+                    let selected = self.state.selected_proof_step == Some(index);
+                    let label =
+                        ui.add(Label::new(text_job).wrap(false).sense(egui::Sense::click()));
+                    if selected {
+                        ui.painter().rect_stroke(
+                            label.rect,
+                            0.0,
+                            egui::Stroke::new(2.0, Color32::YELLOW),
+                        );
+                    }
+                    if label.clicked() {
+                        clicked = Some(index);
+                    }
+                }
+            },
+        );
+
+        if let Some(index) = clicked {
+            if self.state.selected_proof_step == Some(index) {
+                self.state.selected_proof_step = None;
+            } else {
+                self.state.selected_proof_step = Some(index);
+            }
+        }
+    }
+
+    /// Save the proof to a file in the given format
+    fn export_proof(&mut self, format: ProofFormat) {
+        let proof_string = match self.proof.as_ref().map(|proof| proof.to_format(format)) {
+            Some(Ok(proof_string)) => proof_string,
+            Some(Err(e)) => {
+                self.current_error = Some(e);
+                return;
+            }
+            None => return,
+        };
+
+        if let Some(file_path) = rfd::FileDialog::new()
+            .set_file_name(format!("sudoku_proof.{}", format.extension()))
+            .save_file()
+        {
+            let save_result = write_sudoku(proof_string, &file_path);
+            if let Err(e) = save_result {
+                self.current_error = Some(e);
+            }
+        }
+    }
+
+    /// Literals of the proof step selected in the proof view, if any
+    pub fn selected_proof_step_variables(&self) -> Option<Vec<CnfVariable>> {
+        if !self.state.show_proof {
+            return None;
+        }
+        let proof = self.proof.as_ref()?;
+        let checked = proof.steps.get(self.state.selected_proof_step?)?;
+        Some(
+            checked
+                .step
+                .literals()
+                .iter()
+                .map(|&literal| CnfVariable::from_cnf(literal, &self.state.encoding))
+                .collect(),
+        )
+    }
+}
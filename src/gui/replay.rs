@@ -0,0 +1,119 @@
+//! GUI code for the animated replay of the learned constraints
+
+use egui::{RichText, Ui};
+
+use crate::{get_cell, replay::changed_cells};
+
+use super::SATApp;
+
+impl SATApp {
+    /// Play, pause and step buttons, speed selection and a scrubber for the replay.
+    /// Also advances a playing replay.
+    pub fn replay_controls(&mut self, ui: &mut Ui, ctx: &egui::Context, text_scale: f32) {
+        let conflicts = self.trails.len();
+        let mut new_position = None;
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Replay:").size(text_scale));
+            ui.add_enabled_ui(conflicts > 0, |ui| {
+                let replay = &mut self.state.replay;
+                if ui
+                    .button(RichText::new("⏮").size(text_scale))
+                    .on_hover_text(RichText::new("Previous conflict").size(text_scale))
+                    .clicked()
+                {
+                    replay.pause();
+                    new_position = replay.step(false, conflicts);
+                }
+
+                let play_text = if replay.playing { "⏸" } else { "▶" };
+                if ui
+                    .button(RichText::new(play_text).size(text_scale))
+                    .on_hover_text(RichText::new("Play or pause the replay").size(text_scale))
+                    .clicked()
+                {
+                    if replay.playing {
+                        replay.pause();
+                    } else {
+                        new_position = replay.play(conflicts);
+                    }
+                }
+
+                if ui
+                    .button(RichText::new("⏭").size(text_scale))
+                    .on_hover_text(RichText::new("Next conflict").size(text_scale))
+                    .clicked()
+                {
+                    replay.pause();
+                    new_position = replay.step(true, conflicts);
+                }
+
+                if ui
+                    .button(RichText::new("⏹").size(text_scale))
+                    .on_hover_text(RichText::new("Stop the replay").size(text_scale))
+                    .clicked()
+                {
+                    replay.stop();
+                }
+
+                ui.add(
+                    egui::Slider::new(&mut replay.speed, 1.0..=50.0)
+                        .logarithmic(true)
+                        .max_decimals(0)
+                        .suffix("/s"),
+                )
+                .on_hover_text(RichText::new("Conflicts per second").size(text_scale));
+
+                // Scrubber over the conflicts, numbered from 1 like the constraint list
+                let mut scrubber = replay.position.map_or(0, |position| position + 1);
+                let scrubber_response =
+                    ui.add(egui::Slider::new(&mut scrubber, 1..=conflicts.max(1)));
+                if scrubber_response.changed() {
+                    replay.pause();
+                    new_position = replay.seek(scrubber - 1, conflicts);
+                }
+            });
+        });
+
+        if self.state.replay.playing {
+            let dt = ctx.input(|i| i.stable_dt);
+            if let Some(position) = self.state.replay.advance(dt, conflicts) {
+                new_position = Some(position);
+            }
+            ctx.request_repaint();
+        }
+
+        if let Some(position) = new_position {
+            self.state.show_trail = true;
+            self.select_constraint(position);
+        }
+    }
+
+    /// Highlights the cells whose trail literals changed since the previous conflict,
+    /// if the replay is showing a conflict
+    pub fn update_replay_changes(&mut self) {
+        for row in self.sudoku.iter_mut() {
+            for cell in row.iter_mut() {
+                cell.changed_in_replay = false;
+            }
+        }
+
+        let position = match self.state.replay.position {
+            Some(position) if position > 0 && position < self.trails.len() => position,
+            _ => return,
+        };
+        // The user may have selected another constraint after the replay moved
+        if !self.state.show_trail || self.state.selected_constraint_index() != Some(position) {
+            return;
+        }
+
+        let changed = changed_cells(
+            &self.trails.trail_at_index(position - 1),
+            &self.trails.trail_at_index(position),
+            &self.state.encoding,
+        );
+        for (row, col) in changed {
+            get_cell(&mut self.sudoku, row, col).changed_in_replay = true;
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! GUI code for stepping through the conflict analysis of a learned constraint
+
+use egui::{text::LayoutJob, Color32, FontId, Label, RichText, ScrollArea, TextStyle, Ui};
+
+use crate::{cnf::CnfVariable, resolution::Derivation};
+
+use super::SATApp;
+
+impl SATApp {
+    /// Button for opening the conflict analysis of the selected constraint
+    pub fn analysis_controls(&mut self, ui: &mut Ui, ctx: &egui::Context, text_scale: f32) {
+        ui.horizontal(|ui| {
+            let analysis_button = ui
+                .add_enabled(
+                    self.selected_derivation().is_some(),
+                    egui::Button::new(RichText::new("Conflict analysis").size(text_scale)),
+                )
+                .on_hover_text(
+                    RichText::new("Resolution steps that derived the selected constraint")
+                        .size(text_scale),
+                );
+            if analysis_button.clicked() {
+                self.state.show_analysis = true;
+                self.state.analysis_step = 0;
+            }
+        });
+
+        self.show_analysis(ctx);
+    }
+
+    /// Derivation of the selected constraint, None if no constraint is selected
+    /// or its derivation was not recorded
+    fn selected_derivation(&self) -> Option<(usize, Derivation)> {
+        let index = self.state.selected_constraint_index()?;
+        let (steps, minimization) = self.trails.resolution_at_index(index)?;
+        let learned = self.constraints.borrow().get(index)?.clone();
+        Some((index, Derivation::new(&steps, &minimization, &learned)))
+    }
+
+    /// Conflict analysis view, works as a separate window from the main app
+    fn show_analysis(&mut self, ctx: &egui::Context) {
+        if !self.state.show_analysis {
+            return;
+        }
+
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("immediate_viewport_analysis"),
+            egui::ViewportBuilder::default()
+                .with_title("Conflict analysis")
+                .with_inner_size([600.0, 500.0]),
+            |ctx, _class| {
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.state.show_analysis = false;
+                }
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let width = ui.available_width();
+                    let text_scale = (width / 50.0).max(9.0);
+                    match self.selected_derivation() {
+                        Some((index, derivation)) => {
+                            self.analysis_steps(ui, index, &derivation, text_scale)
+                        }
+                        None => {
+                            ui.label(
+                                RichText::new("Select a learned constraint to see its derivation")
+                                    .size(text_scale),
+                            );
+                        }
+                    }
+                });
+            },
+        )
+    }
+
+    /// Steps of the derivation up to the current one. The resolutions of the minimization follow
+    /// those of the analysis, and the last step is the learned clause.
+    fn analysis_steps(
+        &mut self,
+        ui: &mut Ui,
+        index: usize,
+        derivation: &Derivation,
+        text_scale: f32,
+    ) {
+        let step_count = derivation.steps.len() + derivation.minimization.len() + 1;
+        self.state.analysis_step = self.state.analysis_step.min(step_count - 1);
+
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(format!("Learned constraint #{}", index + 1))
+                    .size(text_scale * 1.2)
+                    .strong(),
+            );
+            if ui.button(RichText::new("⏮").size(text_scale)).clicked() {
+                self.state.analysis_step = self.state.analysis_step.saturating_sub(1);
+            }
+            if ui.button(RichText::new("⏭").size(text_scale)).clicked() {
+                self.state.analysis_step = (self.state.analysis_step + 1).min(step_count - 1);
+            }
+            if ui.button(RichText::new("All").size(text_scale)).clicked() {
+                self.state.analysis_step = step_count - 1;
+            }
+            ui.label(
+                RichText::new(format!(
+                    "Step {} of {}",
+                    self.state.analysis_step + 1,
+                    step_count
+                ))
+                .size(text_scale),
+            );
+        });
+        ui.separator();
+
+        let font_id = TextStyle::Body.resolve(ui.style());
+        let large_font = FontId::new(text_scale * 1.5, font_id.family.clone());
+        let small_font = FontId::new(text_scale, font_id.family.clone());
+        let text_color = ui.visuals().text_color();
+        let clause_label = |clause: &[i32], ui: &mut Ui| {
+            let mut text_job = LayoutJob::default();
+            self.append_clause_to_layout_job(
+                clause,
+                &mut text_job,
+                &large_font,
+                &small_font,
+                text_color,
+            );
+            text_job.wrap.max_width = ui.available_width();
+            ui.add(Label::new(text_job));
+        };
+
+        ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                let analysis_steps = derivation.steps.len();
+                for (step_index, step) in derivation
+                    .steps
+                    .iter()
+                    .chain(&derivation.minimization)
+                    .enumerate()
+                    .take(self.state.analysis_step + 1)
+                {
+                    if step_index == analysis_steps {
+                        ui.label(RichText::new("1st UIP clause").size(text_scale).strong());
+                        clause_label(derivation.first_uip_clause(), ui);
+                        ui.label(RichText::new("Minimization").size(text_scale).strong());
+                        ui.label(RichText::new("Removed literals").size(text_scale));
+                        clause_label(&derivation.minimized, ui);
+                        ui.separator();
+                    }
+                    match step.pivot {
+                        None => {
+                            ui.label(RichText::new("Conflict clause").size(text_scale).strong());
+                            clause_label(&step.reason, ui);
+                        }
+                        Some(pivot) => {
+                            let mut text_job = LayoutJob::default();
+                            text_job.append(
+                                &format!("{}. Resolve on ", step_index),
+                                0.0,
+                                egui::TextFormat {
+                                    font_id: small_font.clone(),
+                                    color: text_color,
+                                    ..Default::default()
+                                },
+                            );
+                            Self::append_var_to_layout_job(
+                                self.sudoku.clone(),
+                                &CnfVariable::from_cnf(pivot, &self.state.encoding),
+                                &mut text_job,
+                                &large_font,
+                                &small_font,
+                                text_color,
+                            );
+                            text_job.append(
+                                " with its reason",
+                                0.0,
+                                egui::TextFormat {
+                                    font_id: small_font.clone(),
+                                    color: text_color,
+                                    ..Default::default()
+                                },
+                            );
+                            ui.add(Label::new(text_job));
+                            clause_label(&step.reason, ui);
+                            ui.label(RichText::new("Resolvent").size(text_scale));
+                            clause_label(&step.resolvent, ui);
+                        }
+                    }
+                    ui.separator();
+                }
+
+                if self.state.analysis_step + 1 == step_count {
+                    if derivation.minimization.is_empty() {
+                        ui.label(RichText::new("1st UIP clause").size(text_scale).strong());
+                        clause_label(derivation.first_uip_clause(), ui);
+                        ui.label(RichText::new("Minimization").size(text_scale).strong());
+                        ui.label(RichText::new("No literals removed").size(text_scale));
+                    }
+                    ui.label(RichText::new("Learned clause").size(text_scale).strong());
+                    clause_label(&derivation.learned, ui);
+                }
+            });
+    }
+
+    /// Appends the literals of the clause joined by the disjunction symbol
+    fn append_clause_to_layout_job(
+        &self,
+        clause: &[i32],
+        text_job: &mut LayoutJob,
+        large_font: &FontId,
+        small_font: &FontId,
+        text_color: Color32,
+    ) {
+        let mut literals = clause.iter().peekable();
+        while let Some(&literal) = literals.next() {
+            Self::append_var_to_layout_job(
+                self.sudoku.clone(),
+                &CnfVariable::from_cnf(literal, &self.state.encoding),
+                text_job,
+                large_font,
+                small_font,
+                text_color,
+            );
+            if literals.peek().is_some() {
+                text_job.append(
+                    "v",
+                    0.0,
+                    egui::TextFormat {
+                        font_id: large_font.clone(),
+                        color: Color32::DARK_GRAY,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+}
@@ -1,10 +1,24 @@
-use std::thread::{self, available_parallelism};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread::{self, available_parallelism},
+    time::Duration,
+};
 
-use egui::{Label, RichText, Ui};
+use cadical::Solver;
+use egui::{FontId, Label, RichText, TextStyle, Ui};
 use egui_extras::{Column, TableBuilder};
 
 use crate::{
     app_state::EncodingType,
+    backend::{Backend, SolveLimits, SolverBackend},
+    cadical_wrapper::CadicalCallbackWrapper,
+    error::AppError,
+    external::{limit_arguments, solver_name, ExternalSolver},
+    lazy::{solve_sudoku_lazily, LAZY_ENCODING},
+    parse_numeric_input,
+    propagator::solve_sudoku_with_propagator,
+    schema::{Content, Document, SolveRecord},
     statistics::Statistics,
     sudoku::{solve_sudoku, write_sudoku},
 };
@@ -57,10 +71,163 @@ const ENCODINGS: [EncodingType; 8] = [
     EncodingType::Binary,
 ];
 
+/// A solve of processing with all configurations
+#[derive(Clone, Copy)]
+enum Configuration {
+    Eager(EncodingType, Backend),
+    /// The lazy re-solve baseline, see `lazy`
+    Lazy(Backend),
+    /// The uniqueness rules enforced by cadical's external propagator, see `propagator`
+    Propagator,
+}
+
+/// Solves the sudoku with a new solver of the backend and collects its statistics
+fn process_configuration(
+    clues: Vec<Vec<Option<i32>>>,
+    configuration: Configuration,
+    limits: SolveLimits,
+) -> Statistics {
+    match configuration {
+        Configuration::Eager(encoding, backend) => {
+            let mut solver = backend.solver();
+            solver.set_limits(&limits);
+            let mut stats = match solve_sudoku(&clues, solver.as_mut(), &encoding) {
+                Ok(solved) => {
+                    Statistics::from_cadical_stats(solver.stats(), encoding, clues, solved)
+                }
+                Err(failure) => Statistics::from_failure(solver.stats(), encoding, clues, failure),
+            };
+            stats.solver = backend.name().to_string();
+            stats
+        }
+        Configuration::Lazy(backend) => {
+            let mut solver = backend.solver();
+            let lazy = solve_sudoku_lazily(&clues, solver.as_mut(), &limits);
+            let mut stats = match lazy.solution {
+                Ok(solved) => {
+                    Statistics::from_cadical_stats(solver.stats(), LAZY_ENCODING, clues, solved)
+                }
+                Err(failure) => {
+                    Statistics::from_failure(solver.stats(), LAZY_ENCODING, clues, failure)
+                }
+            };
+            stats.lazy_clauses = Some(lazy.generated.len());
+            stats.solver = backend.name().to_string();
+            stats
+        }
+        Configuration::Propagator => {
+            let mut solver: Solver<CadicalCallbackWrapper> = Solver::with_config("plain").unwrap();
+            solver.set_limits(&limits);
+            let propagated = solve_sudoku_with_propagator(&clues, &mut solver);
+            let mut stats = match propagated.solution {
+                Ok(solved) => {
+                    Statistics::from_cadical_stats(solver.stats(), LAZY_ENCODING, clues, solved)
+                }
+                Err(failure) => {
+                    Statistics::from_failure(solver.stats(), LAZY_ENCODING, clues, failure)
+                }
+            };
+            stats.reason_clauses = Some(propagated.generated.len());
+            stats
+        }
+    }
+}
+
+/// Solves the sudoku with a new instance of the external solver and collects its statistics.
+/// Fails if the solver could not be run or printed no result.
+fn process_external(
+    path: PathBuf,
+    timeout: Duration,
+    clues: Vec<Vec<Option<i32>>>,
+    encoding: EncodingType,
+    limits: SolveLimits,
+) -> Result<Statistics, AppError> {
+    let mut solver = ExternalSolver::new(path, timeout);
+    solver.set_limits(&limits);
+    let result = solve_sudoku(&clues, &mut solver, &encoding);
+    if let Some(e) = solver.take_error() {
+        return Err(e);
+    }
+
+    let mut stats = match result {
+        Ok(solved) => Statistics::from_cadical_stats(solver.stats(), encoding, clues, solved),
+        Err(failure) => Statistics::from_failure(solver.stats(), encoding, clues, failure),
+    };
+    stats.solver = solver.name();
+    Ok(stats)
+}
+
+/// Solves the sudoku with each encoding using the solver executable and adds the statistics to
+/// the history. A failure to run the solver fails every encoding, so the first one is returned
+/// and the remaining encodings are not started.
+fn process_external_encodings(
+    path: PathBuf,
+    timeout: Duration,
+    clues: Vec<Vec<Option<i32>>>,
+    difficulty: Option<f64>,
+    limits: SolveLimits,
+    multithreaded: bool,
+    history: Arc<Mutex<Vec<Statistics>>>,
+) -> Result<(), AppError> {
+    if !multithreaded {
+        for encoding in ENCODINGS {
+            let mut stats =
+                process_external(path.clone(), timeout, clues.clone(), encoding, limits)?;
+            stats.difficulty = difficulty;
+
+            let mut history = history.lock().unwrap();
+            history.push(stats);
+        }
+        return Ok(());
+    }
+
+    let dispatch_amount = match available_parallelism() {
+        Ok(n) => n.get(),
+        Err(_) => 2,
+    };
+
+    for chunk in ENCODINGS.chunks(dispatch_amount) {
+        let mut handles = Vec::new();
+
+        for encoding in chunk.iter().copied() {
+            let path = path.clone();
+            let clues = clues.clone();
+            let history = history.clone();
+
+            let handle = thread::spawn(move || {
+                let mut stats = process_external(path, timeout, clues, encoding, limits)?;
+                stats.difficulty = difficulty;
+
+                let mut history = history.lock().unwrap();
+                history.push(stats);
+                Ok(())
+            });
+
+            handles.push(handle);
+        }
+
+        let mut error = None;
+        for handle in handles {
+            if let Err(e) = handle.join().unwrap() {
+                error.get_or_insert(e);
+            }
+        }
+        if let Some(e) = error {
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
 impl SATApp {
     /// Contains main app buttons and functionality for statistics and processing sudoku with
     /// multiple configurations
     pub fn statistics(&mut self, ui: &mut Ui, ctx: &egui::Context, text_scale: f32) {
+        let external_running = self.poll_external_solver();
+        if external_running {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+
         ui.horizontal(|ui| {
             if ui
                 .button(RichText::new("Statistics").size(text_scale))
@@ -70,11 +237,41 @@ impl SATApp {
             }
 
             if ui
-                .button(RichText::new("Process with all configurations").size(text_scale))
+                .add_enabled(
+                    !external_running,
+                    egui::Button::new(
+                        RichText::new("Process with all configurations").size(text_scale),
+                    ),
+                )
                 .clicked()
             {
                 self.reset_cadical_and_solved_sudoku();
                 let clues = self.get_option_value_sudoku();
+                // The sudoku is only rated on request, as rating takes several solves
+                let difficulty = self.difficulty.as_ref().map(|rating| rating.score);
+                let limits = self.state.solve_limits();
+
+                let backends = if self.state.compare_solvers {
+                    Backend::all().to_vec()
+                } else {
+                    vec![self.state.backend]
+                };
+                // The lazy re-solve baseline and the propagator, which only cadical has, are
+                // compared with the eager encodings
+                let configurations: Vec<Configuration> = ENCODINGS
+                    .iter()
+                    .flat_map(|&encoding| {
+                        backends
+                            .iter()
+                            .map(move |&backend| Configuration::Eager(encoding, backend))
+                    })
+                    .chain(backends.iter().map(|&backend| Configuration::Lazy(backend)))
+                    .chain(
+                        backends
+                            .contains(&Backend::Cadical)
+                            .then_some(Configuration::Propagator),
+                    )
+                    .collect();
 
                 if self.state.process_multithreaded {
                     let dispatch_amount = match available_parallelism() {
@@ -82,29 +279,20 @@ impl SATApp {
                         Err(_) => 2,
                     };
 
-                    for chunk in ENCODINGS.iter().as_slice().chunks(dispatch_amount) {
+                    for chunk in configurations.chunks(dispatch_amount) {
                         let mut handles = Vec::new();
 
-                        for encoding in chunk.iter().copied() {
+                        for configuration in chunk.iter().copied() {
                             let clues = clues.clone();
                             let history = self.state.history.clone();
 
+                            // The solvers are not Send, so each thread creates its own
                             let handle = thread::spawn(move || {
-                                let mut solver = cadical::Solver::with_config("plain").unwrap();
-
-                                let res = solve_sudoku(&clues, &mut solver, &encoding);
-                                if let Ok(res) = res {
-                                    let cadical_stats = solver.stats();
-                                    let stats = Statistics::from_cadical_stats(
-                                        cadical_stats,
-                                        encoding,
-                                        clues,
-                                        res,
-                                    );
-
-                                    let mut history = history.lock().unwrap();
-                                    history.push(stats);
-                                }
+                                let mut stats = process_configuration(clues, configuration, limits);
+                                stats.difficulty = difficulty;
+
+                                let mut history = history.lock().unwrap();
+                                history.push(stats);
                             });
 
                             handles.push(handle);
@@ -115,25 +303,19 @@ impl SATApp {
                         }
                     }
                 } else {
-                    for encoding in &ENCODINGS {
-                        let mut solver = cadical::Solver::with_config("plain").unwrap();
-
-                        let res = solve_sudoku(&clues, &mut solver, encoding);
-                        if let Ok(res) = res {
-                            let cadical_stats = solver.stats();
-                            let stats = Statistics::from_cadical_stats(
-                                cadical_stats,
-                                *encoding,
-                                clues.clone(),
-                                res,
-                            );
-
-                            let mut history = self.state.history.lock().unwrap();
-                            history.push(stats);
-                        }
+                    for configuration in configurations {
+                        let mut stats = process_configuration(clues.clone(), configuration, limits);
+                        stats.difficulty = difficulty;
+
+                        let mut history = self.state.history.lock().unwrap();
+                        history.push(stats);
                     }
                 }
 
+                if let Some(path) = self.state.external_solver.clone() {
+                    self.process_with_external_solver(path, &clues, difficulty, &limits);
+                }
+
                 self.state.show_statistics = true;
             }
 
@@ -141,11 +323,129 @@ impl SATApp {
                 &mut self.state.process_multithreaded,
                 RichText::new("Parallel").size(text_scale),
             );
+            ui.checkbox(
+                &mut self.state.compare_solvers,
+                RichText::new("Compare solvers").size(text_scale),
+            )
+            .on_hover_text(
+                RichText::new("Process each configuration with every solver backend")
+                    .size(text_scale),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            if ui
+                .button(RichText::new("External solver").size(text_scale))
+                .on_hover_text(
+                    RichText::new(
+                        "Solver executable, for example kissat or minisat, that also processes \
+                        all configurations. It is given a DIMACS file and has to print the \
+                        result and the model as `s` and `v` lines.",
+                    )
+                    .size(text_scale),
+                )
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new().pick_file() {
+                    self.state.external_solver = Some(path);
+                }
+            }
+
+            if let Some(path) = self.state.external_solver.clone() {
+                ui.label(RichText::new(path.display().to_string()).size(text_scale));
+                if ui.button(RichText::new("Clear").size(text_scale)).clicked() {
+                    self.state.external_solver = None;
+                }
+            }
+
+            let timeout_label = ui.label(RichText::new("Timeout (s):").size(text_scale));
+            let font_id = TextStyle::Body.resolve(ui.style());
+            let font = FontId::new(text_scale, font_id.family.clone());
+            ui.add(
+                egui::TextEdit::singleline(&mut self.state.external_timeout_input)
+                    .desired_width(3.0 * text_scale)
+                    .font(font)
+                    .horizontal_align(egui::Align::RIGHT),
+            )
+            .labelled_by(timeout_label.id);
+
+            if external_running {
+                ui.spinner();
+                ui.label(RichText::new("Running").size(text_scale));
+            }
         });
 
+        if let Some(path) = &self.state.external_solver {
+            let (_, unsupported) = limit_arguments(&solver_name(path), &self.state.solve_limits());
+            if !unsupported.is_empty() {
+                ui.label(
+                    RichText::new(format!(
+                        "The {} limits do not apply to the external solver, it has no options for them",
+                        unsupported.join(" and ")
+                    ))
+                    .size(text_scale)
+                    .color(ui.visuals().warn_fg_color),
+                );
+            }
+        }
+
         self.show_statistics(ctx);
     }
 
+    /// Starts solving the sudoku with each encoding using the solver executable on a worker
+    /// thread, so that the GUI is not blocked while the solver runs. The statistics are added
+    /// to the history as the encodings finish, see `poll_external_solver`.
+    fn process_with_external_solver(
+        &mut self,
+        path: PathBuf,
+        clues: &[Vec<Option<i32>>],
+        difficulty: Option<f64>,
+        limits: &SolveLimits,
+    ) {
+        let Some(timeout) = parse_numeric_input(&self.state.external_timeout_input) else {
+            self.current_error = Some(AppError::InvalidInput {
+                field: "The timeout of the external solver",
+                expected: "a positive number of seconds",
+            });
+            return;
+        };
+
+        let timeout = Duration::from_secs(timeout as u64);
+        let limits = *limits;
+        let clues = clues.to_vec();
+        let multithreaded = self.state.process_multithreaded;
+        let history = self.state.history.clone();
+        self.external_run = Some(thread::spawn(move || {
+            process_external_encodings(
+                path,
+                timeout,
+                clues,
+                difficulty,
+                limits,
+                multithreaded,
+                history,
+            )
+        }));
+    }
+
+    /// Checks whether the external solver has finished, and shows the error if it failed.
+    /// Returns true while it is still running.
+    fn poll_external_solver(&mut self) -> bool {
+        match self.external_run.take() {
+            Some(handle) if handle.is_finished() => {
+                if let Err(e) = handle.join().unwrap() {
+                    self.current_error = Some(e);
+                }
+                false
+            }
+            Some(handle) => {
+                self.external_run = Some(handle);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Statistics view, works as a separate window from the main app
     fn show_statistics(&mut self, ctx: &egui::Context) {
         if self.state.show_statistics {
@@ -179,12 +479,18 @@ impl SATApp {
                                 {
                                     self.export_as_csv();
                                 }
+                                if ui
+                                    .button(RichText::new("Export as json").size(text_scale))
+                                    .clicked()
+                                {
+                                    self.export_as_json();
+                                }
                             });
                             let history = self.state.history.lock().unwrap();
 
                             TableBuilder::new(ui)
                                 .striped(true)
-                                .columns(Column::auto().clip(false), 14)
+                                .columns(Column::auto().clip(false), 20)
                                 .auto_shrink([false, false])
                                 .max_scroll_height(height)
                                 .header(text_scale, |mut header| {
@@ -254,6 +560,73 @@ impl SATApp {
                                             .size(text_scale),
                                         );
                                     });
+                                    header.col(|ui| {
+                                        let label = Label::new(
+                                            RichText::new("Propa-\ngations").size(text_scale),
+                                        )
+                                        .wrap(false);
+                                        ui.add(label).on_hover_text(
+                                            RichText::new(
+                                                "How many literals were propagated during search",
+                                            )
+                                            .size(text_scale),
+                                        );
+                                    });
+                                    header.col(|ui| {
+                                        let label = Label::new(
+                                            RichText::new("Difficulty").size(text_scale),
+                                        )
+                                        .wrap(false);
+                                        ui.add(label).on_hover_text(
+                                            RichText::new(
+                                                "Rating from solver metrics and human techniques",
+                                            )
+                                            .size(text_scale),
+                                        );
+                                    });
+                                    header.col(|ui| {
+                                        let label = Label::new(
+                                            RichText::new("Lazy\nclauses").size(text_scale),
+                                        )
+                                        .wrap(false);
+                                        ui.add(label).on_hover_text(
+                                            RichText::new(
+                                                "How many uniqueness clauses were generated \
+                                                from broken models instead of encoded up front",
+                                            )
+                                            .size(text_scale),
+                                        );
+                                    });
+                                    header.col(|ui| {
+                                        let label = Label::new(
+                                            RichText::new("Reason\nclauses").size(text_scale),
+                                        )
+                                        .wrap(false);
+                                        ui.add(label).on_hover_text(
+                                            RichText::new(
+                                                "How many uniqueness clauses the propagator \
+                                                generated as reasons during search",
+                                            )
+                                            .size(text_scale),
+                                        );
+                                    });
+                                    header.col(|ui| {
+                                        let label =
+                                            Label::new(RichText::new("Solver").size(text_scale))
+                                                .wrap(false);
+                                        ui.add(label);
+                                    });
+                                    header.col(|ui| {
+                                        let label =
+                                            Label::new(RichText::new("Result").size(text_scale))
+                                                .wrap(false);
+                                        ui.add(label).on_hover_text(
+                                            RichText::new(
+                                                "UNKNOWN if the solve reached a solve limit",
+                                            )
+                                            .size(text_scale),
+                                        );
+                                    });
                                     header.col(|ui| {
                                         let label =
                                             Label::new(RichText::new("Encoding").size(text_scale))
@@ -397,12 +770,84 @@ impl SATApp {
                                                 );
                                             });
 
+                                            // propagations
+                                            row.col(|ui| {
+                                                ui.label(
+                                                    RichText::new(format!("{}", his.propagations))
+                                                        .size(text_scale),
+                                                );
+                                            });
+
+                                            // difficulty
+                                            row.col(|ui| {
+                                                ui.label(
+                                                    RichText::new(
+                                                        his.difficulty
+                                                            .map_or(String::new(), |score| {
+                                                                format!("{:.1}", score)
+                                                            }),
+                                                    )
+                                                    .size(text_scale),
+                                                );
+                                            });
+
+                                            // lazy clauses
+                                            row.col(|ui| {
+                                                ui.label(
+                                                    RichText::new(
+                                                        his.lazy_clauses
+                                                            .map_or(String::new(), |clauses| {
+                                                                clauses.to_string()
+                                                            }),
+                                                    )
+                                                    .size(text_scale),
+                                                );
+                                            });
+
+                                            // reason clauses
+                                            row.col(|ui| {
+                                                ui.label(
+                                                    RichText::new(
+                                                        his.reason_clauses
+                                                            .map_or(String::new(), |clauses| {
+                                                                clauses.to_string()
+                                                            }),
+                                                    )
+                                                    .size(text_scale),
+                                                );
+                                            });
+
+                                            // solver
+                                            row.col(|ui| {
+                                                ui.label(
+                                                    RichText::new(his.solver.clone())
+                                                        .size(text_scale),
+                                                );
+                                            });
+
+                                            // result
+                                            row.col(|ui| {
+                                                ui.label(
+                                                    RichText::new(his.result()).size(text_scale),
+                                                );
+                                            });
+
                                             // encoding
                                             row.col(|ui| {
                                                 ui.label(
                                                     RichText::new(
                                                         (match his.encoding {
                                                             EncodingType::Binary => "Binary",
+                                                            EncodingType::Decimal { .. }
+                                                                if his.lazy_clauses.is_some() =>
+                                                            {
+                                                                "Decimal (lazy re-solve)"
+                                                            }
+                                                            EncodingType::Decimal { .. }
+                                                                if his.reason_clauses.is_some() =>
+                                                            {
+                                                                "Decimal (propagator)"
+                                                            }
                                                             EncodingType::Decimal { .. } => {
                                                                 "Decimal"
                                                             }
@@ -490,4 +935,81 @@ impl SATApp {
             }
         }
     }
+
+    /// Save the statistics to a json file as a results document
+    fn export_as_json(&mut self) {
+        if let Some(file_path) = rfd::FileDialog::new()
+            .set_file_name("sudoku_statistics.json")
+            .save_file()
+        {
+            let history = self.state.history.lock().unwrap();
+            let records = history.iter().map(SolveRecord::from).collect();
+            let document = Document::new(Content::Results(records));
+            let save_result = write_sudoku(document.to_json(), &file_path);
+            if let Err(e) = save_result {
+                self.current_error = Some(e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    use super::*;
+    use crate::{gui::sudoku_cell::SudokuCell, sudoku::get_sudoku};
+
+    fn app_with_sample() -> (SATApp, Vec<Vec<Option<i32>>>) {
+        let cells = (1..=9)
+            .map(|row| {
+                (1..=9)
+                    .map(|col| SudokuCell::new(row, col, None, false))
+                    .collect()
+            })
+            .collect();
+        let clues = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        (SATApp::new(cells), clues)
+    }
+
+    fn wait_for_external_solver(app: &mut SATApp) {
+        while app.poll_external_solver() {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_process_with_external_solver() {
+        let path = std::env::temp_dir().join(format!("sat-step-unknown-{}", std::process::id()));
+        fs::write(&path, "#!/bin/sh\necho 's UNKNOWN'\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        for multithreaded in [false, true] {
+            let (mut app, clues) = app_with_sample();
+            app.state.process_multithreaded = multithreaded;
+            app.process_with_external_solver(path.clone(), &clues, None, &SolveLimits::default());
+            wait_for_external_solver(&mut app);
+
+            assert!(app.current_error.is_none());
+            let history = app.state.history.lock().unwrap();
+            assert_eq!(history.len(), ENCODINGS.len());
+            assert!(history.iter().all(|stats| stats.status.is_none()));
+        }
+        fs::remove_file(path).unwrap();
+
+        // A solver that can not be run is reported once and adds no rows
+        for multithreaded in [false, true] {
+            let (mut app, clues) = app_with_sample();
+            app.state.process_multithreaded = multithreaded;
+            let missing = PathBuf::from("/nonexistent/sat-step-solver");
+            app.process_with_external_solver(missing, &clues, None, &SolveLimits::default());
+            wait_for_external_solver(&mut app);
+
+            assert!(matches!(
+                app.current_error,
+                Some(AppError::ExternalSolver { .. })
+            ));
+            assert!(app.state.history.lock().unwrap().is_empty());
+        }
+    }
 }
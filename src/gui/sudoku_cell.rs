@@ -1,11 +1,15 @@
 //! Struct and GUI code for an individual sudoku cell
 
-use crate::{app_state::AppState, cnf::CnfVariable};
+use std::collections::BTreeSet;
+
+use crate::{app_state::AppState, cnf::CnfVariable, trail_diff::TrailChange};
 use egui::{
     text::{LayoutJob, TextFormat},
     Color32, Pos2, Rect, RichText, Stroke, Ui, Vec2,
 };
 
+use super::trail_diff::trail_change_color;
+
 const BIG_NUMBER_MULTIPLIER: f32 = 0.6; // Of cell size
 const LITTLE_NUMBER_MULTIPLIER: f32 = 0.225; // Of cell size
 const EMPTY_ROW_MULTIPLIER: f32 = LITTLE_NUMBER_MULTIPLIER * 0.3; // Of cell size
@@ -23,6 +27,13 @@ pub struct SudokuCell {
     pub clue: bool,            // Should the cell be darkened (is it a clue)
     pub part_of_conflict: bool, // Should the cell have highlighted borders
     pub fixed: bool, // Is the value of the cell set by fixed literals (used for highlighting)
+    pub conflicting_clue: bool, // Is the clue part of a minimal set of clues that make the sudoku unsolvable
+    pub rule_violations: Vec<String>, // Sudoku rules the solver's model breaks in this cell
+    pub candidates: BTreeSet<i32>, // Pencil marks given in the editor
+    pub heat: Option<f32>, // Value of the heatmap overlay between 0 and 1, None if the overlay is off
+    pub trail_change: Option<TrailChange>, // Change of the cell between the compared trails
+    pub hovered_in_trail: bool, // Is a literal of the cell hovered in the trail panel
+    pub changed_in_replay: bool, // Did the trail of the cell change since the previous conflict of the replay
     pub eq_symbols: Vec<(String, CnfVariable, bool)>, // Bool tells if symbol should be underlined (the variable is satisfied)
     // 1. bool tells us if the variable should be underlined (such as if it is part of the conflict)
     // 2. bool tells if the variable should have background (it is decided, not propagated)
@@ -62,6 +73,8 @@ impl SudokuCell {
         // Cell BG color
         if Some((self.row, self.col)) == app_state.selected_cell {
             ui.painter().rect_filled(rect, 0.0, Color32::LIGHT_BLUE);
+        } else if self.conflicting_clue {
+            ui.painter().rect_filled(rect, 0.0, Color32::RED);
         } else if self.clue {
             ui.painter().rect_filled(rect, 0.0, Color32::DARK_GRAY);
         } else if self.fixed && app_state.highlight_fixed_literals {
@@ -70,6 +83,11 @@ impl SudokuCell {
             ui.painter().rect_filled(rect, 0.0, Color32::GRAY);
         }
 
+        // Heatmap overlay on top of the background, so that clues can still be told apart
+        if let Some(heat) = self.heat {
+            ui.painter().rect_filled(rect, 0.0, heat_color(heat));
+        }
+
         let size = self.bottom_right.x - self.top_left.x;
         let center = self.top_left + Vec2::new(size / 2.0, size / 2.0);
 
@@ -83,6 +101,36 @@ impl SudokuCell {
             ui.painter().rect_stroke(rect, 0.0, stroke)
         }
 
+        // Cells on the compared trails, coloured by how they changed
+        if let Some(change) = self.trail_change {
+            ui.painter().rect_stroke(
+                rect.shrink(6.0),
+                0.0,
+                Stroke::new(3.0, trail_change_color(change)),
+            );
+        }
+
+        // Cells of the literal hovered in the trail panel
+        if self.hovered_in_trail {
+            ui.painter()
+                .rect_stroke(rect, 0.0, Stroke::new(4.0, Color32::from_rgb(0, 170, 255)));
+        }
+
+        // Cells whose trail changed since the previous conflict of the replay
+        if self.changed_in_replay {
+            ui.painter()
+                .rect_stroke(rect.shrink(4.0), 0.0, Stroke::new(2.0, Color32::LIGHT_BLUE));
+        }
+
+        // Cells that break the rules of sudoku get a red border, and the broken rules are shown on hover
+        if !self.rule_violations.is_empty() {
+            ui.painter()
+                .rect_stroke(rect.shrink(2.0), 0.0, Stroke::new(3.0, Color32::RED));
+            rect_action.clone().on_hover_text(
+                RichText::new(self.rule_violations.join("\n")).size(size * TOOLTIP_MULTIPLIER),
+            );
+        }
+
         if self.draw_big_number {
             if let Some(val) = self.value {
                 ui.painter().text(
@@ -273,6 +321,19 @@ impl Default for SudokuCell {
             row: 1,
             col: 1,
             fixed: false,
+            conflicting_clue: false,
+            rule_violations: Vec::new(),
+            candidates: BTreeSet::new(),
+            heat: None,
+            trail_change: None,
+            hovered_in_trail: false,
+            changed_in_replay: false,
         }
     }
 }
+
+/// Colour of the heatmap overlay, from transparent at 0 to opaque red at 1
+pub fn heat_color(heat: f32) -> Color32 {
+    let heat = heat.clamp(0.0, 1.0);
+    Color32::from_rgba_unmultiplied(255, (200.0 * (1.0 - heat)) as u8, 0, (220.0 * heat) as u8)
+}
@@ -47,6 +47,11 @@ impl SATApp {
         self.reset_visualization_info();
         self.update_selected_constraint();
         self.update_trail_info();
+        self.update_candidates();
+        self.update_heatmap();
+        self.update_replay_changes();
+        self.update_trail_diff();
+        self.update_trail_hover();
 
         self.draw_cells(ui, grid_origin, cell_size);
     }
@@ -165,6 +170,23 @@ impl SATApp {
         }
     }
 
+    /// Show the candidates (pencil marks) as little numbers in cells without other little symbols
+    fn update_candidates(&mut self) {
+        for cell in self.sudoku.iter_mut().flatten() {
+            if cell.draw_big_number
+                || !cell.little_numbers.is_empty()
+                || !cell.eq_symbols.is_empty()
+            {
+                continue;
+            }
+            cell.little_numbers = cell
+                .candidates
+                .iter()
+                .map(|&value| (value, false, false))
+                .collect();
+        }
+    }
+
     /// Update little symbols from a selected constraint in SudokuCells
     fn update_selected_constraint(&mut self) {
         // Only do this if we are visualizing a constraint (and not a trail). That case is handled in update_trail_info
@@ -174,9 +196,11 @@ impl SATApp {
 
         let mut variables = Vec::new();
 
-        // Visualize the clicked constraint, if there is one
+        // Visualize the selected proof step or the clicked constraint, if there is one
         // Otherwise show literals learned so far as little numbers, if we are not showing the solved sudoku
-        if let Some(constraint_index) = self.state.clicked_constraint_index {
+        if let Some(proof_variables) = self.selected_proof_step_variables() {
+            variables = proof_variables;
+        } else if let Some(constraint_index) = self.state.clicked_constraint_index {
             variables = self.rendered_constraints[constraint_index].clone();
         } else if !self.state.show_solved_sudoku {
             variables = self.state.little_number_constraints.clone();
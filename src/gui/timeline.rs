@@ -0,0 +1,239 @@
+//! GUI code for the timeline chart of the learning process
+
+use egui::{pos2, vec2, Align2, Color32, FontId, Rect, RichText, Sense, Shape, Stroke, Ui};
+
+use crate::{
+    ctrl_obj::{ConstraintList, ControllableObj},
+    timeline::{Timeline, TimelineSeries},
+};
+
+use super::SATApp;
+
+/// Space left for the axis labels, relative to the text scale
+const AXIS_MARGIN_MULTIPLIER: f32 = 4.0;
+
+impl SATApp {
+    /// Button for opening the timeline view
+    pub fn timeline_controls(&mut self, ui: &mut Ui, ctx: &egui::Context, text_scale: f32) {
+        ui.horizontal(|ui| {
+            let timeline_button = ui.add_enabled(
+                !self.constraints.is_empty(),
+                egui::Button::new(RichText::new("Timeline").size(text_scale)),
+            );
+            if timeline_button.clicked() {
+                self.state.show_timeline = true;
+            }
+        });
+
+        self.show_timeline(ctx);
+    }
+
+    /// Selects a learned constraint by its index in the full list of constraints and shows the
+    /// page containing it. The filters are kept, unless they hide the constraint.
+    pub fn select_constraint(&mut self, index: usize) {
+        let position = match self.state.filtered_position(index) {
+            Some(position) => position,
+            None => {
+                self.state.clear_filters();
+                index
+            }
+        };
+        (self.rendered_constraints, self.rendered_trails) = self.state.get_filtered();
+        self.state
+            .set_page_number((position / self.state.page_length) as i32);
+        (self.rendered_constraints, self.rendered_trails) = self.state.get_filtered();
+
+        let row = position % self.state.page_length;
+        if row < self.rendered_constraints.len() {
+            let clauses = ConstraintList {
+                clauses: self.rendered_constraints.clone(),
+                trail: self.rendered_trails.clone(),
+                combiner: "v".to_string(),
+            };
+            clauses.clicked(&mut self.state, row);
+            self.state.scroll_to_constraint = true;
+        }
+    }
+
+    /// Timeline view, works as a separate window from the main app
+    fn show_timeline(&mut self, ctx: &egui::Context) {
+        if !self.state.show_timeline {
+            return;
+        }
+
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("immediate_viewport_timeline"),
+            egui::ViewportBuilder::default()
+                .with_title("Timeline")
+                .with_inner_size([800.0, 400.0]),
+            |ctx, _class| {
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    self.state.show_timeline = false;
+                }
+
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let width = ui.available_width();
+                    let text_scale = (width / 80.0).max(9.0);
+                    let timeline = Timeline::new(&self.constraints, &self.trails);
+
+                    ui.horizontal(|ui| {
+                        for series in TimelineSeries::all() {
+                            let mut shown = self.state.timeline_series.contains(&series);
+                            let checkbox = ui.checkbox(
+                                &mut shown,
+                                RichText::new(series.to_string())
+                                    .size(text_scale)
+                                    .color(series_color(series)),
+                            );
+                            if checkbox.changed() {
+                                if shown {
+                                    self.state.timeline_series.push(series);
+                                } else {
+                                    self.state.timeline_series.retain(|&s| s != series);
+                                }
+                            }
+                        }
+                        ui.label(
+                            RichText::new(format!("Restarts: {}", timeline.restarts.len()))
+                                .size(text_scale)
+                                .color(Color32::GRAY),
+                        );
+                    });
+                    ui.separator();
+
+                    self.timeline_chart(ui, &timeline, text_scale);
+                });
+            },
+        )
+    }
+
+    /// Chart with the conflict index on the x-axis. Clicking a point selects the constraint
+    /// learned at that conflict.
+    fn timeline_chart(&mut self, ui: &mut Ui, timeline: &Timeline, text_scale: f32) {
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), Sense::click());
+        let margin = text_scale * AXIS_MARGIN_MULTIPLIER;
+        let chart = Rect::from_min_max(
+            rect.min + vec2(margin, text_scale),
+            rect.max - vec2(text_scale, margin),
+        );
+        let painter = ui.painter_at(rect);
+        let font = FontId::proportional(text_scale);
+        let text_color = ui.visuals().text_color();
+
+        if timeline.points.is_empty() || chart.width() <= 0.0 || chart.height() <= 0.0 {
+            return;
+        }
+
+        let conflicts = timeline.points.len();
+        let max_value = self
+            .state
+            .timeline_series
+            .iter()
+            .map(|&series| timeline.max_value(series))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let x_of = |index: usize| {
+            chart.left() + chart.width() * index as f32 / (conflicts.max(2) - 1) as f32
+        };
+        let y_of = |value: usize| chart.bottom() - chart.height() * value as f32 / max_value as f32;
+
+        // Axes and their labels
+        let axis_stroke = Stroke::new(1.0, Color32::GRAY);
+        painter.line_segment([chart.left_bottom(), chart.right_bottom()], axis_stroke);
+        painter.line_segment([chart.left_bottom(), chart.left_top()], axis_stroke);
+        painter.text(
+            chart.left_top(),
+            Align2::RIGHT_TOP,
+            format!("{} ", max_value),
+            font.clone(),
+            text_color,
+        );
+        painter.text(
+            chart.left_bottom(),
+            Align2::RIGHT_BOTTOM,
+            "0 ",
+            font.clone(),
+            text_color,
+        );
+        painter.text(
+            chart.right_bottom(),
+            Align2::RIGHT_TOP,
+            format!("{}", conflicts),
+            font.clone(),
+            text_color,
+        );
+        painter.text(
+            pos2(chart.center().x, chart.bottom()),
+            Align2::CENTER_TOP,
+            "Conflict",
+            font.clone(),
+            text_color,
+        );
+
+        // Restarts as vertical lines
+        for &restart in &timeline.restarts {
+            let x = x_of(restart.min(conflicts - 1));
+            painter.line_segment(
+                [pos2(x, chart.top()), pos2(x, chart.bottom())],
+                Stroke::new(1.0, Color32::from_gray(90)),
+            );
+        }
+
+        // The selected constraint
+        let selected = self
+            .state
+            .clicked_constraint_index
+            .and_then(|row| self.state.rendered_indexes.get(row).copied());
+        if let Some(selected) = selected {
+            let x = x_of(selected);
+            painter.line_segment(
+                [pos2(x, chart.top()), pos2(x, chart.bottom())],
+                Stroke::new(2.0, Color32::YELLOW),
+            );
+        }
+
+        for &series in &self.state.timeline_series {
+            let points = timeline
+                .points
+                .iter()
+                .enumerate()
+                .map(|(index, point)| pos2(x_of(index), y_of(series.value(point))))
+                .collect::<Vec<_>>();
+            painter.add(Shape::line(points, Stroke::new(1.5, series_color(series))));
+        }
+
+        // Hovering shows the values at the nearest conflict, clicking selects its constraint
+        let hovered = response.hover_pos().map(|pos| {
+            let fraction = ((pos.x - chart.left()) / chart.width()).clamp(0.0, 1.0);
+            (fraction * (conflicts - 1) as f32).round() as usize
+        });
+        if let Some(index) = hovered {
+            let x = x_of(index);
+            painter.line_segment(
+                [pos2(x, chart.top()), pos2(x, chart.bottom())],
+                Stroke::new(1.0, text_color),
+            );
+            let point = &timeline.points[index];
+            let text = TimelineSeries::all()
+                .iter()
+                .map(|series| format!("{}: {}", series, series.value(point)))
+                .collect::<Vec<String>>()
+                .join("\n");
+            response.clone().on_hover_text_at_pointer(
+                RichText::new(format!("Conflict {}\n{}", index + 1, text)).size(text_scale),
+            );
+            if response.clicked() {
+                self.select_constraint(index);
+            }
+        }
+    }
+}
+
+fn series_color(series: TimelineSeries) -> Color32 {
+    match series {
+        TimelineSeries::ClauseLength => Color32::from_rgb(80, 140, 255),
+        TimelineSeries::TrailLength => Color32::from_rgb(60, 180, 75),
+        TimelineSeries::Decisions => Color32::from_rgb(255, 140, 0),
+    }
+}
@@ -0,0 +1,147 @@
+//! GUI code for comparing the trails of two learned constraints
+
+use egui::{text::LayoutJob, Color32, FontId, Label, RichText, ScrollArea, TextStyle, Ui};
+
+use crate::{
+    cnf::CnfVariable,
+    get_cell,
+    trail_diff::{TrailChange, TrailDiff},
+};
+
+use super::SATApp;
+
+impl SATApp {
+    /// Pinning a constraint for comparison, and a legend for the colours of the comparison
+    pub fn trail_comparison_controls(&mut self, ui: &mut Ui, text_scale: f32) {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Compare trails:").size(text_scale));
+            let selected = self.state.selected_constraint_index();
+            if ui
+                .add_enabled(
+                    selected.is_some(),
+                    egui::Button::new(RichText::new("Pin selected").size(text_scale)),
+                )
+                .on_hover_text(
+                    RichText::new(
+                        "Compare the trail of the selected constraint with the next selected one. \
+                        Shift-clicking a constraint in the list also pins it.",
+                    )
+                    .size(text_scale),
+                )
+                .clicked()
+            {
+                self.state.compared_constraint_index = selected;
+            }
+
+            if let Some(pinned) = self.state.compared_constraint_index {
+                ui.label(RichText::new(format!("#{}", pinned + 1)).size(text_scale));
+                if ui.button(RichText::new("Clear").size(text_scale)).clicked() {
+                    self.state.compared_constraint_index = None;
+                }
+
+                for change in [TrailChange::Kept, TrailChange::Retracted, TrailChange::New] {
+                    ui.label(
+                        RichText::new(format!("{:?}", change))
+                            .size(text_scale)
+                            .color(trail_change_color(change)),
+                    );
+                }
+            }
+        });
+    }
+
+    /// The compared constraints in the order they were learned, and the difference of their trails.
+    /// None if there is no pinned constraint or the selected constraint is the pinned one.
+    fn trail_diff(&self) -> Option<(usize, usize, TrailDiff)> {
+        let pinned = self.state.compared_constraint_index?;
+        let selected = self.state.selected_constraint_index()?;
+        if pinned == selected || pinned.max(selected) >= self.trails.len() {
+            return None;
+        }
+
+        let (earlier, later) = (pinned.min(selected), pinned.max(selected));
+        let diff = TrailDiff::new(
+            &self.trails.trail_at_index(earlier),
+            &self.trails.trail_at_index(later),
+        );
+        Some((earlier, later, diff))
+    }
+
+    /// Row above the constraint list with the compared trails, each literal coloured by its change
+    pub fn trail_diff_labels(&self, ui: &mut Ui, text_scale: f32) {
+        let (earlier, later, diff) = match self.trail_diff() {
+            Some(comparison) => comparison,
+            None => return,
+        };
+        let level = diff.backjump_level(&self.trails.var_is_propagated_at_index(earlier));
+
+        ui.horizontal_wrapped(|ui| {
+            ui.add(
+                Label::new(
+                    RichText::new(format!(
+                        "Trails #{} → #{}: {} kept, {} retracted, {} new, backjumped to level {}",
+                        earlier + 1,
+                        later + 1,
+                        diff.kept.len(),
+                        diff.retracted.len(),
+                        diff.new.len(),
+                        level
+                    ))
+                    .size(text_scale),
+                )
+                .wrap(false),
+            );
+        });
+
+        let font_id = TextStyle::Body.resolve(ui.style());
+        let large_font = FontId::new(text_scale * 1.5, font_id.family.clone());
+        let small_font = FontId::new(text_scale, font_id.family.clone());
+        let mut text_job = LayoutJob::default();
+        for (literal, change) in diff.literals() {
+            let first_section = text_job.sections.len();
+            Self::append_var_to_layout_job(
+                self.sudoku.clone(),
+                &CnfVariable::from_cnf(literal, &self.state.encoding),
+                &mut text_job,
+                &large_font,
+                &small_font,
+                Color32::BLACK,
+            );
+            for section in text_job.sections[first_section..].iter_mut() {
+                section.format.background = trail_change_color(change);
+            }
+            text_job.append(" ", 0.0, Default::default());
+        }
+        text_job.wrap.max_width = ui.available_width();
+        ScrollArea::vertical()
+            .id_source("trail_diff")
+            .max_height(text_scale * 6.0)
+            .show(ui, |ui| {
+                ui.add(Label::new(text_job));
+            });
+    }
+
+    /// Colours the cells of the compared trails by their most significant change
+    pub fn update_trail_diff(&mut self) {
+        for row in self.sudoku.iter_mut() {
+            for cell in row.iter_mut() {
+                cell.trail_change = None;
+            }
+        }
+
+        if let Some((_, _, diff)) = self.trail_diff() {
+            for ((row, col), change) in diff.cell_changes(&self.state.encoding) {
+                get_cell(&mut self.sudoku, row, col).trail_change = Some(change);
+            }
+        }
+    }
+}
+
+/// Colour used for a change in both the list and the grid
+pub fn trail_change_color(change: TrailChange) -> Color32 {
+    match change {
+        TrailChange::Kept => Color32::from_rgb(200, 200, 200),
+        TrailChange::Retracted => Color32::from_rgb(230, 90, 200),
+        TrailChange::New => Color32::from_rgb(90, 220, 90),
+    }
+}
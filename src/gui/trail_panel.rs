@@ -0,0 +1,137 @@
+//! GUI code for the textual trail of the selected conflict, grouped by decision level
+
+use egui::{text::LayoutJob, Color32, FontId, Label, RichText, ScrollArea, Sense, TextStyle, Ui};
+
+use crate::{cnf::CnfVariable, get_cell};
+
+use super::SATApp;
+
+/// Background of the decisions, the same as for decided little numbers on the grid
+const DECISION_BG_COLOR: Color32 = Color32::from_gray(220);
+
+impl SATApp {
+    /// Panel next to the grid listing the trail of the selected constraint in order
+    pub fn trail_panel(&mut self, ctx: &egui::Context) {
+        self.state.hovered_trail_literal = None;
+        if !self.state.show_trail_panel {
+            return;
+        }
+
+        egui::SidePanel::right("trail_panel")
+            .resizable(true)
+            .default_width(250.0)
+            .show(ctx, |ui| {
+                let text_scale = (ui.available_width() / 20.0).clamp(10.0, 16.0);
+                match self.state.selected_constraint_index() {
+                    Some(index) if index < self.trails.len() => {
+                        self.trail_levels(ui, index, text_scale)
+                    }
+                    _ => {
+                        ui.label(
+                            RichText::new("Select a learned constraint to see its trail")
+                                .size(text_scale),
+                        );
+                    }
+                }
+            });
+    }
+
+    /// Trail of the conflict at the index, one heading per decision level
+    fn trail_levels(&mut self, ui: &mut Ui, index: usize, text_scale: f32) {
+        let levels = self.trails.decision_levels_at_index(index);
+        ui.label(
+            RichText::new(format!("Trail at conflict #{}", index + 1))
+                .size(text_scale * 1.2)
+                .strong(),
+        );
+        ui.label(
+            RichText::new(format!(
+                "{} literals on {} decision levels",
+                levels.iter().map(Vec::len).sum::<usize>(),
+                levels.len() - 1
+            ))
+            .size(text_scale),
+        );
+        ui.separator();
+
+        let font_id = TextStyle::Body.resolve(ui.style());
+        let large_font = FontId::new(text_scale * 1.5, font_id.family.clone());
+        let small_font = FontId::new(text_scale, font_id.family.clone());
+        let text_color = ui.visuals().text_color();
+
+        ScrollArea::vertical()
+            .id_source("trail_panel_levels")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                let mut position = 0;
+                for (level, literals) in levels.iter().enumerate() {
+                    if literals.is_empty() {
+                        continue;
+                    }
+                    ui.label(
+                        RichText::new(format!("Level {}", level))
+                            .size(text_scale)
+                            .strong(),
+                    );
+                    for &(literal, is_propagated) in literals {
+                        position += 1;
+                        let mut text_job = LayoutJob::default();
+                        text_job.append(
+                            &format!("{:>4}  ", position),
+                            0.0,
+                            egui::TextFormat {
+                                font_id: small_font.clone(),
+                                color: Color32::GRAY,
+                                ..Default::default()
+                            },
+                        );
+                        let first_section = text_job.sections.len();
+                        Self::append_var_to_layout_job(
+                            self.sudoku.clone(),
+                            &CnfVariable::from_cnf(literal, &self.state.encoding),
+                            &mut text_job,
+                            &large_font,
+                            &small_font,
+                            if is_propagated {
+                                text_color
+                            } else {
+                                Color32::BLACK
+                            },
+                        );
+                        if !is_propagated {
+                            for section in text_job.sections[first_section..].iter_mut() {
+                                section.format.background = DECISION_BG_COLOR;
+                            }
+                        }
+
+                        let kind = if is_propagated {
+                            "Propagated"
+                        } else {
+                            "Decision"
+                        };
+                        let response = ui
+                            .add(Label::new(text_job).sense(Sense::hover()))
+                            .on_hover_text(RichText::new(kind).size(text_scale));
+                        if response.hovered() {
+                            self.state.hovered_trail_literal = Some(literal);
+                        }
+                    }
+                }
+            });
+    }
+
+    /// Highlights the cells of the trail literal hovered in the trail panel
+    pub fn update_trail_hover(&mut self) {
+        for row in self.sudoku.iter_mut() {
+            for cell in row.iter_mut() {
+                cell.hovered_in_trail = false;
+            }
+        }
+
+        if let Some(literal) = self.state.hovered_trail_literal {
+            for (row, col) in CnfVariable::from_cnf(literal, &self.state.encoding).cells() {
+                get_cell(&mut self.sudoku, row, col).hovered_in_trail = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Counts of how much each cell was involved in the search, shown as a heatmap on the grid
+
+use std::fmt;
+
+use crate::{
+    activity::VariableActivity, app_state::EncodingType, cnf::CnfVariable, ConstraintList, Trail,
+};
+
+/// What the heatmap counts for each cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeatmapMetric {
+    InClauses,
+    InConflicts,
+    DecidedOnTrail,
+    SolverDecisions,
+}
+
+impl HeatmapMetric {
+    pub fn all() -> [HeatmapMetric; 4] {
+        [
+            HeatmapMetric::InClauses,
+            HeatmapMetric::InConflicts,
+            HeatmapMetric::DecidedOnTrail,
+            HeatmapMetric::SolverDecisions,
+        ]
+    }
+
+    /// Longer explanation of the metric, for hover texts and the legend
+    pub fn description(&self) -> &'static str {
+        match self {
+            HeatmapMetric::InClauses => "Number of learned clauses with a literal of the cell",
+            HeatmapMetric::InConflicts => "Number of conflicts with a literal of the cell",
+            HeatmapMetric::DecidedOnTrail => {
+                "Number of decisions on the cell in the trails at conflicts"
+            }
+            HeatmapMetric::SolverDecisions => {
+                "Number of decisions the solver made on the cell during the whole search"
+            }
+        }
+    }
+}
+
+impl fmt::Display for HeatmapMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HeatmapMetric::InClauses => "In clauses",
+            HeatmapMetric::InConflicts => "In conflicts",
+            HeatmapMetric::DecidedOnTrail => "Decided on trail",
+            HeatmapMetric::SolverDecisions => "Solver decisions",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Counts the metric for each cell. The counts are indexed from 0, so `counts[0][0]` is cell (1, 1).
+/// Solver decisions are all zero if the activity of the variables is not known.
+pub fn cell_counts(
+    metric: HeatmapMetric,
+    constraints: &ConstraintList,
+    trails: &Trail,
+    activity: Option<&VariableActivity>,
+    encoding: &EncodingType,
+) -> Vec<Vec<usize>> {
+    let mut counts = vec![vec![0; 9]; 9];
+    match metric {
+        HeatmapMetric::InClauses => {
+            for constraint in constraints.borrow().iter() {
+                count_once(&mut counts, constraint, encoding);
+            }
+        }
+        HeatmapMetric::InConflicts => {
+            for conflict in trails.conflict_literals.borrow().iter() {
+                count_once(&mut counts, conflict, encoding);
+            }
+        }
+        HeatmapMetric::DecidedOnTrail => {
+            let trail = trails.trail.borrow();
+            let var_is_propagated = trails.var_is_propagated.borrow();
+            for (literals, propagated) in trail.iter().zip(var_is_propagated.iter()) {
+                for (&literal, &is_propagated) in literals.iter().zip(propagated.iter()) {
+                    if is_propagated {
+                        continue;
+                    }
+                    for (row, col) in CnfVariable::from_cnf(literal, encoding).cells() {
+                        counts[row as usize - 1][col as usize - 1] += 1;
+                    }
+                }
+            }
+        }
+        HeatmapMetric::SolverDecisions => {
+            if let Some(activity) = activity {
+                counts = activity.cell_decisions();
+            }
+        }
+    }
+    counts
+}
+
+/// Increases the count of each cell in the clause by one, however many of its literals are in the clause
+fn count_once(counts: &mut [Vec<usize>], clause: &[i32], encoding: &EncodingType) {
+    let mut cells = clause
+        .iter()
+        .flat_map(|&literal| CnfVariable::from_cnf(literal, encoding).cells())
+        .collect::<Vec<(i32, i32)>>();
+    cells.sort();
+    cells.dedup();
+    for (row, col) in cells {
+        counts[row as usize - 1][col as usize - 1] += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cnf::{binary_encoding::eq_cnf_identifier, decimal_encoding::cnf_identifier};
+
+    use super::*;
+
+    const ENCODING: EncodingType = EncodingType::Decimal {
+        cell_at_least_one: true,
+        cell_at_most_one: false,
+        sudoku_has_all_values: false,
+        sudoku_has_unique_values: true,
+    };
+
+    #[test]
+    fn test_cell_counts() {
+        let mut constraints = ConstraintList::new();
+        let mut trails = Trail::new();
+
+        // Two literals of the same cell count once
+        constraints.push(vec![cnf_identifier(1, 1, 1), -cnf_identifier(1, 1, 2)]);
+        constraints.push(vec![cnf_identifier(1, 1, 3), cnf_identifier(9, 9, 3)]);
+        trails.push(
+            vec![-cnf_identifier(2, 3, 4)],
+            vec![cnf_identifier(4, 5, 6), cnf_identifier(2, 3, 4)],
+            vec![false, true],
+        );
+        trails.push(
+            vec![-cnf_identifier(2, 3, 4), -cnf_identifier(2, 3, 5)],
+            vec![cnf_identifier(4, 5, 6), cnf_identifier(4, 5, 7)],
+            vec![false, false],
+        );
+
+        let in_clauses = cell_counts(
+            HeatmapMetric::InClauses,
+            &constraints,
+            &trails,
+            None,
+            &ENCODING,
+        );
+        assert_eq!(in_clauses[0][0], 2);
+        assert_eq!(in_clauses[8][8], 1);
+        assert_eq!(in_clauses.iter().flatten().sum::<usize>(), 3);
+
+        let in_conflicts = cell_counts(
+            HeatmapMetric::InConflicts,
+            &constraints,
+            &trails,
+            None,
+            &ENCODING,
+        );
+        assert_eq!(in_conflicts[1][2], 2);
+        assert_eq!(in_conflicts.iter().flatten().sum::<usize>(), 2);
+
+        let decided = cell_counts(
+            HeatmapMetric::DecidedOnTrail,
+            &constraints,
+            &trails,
+            None,
+            &ENCODING,
+        );
+        assert_eq!(decided[3][4], 3);
+        assert_eq!(decided[1][2], 0);
+
+        // Without the activity of the variables there are no solver decisions to count
+        let solver_decisions = cell_counts(
+            HeatmapMetric::SolverDecisions,
+            &constraints,
+            &trails,
+            None,
+            &ENCODING,
+        );
+        assert_eq!(solver_decisions.iter().flatten().sum::<usize>(), 0);
+    }
+
+    #[test]
+    fn test_cells_of_equality_variables() {
+        let mut constraints = ConstraintList::new();
+        let trails = Trail::new();
+        constraints.push(vec![eq_cnf_identifier(1, 2, 3, 4, 0)]);
+
+        let counts = cell_counts(
+            HeatmapMetric::InClauses,
+            &constraints,
+            &trails,
+            None,
+            &EncodingType::Binary,
+        );
+        assert_eq!(counts[0][1], 1);
+        assert_eq!(counts[2][3], 1);
+        assert_eq!(counts.iter().flatten().sum::<usize>(), 2);
+    }
+}
@@ -0,0 +1,116 @@
+//! Undo/redo history for editing the clues of the sudoku
+
+use std::collections::VecDeque;
+
+/// Maximum number of edits that can be undone
+pub const HISTORY_LIMIT: usize = 100;
+
+/// Struct for storing the clues of the sudoku before each edit.
+/// The oldest edits are dropped when the history grows over its limit.
+pub struct EditHistory {
+    undo_stack: VecDeque<Vec<Vec<Option<i32>>>>,
+    redo_stack: Vec<Vec<Vec<Option<i32>>>>,
+    limit: usize,
+}
+
+impl EditHistory {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Record an edit from `before` to `after`. Does nothing if the clues did not change.
+    /// A new edit clears the redo history.
+    pub fn record(&mut self, before: Vec<Vec<Option<i32>>>, after: &[Vec<Option<i32>>]) {
+        if before == after || self.limit == 0 {
+            return;
+        }
+        if self.undo_stack.len() == self.limit {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(before);
+        self.redo_stack.clear();
+    }
+
+    /// Returns the clues before the latest edit, and stores the current clues for redoing
+    pub fn undo(&mut self, current: Vec<Vec<Option<i32>>>) -> Option<Vec<Vec<Option<i32>>>> {
+        let previous = self.undo_stack.pop_back()?;
+        self.redo_stack.push(current);
+        Some(previous)
+    }
+
+    /// Returns the clues after the latest undone edit, and stores the current clues for undoing
+    pub fn redo(&mut self, current: Vec<Vec<Option<i32>>>) -> Option<Vec<Vec<Option<i32>>>> {
+        let next = self.redo_stack.pop()?;
+        self.undo_stack.push_back(current);
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new(HISTORY_LIMIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_with(value: Option<i32>) -> Vec<Vec<Option<i32>>> {
+        let mut grid = vec![vec![None; 9]; 9];
+        grid[0][0] = value;
+        grid
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = EditHistory::default();
+        assert!(!history.can_undo());
+
+        history.record(grid_with(None), &grid_with(Some(1)));
+        history.record(grid_with(Some(1)), &grid_with(Some(2)));
+        // Unchanged clues are not recorded
+        history.record(grid_with(Some(2)), &grid_with(Some(2)));
+
+        assert_eq!(history.undo(grid_with(Some(2))), Some(grid_with(Some(1))));
+        assert_eq!(history.undo(grid_with(Some(1))), Some(grid_with(None)));
+        assert_eq!(history.undo(grid_with(None)), None);
+
+        assert_eq!(history.redo(grid_with(None)), Some(grid_with(Some(1))));
+        assert!(history.can_redo());
+
+        // A new edit clears the redo history
+        history.record(grid_with(Some(1)), &grid_with(Some(3)));
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(grid_with(Some(3))), Some(grid_with(Some(1))));
+    }
+
+    #[test]
+    fn test_history_limit() {
+        let mut history = EditHistory::new(3);
+        for value in 1..=5 {
+            history.record(grid_with(Some(value)), &grid_with(Some(value + 1)));
+        }
+
+        let mut current = grid_with(Some(6));
+        let mut undone = 0;
+        while let Some(previous) = history.undo(current.clone()) {
+            current = previous;
+            undone += 1;
+        }
+        assert_eq!(undone, 3);
+        assert_eq!(current, grid_with(Some(3)));
+    }
+}
@@ -0,0 +1,642 @@
+//! Logical sudoku solver that uses the same techniques as humans do.
+//! Each step is recorded with an explanation, so that it can be compared to what CaDiCaL learns.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt,
+};
+
+use crate::validation::{unit_cells, Unit};
+
+/// Solving techniques, ordered from the easiest to the hardest
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Technique {
+    NakedSingle,
+    HiddenSingle,
+    NakedPair,
+    HiddenPair,
+    PointingPair,
+    BoxLineReduction,
+    XWing,
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Technique::NakedSingle => "Naked single",
+            Technique::HiddenSingle => "Hidden single",
+            Technique::NakedPair => "Naked pair",
+            Technique::HiddenPair => "Hidden pair",
+            Technique::PointingPair => "Pointing",
+            Technique::BoxLineReduction => "Box-line reduction",
+            Technique::XWing => "X-wing",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A single deduction made with a technique. All positions are 1-9 indexed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub technique: Technique,
+    pub placement: Option<(i32, i32, i32)>, // (row, col, value) set by the step
+    pub eliminations: Vec<(i32, i32, i32)>, // (row, col, value) candidates removed by the step
+    pub explanation: String,
+}
+
+/// How a learned unit clause relates to the steps of the human solver
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnitExplanation {
+    Clues,       // Follows directly from the clues
+    Step(usize), // Follows after the step with this index
+    Unexplained, // The techniques are not enough to deduce it
+}
+
+/// Result of solving a sudoku with human techniques
+pub struct HumanSolution {
+    pub steps: Vec<Step>,
+    pub grid: Vec<Vec<Option<i32>>>,
+    initial_candidates: Vec<Vec<BTreeSet<i32>>>,
+}
+
+impl HumanSolution {
+    pub fn is_solved(&self) -> bool {
+        self.grid.iter().flatten().all(|value| value.is_some())
+    }
+
+    /// The hardest technique needed in the solution
+    pub fn hardest_technique(&self) -> Option<Technique> {
+        self.steps.iter().map(|step| step.technique).max()
+    }
+
+    /// Finds the first step after which the cell can only have one of the `possible` values.
+    /// Used to check if a unit clause learned by the solver corresponds to a human technique.
+    pub fn explaining_step(&self, row: i32, col: i32, possible: &HashSet<i32>) -> UnitExplanation {
+        let (row_index, col_index) = (row as usize - 1, col as usize - 1);
+        let mut candidates = self.initial_candidates[row_index][col_index].clone();
+        if candidates.iter().all(|value| possible.contains(value)) {
+            return UnitExplanation::Clues;
+        }
+
+        for (index, step) in self.steps.iter().enumerate() {
+            if let Some((step_row, step_col, value)) = step.placement {
+                if (step_row, step_col) == (row, col) {
+                    candidates = BTreeSet::from([value]);
+                }
+            }
+            for &(step_row, step_col, value) in &step.eliminations {
+                if (step_row, step_col) == (row, col) {
+                    candidates.remove(&value);
+                }
+            }
+            if candidates.iter().all(|value| possible.contains(value)) {
+                return UnitExplanation::Step(index);
+            }
+        }
+
+        UnitExplanation::Unexplained
+    }
+}
+
+/// Solves the sudoku as far as the techniques allow. Easier techniques are always tried first.
+pub fn solve_human(clues: &[Vec<Option<i32>>]) -> HumanSolution {
+    let mut board = Board::new(clues);
+    let initial_candidates = board.candidates.clone();
+    let mut steps = Vec::new();
+
+    while let Some(mut step) = board.find_step() {
+        board.apply(&mut step);
+        steps.push(step);
+    }
+
+    HumanSolution {
+        steps,
+        grid: board.grid,
+        initial_candidates,
+    }
+}
+
+/// Grid and the remaining candidates of each cell, indexed from 0.
+/// Candidates of a cell with a value contain only that value.
+struct Board {
+    grid: Vec<Vec<Option<i32>>>,
+    candidates: Vec<Vec<BTreeSet<i32>>>,
+}
+
+impl Board {
+    fn new(clues: &[Vec<Option<i32>>]) -> Self {
+        let mut board = Self {
+            grid: vec![vec![None; 9]; 9],
+            candidates: vec![vec![(1..=9).collect(); 9]; 9],
+        };
+        for (row, line) in clues.iter().enumerate() {
+            for (col, value) in line.iter().enumerate() {
+                if let Some(value) = value {
+                    board.place(row as i32 + 1, col as i32 + 1, *value);
+                }
+            }
+        }
+        board
+    }
+
+    fn value(&self, (row, col): (i32, i32)) -> Option<i32> {
+        self.grid[row as usize - 1][col as usize - 1]
+    }
+
+    fn candidates(&self, (row, col): (i32, i32)) -> &BTreeSet<i32> {
+        &self.candidates[row as usize - 1][col as usize - 1]
+    }
+
+    /// Empty cells of the unit that can have the value
+    fn positions(&self, unit: Unit, value: i32) -> Vec<(i32, i32)> {
+        unit_cells(unit)
+            .into_iter()
+            .filter(|&cell| self.value(cell).is_none() && self.candidates(cell).contains(&value))
+            .collect()
+    }
+
+    /// Sets the value of a cell and removes it from the candidates of the cell's peers.
+    /// Returns the removed candidates.
+    fn place(&mut self, row: i32, col: i32, value: i32) -> Vec<(i32, i32, i32)> {
+        self.grid[row as usize - 1][col as usize - 1] = Some(value);
+        self.candidates[row as usize - 1][col as usize - 1] = BTreeSet::from([value]);
+
+        let mut eliminations = Vec::new();
+        let peers = unit_cells(Unit::Row(row))
+            .into_iter()
+            .chain(unit_cells(Unit::Column(col)))
+            .chain(unit_cells(Unit::Box(Unit::box_of(row, col))));
+        for (peer_row, peer_col) in peers {
+            if (peer_row, peer_col) == (row, col) {
+                continue;
+            }
+            let cell_candidates =
+                &mut self.candidates[peer_row as usize - 1][peer_col as usize - 1];
+            if self.grid[peer_row as usize - 1][peer_col as usize - 1].is_none()
+                && cell_candidates.remove(&value)
+            {
+                eliminations.push((peer_row, peer_col, value));
+            }
+        }
+        eliminations
+    }
+
+    /// Applies the step. The candidates removed by a placement are added to the step.
+    fn apply(&mut self, step: &mut Step) {
+        if let Some((row, col, value)) = step.placement {
+            let eliminations = self.place(row, col, value);
+            step.eliminations.extend(eliminations);
+        }
+        for &(row, col, value) in &step.eliminations {
+            if self.grid[row as usize - 1][col as usize - 1].is_none() {
+                self.candidates[row as usize - 1][col as usize - 1].remove(&value);
+            }
+        }
+    }
+
+    /// Finds the next step with the easiest technique that makes progress
+    fn find_step(&self) -> Option<Step> {
+        // A cell without candidates means the sudoku has no solution
+        let contradiction = (1..=9)
+            .flat_map(|row| (1..=9).map(move |col| (row, col)))
+            .any(|cell| self.value(cell).is_none() && self.candidates(cell).is_empty());
+        if contradiction {
+            return None;
+        }
+
+        self.naked_single()
+            .or_else(|| self.hidden_single())
+            .or_else(|| self.naked_pair())
+            .or_else(|| self.hidden_pair())
+            .or_else(|| self.pointing())
+            .or_else(|| self.box_line_reduction())
+            .or_else(|| self.x_wing())
+    }
+
+    fn naked_single(&self) -> Option<Step> {
+        for row in 1..=9 {
+            for col in 1..=9 {
+                let candidates = self.candidates((row, col));
+                if self.value((row, col)).is_none() && candidates.len() == 1 {
+                    let value = *candidates.iter().next()?;
+                    return Some(Step {
+                        technique: Technique::NakedSingle,
+                        placement: Some((row, col, value)),
+                        eliminations: Vec::new(),
+                        explanation: format!(
+                            "Cell ({}, {}) has only one candidate left: {}",
+                            row, col, value
+                        ),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn hidden_single(&self) -> Option<Step> {
+        for unit in Unit::all() {
+            for value in 1..=9 {
+                let positions = self.positions(unit, value);
+                if let [(row, col)] = positions[..] {
+                    return Some(Step {
+                        technique: Technique::HiddenSingle,
+                        placement: Some((row, col, value)),
+                        eliminations: Vec::new(),
+                        explanation: format!(
+                            "Value {} can only go to cell ({}, {}) in {}",
+                            value, row, col, unit
+                        ),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn naked_pair(&self) -> Option<Step> {
+        for unit in Unit::all() {
+            let cells = unit_cells(unit);
+            let pairs = cells
+                .iter()
+                .filter(|&&cell| self.value(cell).is_none() && self.candidates(cell).len() == 2)
+                .collect::<Vec<&(i32, i32)>>();
+
+            for (index, &&first) in pairs.iter().enumerate() {
+                for &&second in &pairs[index + 1..] {
+                    let pair = self.candidates(first);
+                    if pair != self.candidates(second) {
+                        continue;
+                    }
+                    let eliminations = self.eliminate_from(
+                        cells
+                            .iter()
+                            .filter(|&&cell| cell != first && cell != second),
+                        pair,
+                    );
+                    if !eliminations.is_empty() {
+                        return Some(Step {
+                            technique: Technique::NakedPair,
+                            placement: None,
+                            eliminations,
+                            explanation: format!(
+                                "Cells {:?} and {:?} in {} can only be {:?}, \
+                                so no other cell in the {} can have these values",
+                                first, second, unit, pair, unit
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn hidden_pair(&self) -> Option<Step> {
+        for unit in Unit::all() {
+            for first in 1..=9 {
+                let positions = self.positions(unit, first);
+                if positions.len() != 2 {
+                    continue;
+                }
+                for second in (first + 1)..=9 {
+                    if self.positions(unit, second) != positions {
+                        continue;
+                    }
+                    let pair = BTreeSet::from([first, second]);
+                    let mut eliminations = Vec::new();
+                    for &(row, col) in &positions {
+                        for &value in self.candidates((row, col)) {
+                            if !pair.contains(&value) {
+                                eliminations.push((row, col, value));
+                            }
+                        }
+                    }
+                    if !eliminations.is_empty() {
+                        return Some(Step {
+                            technique: Technique::HiddenPair,
+                            placement: None,
+                            eliminations,
+                            explanation: format!(
+                                "Values {} and {} can only go to cells {:?} and {:?} in {}, \
+                                so these cells can not have other values",
+                                first, second, positions[0], positions[1], unit
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Candidates of a box that are all on one line can be removed from the rest of the line
+    fn pointing(&self) -> Option<Step> {
+        for index in 1..=9 {
+            let unit = Unit::Box(index);
+            for value in 1..=9 {
+                let positions = self.positions(unit, value);
+                if positions.is_empty() {
+                    continue;
+                }
+                let (first_row, first_col) = positions[0];
+                let mut lines = Vec::new();
+                if positions.iter().all(|&(row, _)| row == first_row) {
+                    lines.push(Unit::Row(first_row));
+                }
+                if positions.iter().all(|&(_, col)| col == first_col) {
+                    lines.push(Unit::Column(first_col));
+                }
+                for line in lines {
+                    let eliminations = self.eliminate_from(
+                        unit_cells(line)
+                            .iter()
+                            .filter(|&&(row, col)| Unit::box_of(row, col) != index),
+                        &BTreeSet::from([value]),
+                    );
+                    if !eliminations.is_empty() {
+                        return Some(Step {
+                            technique: Technique::PointingPair,
+                            placement: None,
+                            eliminations,
+                            explanation: format!(
+                                "In {}, value {} can only be in {}, \
+                                so it can not be elsewhere in {}",
+                                unit, value, line, line
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Candidates of a line that are all in one box can be removed from the rest of the box
+    fn box_line_reduction(&self) -> Option<Step> {
+        let lines = (1..=9).map(Unit::Row).chain((1..=9).map(Unit::Column));
+        for line in lines {
+            for value in 1..=9 {
+                let positions = self.positions(line, value);
+                if positions.is_empty() {
+                    continue;
+                }
+                let box_index = Unit::box_of(positions[0].0, positions[0].1);
+                if positions
+                    .iter()
+                    .any(|&(row, col)| Unit::box_of(row, col) != box_index)
+                {
+                    continue;
+                }
+                let eliminations = self.eliminate_from(
+                    unit_cells(Unit::Box(box_index))
+                        .iter()
+                        .filter(|cell| !unit_cells(line).contains(cell)),
+                    &BTreeSet::from([value]),
+                );
+                if !eliminations.is_empty() {
+                    let unit = Unit::Box(box_index);
+                    return Some(Step {
+                        technique: Technique::BoxLineReduction,
+                        placement: None,
+                        eliminations,
+                        explanation: format!(
+                            "In {}, value {} can only be in {}, so it can not be elsewhere in {}",
+                            line, value, unit, unit
+                        ),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn x_wing(&self) -> Option<Step> {
+        for value in 1..=9 {
+            // First with rows as the base lines and columns as the cover lines, then the other way
+            for by_rows in [true, false] {
+                let base = |index| {
+                    if by_rows {
+                        Unit::Row(index)
+                    } else {
+                        Unit::Column(index)
+                    }
+                };
+                let cover = |index| {
+                    if by_rows {
+                        Unit::Column(index)
+                    } else {
+                        Unit::Row(index)
+                    }
+                };
+                // Index of the cover line of a cell
+                let cover_index = |cell: &(i32, i32)| if by_rows { cell.1 } else { cell.0 };
+
+                let lines = (1..=9)
+                    .map(|index| {
+                        let positions = self.positions(base(index), value);
+                        (
+                            index,
+                            positions.iter().map(cover_index).collect::<Vec<i32>>(),
+                        )
+                    })
+                    .filter(|(_, covers)| covers.len() == 2)
+                    .collect::<Vec<(i32, Vec<i32>)>>();
+
+                for (index, (first, covers)) in lines.iter().enumerate() {
+                    for (second, other_covers) in &lines[index + 1..] {
+                        if covers != other_covers {
+                            continue;
+                        }
+                        let base_cells = unit_cells(base(*first))
+                            .into_iter()
+                            .chain(unit_cells(base(*second)))
+                            .collect::<Vec<(i32, i32)>>();
+                        let eliminations = self.eliminate_from(
+                            unit_cells(cover(covers[0]))
+                                .iter()
+                                .chain(unit_cells(cover(covers[1])).iter())
+                                .filter(|cell| !base_cells.contains(cell)),
+                            &BTreeSet::from([value]),
+                        );
+                        if !eliminations.is_empty() {
+                            return Some(Step {
+                                technique: Technique::XWing,
+                                placement: None,
+                                eliminations,
+                                explanation: format!(
+                                    "In {} and {}, value {} can only be in {} and {}, \
+                                    so it can not be elsewhere in them",
+                                    base(*first),
+                                    base(*second),
+                                    value,
+                                    cover(covers[0]),
+                                    cover(covers[1])
+                                ),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Candidates in `values` that the given empty cells still have
+    fn eliminate_from<'a>(
+        &self,
+        cells: impl Iterator<Item = &'a (i32, i32)>,
+        values: &BTreeSet<i32>,
+    ) -> Vec<(i32, i32, i32)> {
+        let mut eliminations = Vec::new();
+        for &(row, col) in cells {
+            if self.value((row, col)).is_some() {
+                continue;
+            }
+            for &value in self.candidates((row, col)).intersection(values) {
+                eliminations.push((row, col, value));
+            }
+        }
+        eliminations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::clues_from_string;
+
+    #[test]
+    fn test_solve_with_singles() {
+        let clues = clues_from_string(
+            "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79\n"
+                .to_string(),
+            ".",
+        )
+        .unwrap();
+        let solution = solve_human(&clues);
+
+        assert!(solution.is_solved());
+        assert!(solution.hardest_technique() <= Some(Technique::HiddenSingle));
+        assert_eq!(solution.steps.len(), 51);
+        assert_eq!(
+            solution.grid[0],
+            [5, 3, 4, 6, 7, 8, 9, 1, 2].map(Some).to_vec()
+        );
+    }
+
+    #[test]
+    fn test_steps_are_explained() {
+        let clues = clues_from_string(
+            "53..7....\n\
+             6..195...\n\
+             .98....6.\n\
+             8...6...3\n\
+             4..8.3..1\n\
+             7...2...6\n\
+             .6....28.\n\
+             ...419..5\n\
+             ....8..79\n"
+                .to_string(),
+            ".",
+        )
+        .unwrap();
+        let solution = solve_human(&clues);
+
+        assert!(solution
+            .steps
+            .iter()
+            .all(|step| !step.explanation.is_empty()));
+
+        // Each placed value is deduced at the latest in its own step
+        let mut explained_by_steps = 0;
+        for (index, step) in solution.steps.iter().enumerate() {
+            let (row, col, value) = step.placement.unwrap();
+            match solution.explaining_step(row, col, &HashSet::from([value])) {
+                UnitExplanation::Clues => {}
+                UnitExplanation::Step(step_index) => {
+                    assert!(step_index <= index);
+                    explained_by_steps += 1;
+                }
+                UnitExplanation::Unexplained => panic!("Placement was not explained"),
+            }
+        }
+        assert!(explained_by_steps > 0);
+        // Cell (1, 3) can not be 5, because of the clue in the same row
+        assert_eq!(
+            solution.explaining_step(1, 3, &(1..=9).filter(|&v| v != 5).collect()),
+            UnitExplanation::Clues
+        );
+    }
+
+    #[test]
+    fn test_x_wing() {
+        // Value 1 is only in columns 1 and 9 in rows 1 and 2
+        let mut board = Board::new(&vec![vec![None; 9]; 9]);
+        for row in 1..=2 {
+            for col in 2..=8 {
+                board.candidates[row - 1][col - 1].remove(&1);
+            }
+        }
+        let step = board.x_wing().unwrap();
+        assert_eq!(step.technique, Technique::XWing);
+        assert_eq!(step.eliminations.len(), 14);
+        assert!(step
+            .eliminations
+            .iter()
+            .all(|&(row, col, value)| value == 1 && row > 2 && (col == 1 || col == 9)));
+    }
+
+    #[test]
+    fn test_naked_pair() {
+        let mut board = Board::new(&vec![vec![None; 9]; 9]);
+        board.candidates[0][0] = BTreeSet::from([1, 2]);
+        board.candidates[0][1] = BTreeSet::from([1, 2]);
+        let step = board.naked_pair().unwrap();
+        assert_eq!(step.technique, Technique::NakedPair);
+        // Row 1 is found first
+        assert!(step
+            .eliminations
+            .iter()
+            .all(|&(row, col, value)| row == 1 && col > 2 && (value == 1 || value == 2)));
+        assert_eq!(step.eliminations.len(), 14);
+    }
+
+    #[test]
+    fn test_pointing() {
+        // In box 1, value 1 can only be in row 1
+        let mut board = Board::new(&vec![vec![None; 9]; 9]);
+        for row in 2..=3 {
+            for col in 1..=3 {
+                board.candidates[row - 1][col - 1].remove(&1);
+            }
+        }
+        let step = board.pointing().unwrap();
+        assert_eq!(step.technique, Technique::PointingPair);
+        assert_eq!(
+            step.eliminations,
+            (4..=9).map(|col| (1, col, 1)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_contradiction_stops_solving() {
+        let mut clues = vec![vec![None; 9]; 9];
+        // Cell (1, 1) can not have any value
+        for value in 1..=8 {
+            clues[0][value as usize] = Some(value);
+        }
+        clues[1][0] = Some(9);
+        let solution = solve_human(&clues);
+        assert!(solution.steps.is_empty());
+        assert!(!solution.is_solved());
+    }
+}
@@ -0,0 +1,281 @@
+//! Lazy re-solve baseline for the sudoku's uniqueness clauses.
+//! Only the clues and the cell at least one rule are encoded up front. Whenever the solver finds
+//! a model, it is checked against the rows, columns and boxes, and each duplicate value is
+//! explained with the clauses of `sudoku_has_unique_values` it breaks. Those are added and the
+//! solver is run again, until the model is a valid sudoku or the clauses are unsatisfiable.
+//!
+//! The clauses are only generated between complete solver calls, so the solver never sees a
+//! uniqueness conflict during search. It works with every backend and serves as the baseline
+//! for the propagator in `propagator`, which generates the clauses during the search of cadical.
+
+use std::time::{Duration, Instant};
+
+use cadical::CadicalStats;
+
+use crate::{
+    app_state::EncodingType,
+    backend::{SolveLimits, SolverBackend},
+    cnf::decimal_encoding::{cnf_identifier, sudoku_to_cnf},
+    sudoku::SolveFailure,
+    validation::{model_values, validate_model, RuleViolation},
+};
+
+/// The encoding that is given to the solver before any clauses are generated
+pub const LAZY_ENCODING: EncodingType = EncodingType::Decimal {
+    cell_at_least_one: true,
+    cell_at_most_one: false,
+    sudoku_has_all_values: false,
+    sudoku_has_unique_values: false,
+};
+
+/// Result of a lazy solve. The generated clauses are kept when the solve fails, so that a failed
+/// solve can be compared too.
+#[derive(Clone, Debug)]
+pub struct LazySolve {
+    pub solution: Result<Vec<Vec<Option<i32>>>, SolveFailure>,
+    pub generated: Vec<Vec<i32>>, // Uniqueness clauses added between the solver calls, in order
+}
+
+/// Solves the sudoku, adding the uniqueness clauses only when a model breaks them.
+/// The limits hold for all the solver calls together, like for a single solve.
+pub fn solve_sudoku_lazily(
+    sudoku_clues: &[Vec<Option<i32>>],
+    solver: &mut (impl SolverBackend + ?Sized),
+    limits: &SolveLimits,
+) -> LazySolve {
+    for clause in sudoku_to_cnf(sudoku_clues, true, false, false, false) {
+        solver.add_clause(clause);
+    }
+
+    let start = Instant::now();
+    let initial = solver.stats();
+    let mut generated = Vec::new();
+    loop {
+        let used = solver.stats();
+        let Some(remaining) = remaining_limits(limits, &initial, &used, start.elapsed()) else {
+            return LazySolve {
+                solution: Err(SolveFailure::LimitReached),
+                generated,
+            };
+        };
+        solver.set_limits(&remaining);
+        let failure = match solver.solve() {
+            Some(true) => None,
+            Some(false) => Some(SolveFailure::Unsatisfiable),
+            None => Some(SolveFailure::LimitReached),
+        };
+        if let Some(failure) = failure {
+            return LazySolve {
+                solution: Err(failure),
+                generated,
+            };
+        }
+
+        let values = model_values(solver, &LAZY_ENCODING);
+        let explanations = explain_duplicates(&validate_model(&values));
+        if explanations.is_empty() {
+            let solution = values
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.first().copied()).collect())
+                .collect();
+            return LazySolve {
+                solution: Ok(solution),
+                generated,
+            };
+        }
+
+        for clause in explanations {
+            solver.add_clause(clause.clone());
+            generated.push(clause);
+        }
+    }
+}
+
+/// What is left of the limits after the solver calls since `initial`, None if a limit is spent.
+/// The solver counts conflicts, decisions and propagations over all its calls.
+fn remaining_limits(
+    limits: &SolveLimits,
+    initial: &CadicalStats,
+    used: &CadicalStats,
+    elapsed: Duration,
+) -> Option<SolveLimits> {
+    let remaining = |limit: Option<i32>, initial: i64, used: i64| match limit {
+        Some(limit) if limit as i64 <= used - initial => None,
+        Some(limit) => Some(Some(limit - (used - initial) as i32)),
+        None => Some(None),
+    };
+    let seconds = match limits.seconds {
+        Some(seconds) if elapsed.as_secs_f64() >= seconds as f64 => return None,
+        // Started seconds count as whole seconds, as the limit is given in seconds
+        Some(seconds) => Some(seconds - elapsed.as_secs() as i32),
+        None => None,
+    };
+
+    Some(SolveLimits {
+        seconds,
+        conflicts: remaining(limits.conflicts, initial.conflicts, used.conflicts)?,
+        decisions: remaining(limits.decisions, initial.decisions, used.decisions)?,
+        propagations: remaining(limits.propagations, initial.propagations, used.propagations)?,
+    })
+}
+
+/// Clauses that forbid each pair of cells from having the same value in a unit
+pub fn explain_duplicates(violations: &[RuleViolation]) -> Vec<Vec<i32>> {
+    let mut clauses: Vec<Vec<i32>> = Vec::new();
+    for violation in violations {
+        if let RuleViolation::Duplicate { value, cells, .. } = violation {
+            for (index, &(row1, col1)) in cells.iter().enumerate() {
+                for &(row2, col2) in &cells[index + 1..] {
+                    let clause = vec![
+                        -cnf_identifier(row1, col1, *value),
+                        -cnf_identifier(row2, col2, *value),
+                    ];
+                    // A pair of cells can share both a box and a row or a column
+                    if !clauses.contains(&clause) {
+                        clauses.push(clause);
+                    }
+                }
+            }
+        }
+    }
+    clauses
+}
+
+#[cfg(test)]
+mod tests {
+    use cadical::Solver;
+
+    use crate::{
+        cadical_wrapper::CadicalCallbackWrapper,
+        cdcl::CdclSolver,
+        sudoku::{get_sudoku, solve_sudoku},
+        validation::Unit,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_explain_duplicates() {
+        let violations = vec![
+            RuleViolation::Duplicate {
+                unit: Unit::Row(1),
+                value: 5,
+                cells: vec![(1, 1), (1, 2), (1, 9)],
+            },
+            RuleViolation::Duplicate {
+                unit: Unit::Box(1),
+                value: 5,
+                cells: vec![(1, 1), (1, 2)],
+            },
+            RuleViolation::NoValue { row: 2, col: 2 },
+        ];
+
+        assert_eq!(
+            explain_duplicates(&violations),
+            vec![
+                vec![-cnf_identifier(1, 1, 5), -cnf_identifier(1, 2, 5)],
+                vec![-cnf_identifier(1, 1, 5), -cnf_identifier(1, 9, 5)],
+                vec![-cnf_identifier(1, 2, 5), -cnf_identifier(1, 9, 5)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lazy_solve() {
+        let clues = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        let mut lazy_solver: Solver<CadicalCallbackWrapper> = Solver::with_config("plain").unwrap();
+        let lazy = solve_sudoku_lazily(&clues, &mut lazy_solver, &SolveLimits::default());
+
+        let mut eager_solver: Solver<CadicalCallbackWrapper> =
+            Solver::with_config("plain").unwrap();
+        let encoding = EncodingType::Decimal {
+            cell_at_least_one: true,
+            cell_at_most_one: false,
+            sudoku_has_all_values: false,
+            sudoku_has_unique_values: true,
+        };
+        let eager = solve_sudoku(&clues, &mut eager_solver, &encoding).unwrap();
+
+        // The sample has a single solution, so both find it
+        assert_eq!(lazy.solution, Ok(eager));
+        // Only a part of the 8748 uniqueness clauses is needed
+        assert!(!lazy.generated.is_empty());
+        assert!(lazy.generated.len() < 8748);
+        assert!(validate_model(&model_values(&lazy_solver, &LAZY_ENCODING)).is_empty());
+    }
+
+    #[test]
+    fn test_limits_hold_for_all_solver_calls() {
+        // The Rust solver takes a few hundred decisions in each call, and thousands in total
+        let clues = get_sudoku("data/hardest-ever-sudoku.txt".to_string()).unwrap();
+        let limits = SolveLimits {
+            decisions: Some(1000),
+            ..Default::default()
+        };
+        let mut solver: CdclSolver<CadicalCallbackWrapper> = CdclSolver::new();
+        let lazy = solve_sudoku_lazily(&clues, &mut solver, &limits);
+
+        assert_eq!(lazy.solution, Err(SolveFailure::LimitReached));
+        assert!(lazy.generated.len() > 1);
+        assert!(solver.stats().decisions <= 1000);
+    }
+
+    #[test]
+    fn test_remaining_limits() {
+        let limits = SolveLimits {
+            seconds: Some(10),
+            conflicts: Some(100),
+            decisions: None,
+            propagations: Some(1000),
+        };
+        let initial = CadicalStats {
+            conflicts: 20,
+            propagations: 500,
+            ..Solver::<CadicalCallbackWrapper>::with_config("plain")
+                .unwrap()
+                .stats()
+        };
+        let used = CadicalStats {
+            conflicts: 60,
+            propagations: 900,
+            ..initial
+        };
+        assert_eq!(
+            remaining_limits(&limits, &initial, &used, Duration::from_millis(2500)),
+            Some(SolveLimits {
+                seconds: Some(8),
+                conflicts: Some(60),
+                decisions: None,
+                propagations: Some(600),
+            })
+        );
+
+        let spent = CadicalStats {
+            propagations: 1500,
+            ..used
+        };
+        assert_eq!(
+            remaining_limits(&limits, &initial, &spent, Duration::ZERO),
+            None
+        );
+        assert_eq!(
+            remaining_limits(&limits, &initial, &initial, Duration::from_secs(10)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_lazy_solve_failure() {
+        // Two fives in the first row are only forbidden by the generated clauses
+        let mut clues = vec![vec![None; 9]; 9];
+        clues[0][0] = Some(5);
+        clues[0][1] = Some(5);
+        let mut solver: Solver<CadicalCallbackWrapper> = Solver::with_config("plain").unwrap();
+        let lazy = solve_sudoku_lazily(&clues, &mut solver, &SolveLimits::default());
+
+        assert_eq!(lazy.solution, Err(SolveFailure::Unsatisfiable));
+        assert!(lazy
+            .generated
+            .contains(&vec![-cnf_identifier(1, 1, 5), -cnf_identifier(1, 2, 5)]));
+    }
+}
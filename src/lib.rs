@@ -1,12 +1,31 @@
+mod activity;
 mod app_state;
+mod backend;
 mod cadical_wrapper;
+mod cdcl;
 mod cnf;
 mod ctrl_obj;
+mod difficulty;
 mod error;
+mod events;
+mod external;
 mod filtering;
 pub mod gui;
+mod heatmap;
+mod history;
+mod human_solver;
+mod lazy;
+mod proof;
+mod propagator;
+mod replay;
+mod resolution;
+mod schema;
 mod statistics;
 mod sudoku;
+mod sudoku_format;
+mod timeline;
+mod trail_diff;
+mod validation;
 mod warning;
 
 #[cfg(test)]
@@ -20,7 +39,7 @@ use cadical::Solver;
 
 use cadical_wrapper::CadicalCallbackWrapper;
 use cnf::CnfVariable;
-use error::GenericError;
+use error::AppError;
 use gui::sudoku_cell::SudokuCell;
 use sudoku::string_from_grid;
 
@@ -71,12 +90,18 @@ impl Default for ConstraintList {
     }
 }
 
+/// Reasons resolved in the analysis of a conflict or in the minimization of the learned clause,
+/// as the pivot and the reason clause of each step
+pub type ResolutionSteps = Vec<(i32, Vec<i32>)>;
+
 /// Datastructure to hold conflict literals and trail data
 #[derive(Clone)]
 pub struct Trail {
     pub conflict_literals: Rc<RefCell<Vec<Vec<i32>>>>,
     pub trail: Rc<RefCell<Vec<Vec<i32>>>>,
     pub var_is_propagated: Rc<RefCell<Vec<Vec<bool>>>>,
+    pub restarts: Rc<RefCell<Vec<usize>>>, // Number of conflicts before each restart
+    pub resolutions: Rc<RefCell<Vec<(ResolutionSteps, ResolutionSteps)>>>, // Reasons resolved in the analysis and the minimization of each conflict
 }
 
 impl Trail {
@@ -85,6 +110,8 @@ impl Trail {
             conflict_literals: Rc::new(RefCell::new(Vec::new())),
             trail: Rc::new(RefCell::new(Vec::new())),
             var_is_propagated: Rc::new(RefCell::new(Vec::new())),
+            restarts: Rc::new(RefCell::new(Vec::new())),
+            resolutions: Rc::new(RefCell::new(Vec::new())),
         }
     }
 
@@ -116,6 +143,26 @@ impl Trail {
         self.conflict_literals.borrow_mut().clear();
         self.trail.borrow_mut().clear();
         self.var_is_propagated.borrow_mut().clear();
+        self.restarts.borrow_mut().clear();
+        self.resolutions.borrow_mut().clear();
+    }
+
+    /// Records the reasons resolved in the analysis of the latest conflict and in the
+    /// minimization of its learned clause
+    pub fn push_resolution(&mut self, steps: ResolutionSteps, minimization: ResolutionSteps) {
+        self.resolutions.borrow_mut().push((steps, minimization));
+    }
+
+    /// Reasons resolved in the analysis and the minimization of the conflict, None if they
+    /// were not recorded
+    pub fn resolution_at_index(&self, index: usize) -> Option<(ResolutionSteps, ResolutionSteps)> {
+        self.resolutions.borrow().get(index).cloned()
+    }
+
+    /// Records a restart after the conflicts pushed so far
+    pub fn push_restart(&mut self) {
+        let conflicts = self.len();
+        self.restarts.borrow_mut().push(conflicts);
     }
 
     pub fn trail_at_index(&self, index: usize) -> Vec<i32> {
@@ -130,6 +177,21 @@ impl Trail {
         self.var_is_propagated.borrow()[index].clone()
     }
 
+    /// Trail at the index split into decision levels. Each decision starts a new level, and the
+    /// literals before the first decision are on level 0. The bool tells if the literal was propagated.
+    pub fn decision_levels_at_index(&self, index: usize) -> Vec<Vec<(i32, bool)>> {
+        let mut levels = vec![Vec::new()];
+        let trail = self.trail.borrow();
+        let var_is_propagated = self.var_is_propagated.borrow();
+        for (&literal, &is_propagated) in trail[index].iter().zip(var_is_propagated[index].iter()) {
+            if !is_propagated {
+                levels.push(Vec::new());
+            }
+            levels.last_mut().unwrap().push((literal, is_propagated));
+        }
+        levels
+    }
+
     pub fn len(&self) -> usize {
         self.trail.borrow().len()
     }
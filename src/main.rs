@@ -21,7 +21,12 @@ fn main() -> Result<(), eframe::Error> {
         sudoku.push(row);
     }
 
-    let app = Box::new(SATApp::new(sudoku));
+    let mut app = Box::new(SATApp::new(sudoku));
+    // Solve limits can be given as arguments, for example `--time 10 --conflicts 5000`
+    if let Err(e) = app.set_limits_from_args(std::env::args().skip(1)) {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
 
     eframe::run_native("SAT STEP", options, Box::new(|_cc| app))
 }
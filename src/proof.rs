@@ -0,0 +1,679 @@
+//! Proofs of unsatisfiability. CaDiCaL writes the proof in the DRAT format, and the checker
+//! in this module verifies it against the CNF encoding of the sudoku. While checking, the
+//! clauses used for each lemma are recorded, which allows exporting the proof as LRAT too.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::error::AppError;
+
+/// Formats a proof can be exported in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProofFormat {
+    Drat,
+    Lrat,
+}
+
+impl ProofFormat {
+    pub fn extension(&self) -> &str {
+        match self {
+            ProofFormat::Drat => "drat",
+            ProofFormat::Lrat => "lrat",
+        }
+    }
+}
+
+/// A single line of a DRAT proof
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProofStep {
+    Add(Vec<i32>),
+    Delete(Vec<i32>),
+}
+
+impl ProofStep {
+    pub fn literals(&self) -> &[i32] {
+        match self {
+            ProofStep::Add(literals) | ProofStep::Delete(literals) => literals,
+        }
+    }
+}
+
+/// Result of checking a single proof step
+#[derive(Clone, Debug, PartialEq)]
+pub enum StepStatus {
+    /// The lemma follows by unit propagation (RUP). Contains the ids of the clauses used
+    /// in the propagation, in the order they became unit. These are the LRAT hints.
+    Rup(Vec<usize>),
+    /// The lemma is a resolution asymmetric tautology (RAT) on its first literal
+    Rat,
+    /// The lemma could not be verified
+    Failed,
+    /// A clause was deleted
+    Deleted,
+    /// The step comes after the empty clause or a failed lemma, so it was not checked
+    Skipped,
+}
+
+/// A proof step together with the result of checking it
+#[derive(Clone, Debug)]
+pub struct CheckedStep {
+    pub step: ProofStep,
+    pub status: StepStatus,
+    /// LRAT id of the added lemma, or of the deleted clause (None if it was not found)
+    pub clause_id: Option<usize>,
+}
+
+/// Checked proof of unsatisfiability
+#[derive(Clone, Debug)]
+pub struct Proof {
+    pub steps: Vec<CheckedStep>,
+    pub original_clause_count: usize,
+}
+
+impl Proof {
+    /// Read a textual DRAT proof from a file and check it against the given clauses
+    pub fn from_file(path: &Path, clauses: &[Vec<i32>]) -> Result<Self, AppError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(Self::check(clauses, parse_drat(&text)?)),
+            Err(source) => Err(AppError::Read {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    /// Check every step of the proof against the original clauses. Checking stops at the
+    /// first empty clause, as the rest of the proof is not needed after that, or at the
+    /// first lemma that fails the check.
+    pub fn check(clauses: &[Vec<i32>], steps: Vec<ProofStep>) -> Self {
+        let mut checker = Checker::new();
+        for clause in clauses {
+            checker.add_clause(clause);
+        }
+
+        let mut checked_steps = Vec::with_capacity(steps.len());
+        let mut done = false;
+        for step in steps {
+            let (status, clause_id) = if done {
+                (StepStatus::Skipped, None)
+            } else {
+                match &step {
+                    ProofStep::Add(lemma) => {
+                        let status = if let Some(hints) = checker.check_rup(lemma) {
+                            StepStatus::Rup(hints)
+                        } else if checker.check_rat(lemma) {
+                            StepStatus::Rat
+                        } else {
+                            StepStatus::Failed
+                        };
+                        done = lemma.is_empty() || status == StepStatus::Failed;
+                        (status, Some(checker.add_clause(lemma)))
+                    }
+                    ProofStep::Delete(clause) => {
+                        (StepStatus::Deleted, checker.delete_clause(clause))
+                    }
+                }
+            };
+
+            checked_steps.push(CheckedStep {
+                step,
+                status,
+                clause_id,
+            });
+        }
+
+        Self {
+            steps: checked_steps,
+            original_clause_count: clauses.len(),
+        }
+    }
+
+    /// The proof is valid if it derives the empty clause, and every lemma before it was verified
+    pub fn is_valid(&self) -> bool {
+        for checked in &self.steps {
+            match (&checked.step, &checked.status) {
+                (_, StepStatus::Failed) => return false,
+                (ProofStep::Add(lemma), _) if lemma.is_empty() => return true,
+                _ => (),
+            }
+        }
+        false
+    }
+
+    /// Number of lemmas (added clauses) in the proof
+    pub fn lemma_count(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|checked| matches!(checked.step, ProofStep::Add(_)))
+            .count()
+    }
+
+    /// Number of lemmas that were verified with either RUP or RAT
+    pub fn verified_count(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|checked| matches!(checked.status, StepStatus::Rup(_) | StepStatus::Rat))
+            .count()
+    }
+
+    /// Returns the proof as a string in the given format
+    pub fn to_format(&self, format: ProofFormat) -> Result<String, AppError> {
+        match format {
+            ProofFormat::Drat => Ok(self.to_drat()),
+            ProofFormat::Lrat => self.to_lrat(),
+        }
+    }
+
+    /// Returns the proof in the textual DRAT format
+    pub fn to_drat(&self) -> String {
+        let mut drat = String::new();
+        for checked in &self.steps {
+            if let ProofStep::Delete(_) = checked.step {
+                drat.push_str("d ");
+            }
+            for literal in checked.step.literals() {
+                drat.push_str(&format!("{} ", literal));
+            }
+            drat.push_str("0\n");
+        }
+        drat
+    }
+
+    /// Returns the proof in the textual LRAT format. The original clauses have the ids
+    /// 1 to `original_clause_count`, in the order they were given to the checker.
+    /// Only proofs that consist of verified RUP steps can be exported.
+    pub fn to_lrat(&self) -> Result<String, AppError> {
+        let mut lrat = String::new();
+        let mut last_id = self.original_clause_count;
+
+        for checked in &self.steps {
+            match (&checked.step, &checked.status, checked.clause_id) {
+                (ProofStep::Add(lemma), StepStatus::Rup(hints), Some(id)) => {
+                    lrat.push_str(&format!("{} ", id));
+                    for literal in lemma {
+                        lrat.push_str(&format!("{} ", literal));
+                    }
+                    lrat.push_str("0 ");
+                    for hint in hints {
+                        lrat.push_str(&format!("{} ", hint));
+                    }
+                    lrat.push_str("0\n");
+                    last_id = id;
+                }
+                (ProofStep::Delete(_), StepStatus::Deleted, Some(id)) => {
+                    lrat.push_str(&format!("{} d {} 0\n", last_id, id));
+                }
+                (ProofStep::Delete(_), _, None) | (_, StepStatus::Skipped, _) => (),
+                (_, StepStatus::Rat, _) => {
+                    return Err(AppError::ProofExport {
+                        reason: "LRAT export does not support RAT lemmas",
+                    })
+                }
+                _ => {
+                    return Err(AppError::ProofExport {
+                        reason: "Only verified proofs can be exported as LRAT",
+                    })
+                }
+            }
+        }
+
+        Ok(lrat)
+    }
+}
+
+/// Parse a proof in the textual DRAT format. Empty lines and comment lines starting with 'c'
+/// are skipped.
+pub fn parse_drat(text: &str) -> Result<Vec<ProofStep>, AppError> {
+    let mut steps = Vec::new();
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') {
+            continue;
+        }
+
+        let (deletion, literals) = match line.strip_prefix('d') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+
+        let mut clause = Vec::new();
+        let mut terminated = false;
+        for token in literals.split_whitespace() {
+            match token.parse::<i32>() {
+                Ok(0) if !terminated => terminated = true,
+                Ok(literal) if !terminated => clause.push(literal),
+                _ => {
+                    return Err(AppError::InvalidProof {
+                        line: line_index + 1,
+                        content: line.to_string(),
+                    })
+                }
+            }
+        }
+        if !terminated {
+            return Err(AppError::InvalidProof {
+                line: line_index + 1,
+                content: line.to_string(),
+            });
+        }
+
+        if deletion {
+            steps.push(ProofStep::Delete(clause));
+        } else {
+            steps.push(ProofStep::Add(clause));
+        }
+    }
+
+    Ok(steps)
+}
+
+/// Counter for the names of the proof files, so that solves running at the same time do not
+/// share a file
+static PROOF_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Path of a new temporary file for CaDiCaL to write a proof to
+pub fn proof_path() -> PathBuf {
+    let index = PROOF_FILES.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("sat-step-{}-{}.drat", std::process::id(), index))
+}
+
+/// What caused unit propagation to fail
+enum Conflict {
+    /// Clause with all literals false
+    Clause(usize),
+    /// Literal of the lemma, that was already true when its negation was assumed
+    Literal(i32),
+}
+
+/// Forward DRAT checker using unit propagation with two watched literals.
+/// Clause ids start from 1, to match LRAT. Id 0 is used as "no reason".
+struct Checker {
+    clauses: Vec<Vec<i32>>,
+    active: Vec<bool>,
+    watches: Vec<Vec<usize>>,
+    lookup: HashMap<Vec<i32>, Vec<usize>>,
+    units: Vec<usize>,
+    empty: Option<usize>,
+    values: Vec<i8>,
+    reasons: Vec<usize>,
+    trail: Vec<i32>,
+}
+
+impl Checker {
+    fn new() -> Self {
+        Self {
+            clauses: vec![Vec::new()],
+            active: vec![false],
+            watches: Vec::new(),
+            lookup: HashMap::new(),
+            units: Vec::new(),
+            empty: None,
+            values: Vec::new(),
+            reasons: Vec::new(),
+            trail: Vec::new(),
+        }
+    }
+
+    /// Index of a literal in the watch lists
+    fn watch_index(literal: i32) -> usize {
+        2 * literal.unsigned_abs() as usize + (literal < 0) as usize
+    }
+
+    fn ensure_variable(&mut self, literal: i32) {
+        let variable = literal.unsigned_abs() as usize;
+        if variable >= self.values.len() {
+            self.values.resize(variable + 1, 0);
+            self.reasons.resize(variable + 1, 0);
+            self.watches.resize(2 * variable + 2, Vec::new());
+        }
+    }
+
+    /// 1 if the literal is true, -1 if it is false and 0 if it is unassigned
+    fn value(&self, literal: i32) -> i8 {
+        let value = self.values[literal.unsigned_abs() as usize];
+        if literal < 0 {
+            -value
+        } else {
+            value
+        }
+    }
+
+    fn assign(&mut self, literal: i32, reason: usize) {
+        let variable = literal.unsigned_abs() as usize;
+        self.values[variable] = if literal > 0 { 1 } else { -1 };
+        self.reasons[variable] = reason;
+        self.trail.push(literal);
+    }
+
+    fn reset(&mut self) {
+        for literal in self.trail.drain(..) {
+            let variable = literal.unsigned_abs() as usize;
+            self.values[variable] = 0;
+            self.reasons[variable] = 0;
+        }
+    }
+
+    fn sorted(clause: &[i32]) -> Vec<i32> {
+        let mut key = clause.to_vec();
+        key.sort();
+        key.dedup();
+        key
+    }
+
+    /// Adds a clause and returns its id
+    fn add_clause(&mut self, clause: &[i32]) -> usize {
+        let mut literals = Vec::with_capacity(clause.len());
+        for &literal in clause {
+            self.ensure_variable(literal);
+            if !literals.contains(&literal) {
+                literals.push(literal);
+            }
+        }
+
+        let id = self.clauses.len();
+        self.lookup
+            .entry(Self::sorted(&literals))
+            .or_default()
+            .push(id);
+
+        match literals.len() {
+            0 => self.empty = Some(id),
+            1 => self.units.push(id),
+            _ => {
+                self.watches[Self::watch_index(literals[0])].push(id);
+                self.watches[Self::watch_index(literals[1])].push(id);
+            }
+        }
+
+        self.clauses.push(literals);
+        self.active.push(true);
+        id
+    }
+
+    /// Deletes a clause with the same literals, and returns its id
+    fn delete_clause(&mut self, clause: &[i32]) -> Option<usize> {
+        let id = self.lookup.get_mut(&Self::sorted(clause))?.pop()?;
+        self.active[id] = false;
+        if self.empty == Some(id) {
+            self.empty = None;
+        }
+        Some(id)
+    }
+
+    /// Propagates the assignments on the trail until a clause becomes false
+    fn propagate(&mut self) -> Option<usize> {
+        let mut head = 0;
+        while head < self.trail.len() {
+            let false_literal = -self.trail[head];
+            head += 1;
+
+            let watch_index = Self::watch_index(false_literal);
+            let mut watch_list = std::mem::take(&mut self.watches[watch_index]);
+            let mut conflict = None;
+            let mut i = 0;
+
+            while i < watch_list.len() {
+                let id = watch_list[i];
+                if !self.active[id] {
+                    watch_list.swap_remove(i);
+                    continue;
+                }
+
+                if self.clauses[id][0] == false_literal {
+                    self.clauses[id].swap(0, 1);
+                }
+                let other = self.clauses[id][0];
+                if self.value(other) == 1 {
+                    i += 1;
+                    continue;
+                }
+
+                let replacement =
+                    (2..self.clauses[id].len()).find(|&k| self.value(self.clauses[id][k]) != -1);
+                if let Some(k) = replacement {
+                    self.clauses[id].swap(1, k);
+                    let new_watch = self.clauses[id][1];
+                    self.watches[Self::watch_index(new_watch)].push(id);
+                    watch_list.swap_remove(i);
+                    continue;
+                }
+
+                if self.value(other) == -1 {
+                    conflict = Some(id);
+                    break;
+                }
+                self.assign(other, id);
+                i += 1;
+            }
+
+            self.watches[watch_index] = watch_list;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    /// Checks if the lemma is a RUP consequence of the active clauses.
+    /// Returns the ids of the clauses used to derive the conflict.
+    fn check_rup(&mut self, lemma: &[i32]) -> Option<Vec<usize>> {
+        if let Some(id) = self.empty {
+            return Some(vec![id]);
+        }
+        for &literal in lemma {
+            self.ensure_variable(literal);
+        }
+        self.reset();
+
+        let conflict = self.assign_units_and_lemma(lemma);
+        let conflict = match conflict {
+            Some(conflict) => Some(conflict),
+            None => self.propagate().map(Conflict::Clause),
+        };
+
+        let hints = conflict.map(|conflict| self.analyze(conflict));
+        self.reset();
+        hints
+    }
+
+    fn assign_units_and_lemma(&mut self, lemma: &[i32]) -> Option<Conflict> {
+        for index in 0..self.units.len() {
+            let id = self.units[index];
+            if !self.active[id] {
+                continue;
+            }
+            let literal = self.clauses[id][0];
+            match self.value(literal) {
+                0 => self.assign(literal, id),
+                -1 => return Some(Conflict::Clause(id)),
+                _ => (),
+            }
+        }
+
+        for &literal in lemma {
+            match self.value(-literal) {
+                0 => self.assign(-literal, 0),
+                -1 => return Some(Conflict::Literal(literal)),
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// Collects the reasons of the assignments that led to the conflict, in trail order
+    fn analyze(&self, conflict: Conflict) -> Vec<usize> {
+        let mut seen = vec![false; self.values.len()];
+        let mut hints = Vec::new();
+
+        let conflict_clause = match conflict {
+            Conflict::Clause(id) => {
+                for literal in &self.clauses[id] {
+                    seen[literal.unsigned_abs() as usize] = true;
+                }
+                Some(id)
+            }
+            Conflict::Literal(literal) => {
+                seen[literal.unsigned_abs() as usize] = true;
+                None
+            }
+        };
+
+        for literal in self.trail.iter().rev() {
+            let variable = literal.unsigned_abs() as usize;
+            if !seen[variable] || self.reasons[variable] == 0 {
+                continue;
+            }
+            let reason = self.reasons[variable];
+            hints.push(reason);
+            for other in &self.clauses[reason] {
+                seen[other.unsigned_abs() as usize] = true;
+            }
+        }
+
+        hints.reverse();
+        hints.extend(conflict_clause);
+        hints
+    }
+
+    /// Checks if the lemma is a RAT on its first literal
+    fn check_rat(&mut self, lemma: &[i32]) -> bool {
+        let pivot = match lemma.first() {
+            Some(&pivot) => pivot,
+            None => return false,
+        };
+
+        let candidates: Vec<usize> = (1..self.clauses.len())
+            .filter(|&id| self.active[id] && self.clauses[id].contains(&-pivot))
+            .collect();
+
+        candidates.into_iter().all(|id| {
+            let mut resolvent = lemma.to_vec();
+            resolvent.extend(self.clauses[id].iter().filter(|&&x| x != -pivot));
+            self.check_rup(&resolvent).is_some()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app_state::EncodingType,
+        sudoku::{get_sudoku, solve_sudoku},
+        CadicalCallbackWrapper, ConstraintList, Trail,
+    };
+
+    fn all_combinations() -> Vec<Vec<i32>> {
+        vec![vec![1, 2], vec![-1, 2], vec![1, -2], vec![-1, -2]]
+    }
+
+    #[test]
+    fn test_parse_drat() {
+        let text = "c comment\n1 2 0\n\nd 1 -2 0\n0\n";
+        let steps = parse_drat(text).unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                ProofStep::Add(vec![1, 2]),
+                ProofStep::Delete(vec![1, -2]),
+                ProofStep::Add(vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_drat() {
+        assert!(parse_drat("1 2\n").is_err());
+        assert!(parse_drat("1 x 0\n").is_err());
+        assert!(parse_drat("1 0 2 0\n").is_err());
+        assert!(matches!(
+            parse_drat("c comment\n1 2 0\n-1 x 0\n"),
+            Err(AppError::InvalidProof { line: 3, ref content }) if content == "-1 x 0"
+        ));
+    }
+
+    #[test]
+    fn test_check_rup_proof() {
+        let steps = vec![
+            ProofStep::Add(vec![1]),
+            ProofStep::Delete(vec![1, 2]),
+            ProofStep::Add(vec![]),
+            ProofStep::Add(vec![5]),
+        ];
+        let proof = Proof::check(&all_combinations(), steps);
+
+        assert!(proof.is_valid());
+        assert_eq!(proof.lemma_count(), 3);
+        assert_eq!(proof.verified_count(), 2);
+        assert_eq!(proof.steps[0].status, StepStatus::Rup(vec![1, 3]));
+        assert_eq!(proof.steps[1].status, StepStatus::Deleted);
+        assert_eq!(proof.steps[1].clause_id, Some(1));
+        assert_eq!(proof.steps[3].status, StepStatus::Skipped);
+    }
+
+    #[test]
+    fn test_check_invalid_proof() {
+        let steps = vec![ProofStep::Add(vec![-1]), ProofStep::Add(vec![])];
+        let proof = Proof::check(&all_combinations()[0..3], steps);
+
+        assert!(!proof.is_valid());
+        assert_eq!(proof.steps[0].status, StepStatus::Failed);
+        assert_eq!(proof.steps[1].status, StepStatus::Skipped);
+        assert!(proof.to_lrat().is_err());
+    }
+
+    #[test]
+    fn test_check_rat_lemma() {
+        // 3 does not appear negated anywhere, so it is a RAT but not a RUP
+        let steps = vec![ProofStep::Add(vec![3])];
+        let proof = Proof::check(&[vec![1, 2]], steps);
+        assert_eq!(proof.steps[0].status, StepStatus::Rat);
+    }
+
+    #[test]
+    fn test_proof_formats() {
+        let steps = vec![
+            ProofStep::Add(vec![1]),
+            ProofStep::Delete(vec![1, 2]),
+            ProofStep::Add(vec![]),
+        ];
+        let proof = Proof::check(&all_combinations(), steps);
+
+        assert_eq!(proof.to_drat(), "1 0\nd 1 2 0\n0\n");
+        assert_eq!(
+            proof.to_format(ProofFormat::Lrat).unwrap(),
+            "5 1 0 1 3 0\n5 d 1 0\n6 0 5 2 4 0\n"
+        );
+    }
+
+    #[test]
+    fn test_proof_of_unsolvable_sudoku() {
+        // A clue that does not clash with other clues directly, but makes the sudoku unsolvable
+        let mut sudoku = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        sudoku[0][0] = Some(5);
+
+        let path = std::env::temp_dir().join("sat-step-test-proof.drat");
+        let mut solver = cadical::Solver::with_config("plain").unwrap();
+        let callback_wrapper = CadicalCallbackWrapper::new(ConstraintList::new(), Trail::new());
+        solver.set_callbacks(Some(callback_wrapper.clone()));
+        solver.trace_proof(&path, false).unwrap();
+
+        let encoding = EncodingType::Decimal {
+            cell_at_least_one: true,
+            cell_at_most_one: true,
+            sudoku_has_all_values: true,
+            sudoku_has_unique_values: true,
+        };
+        assert!(solve_sudoku(&sudoku, &mut solver, &encoding).is_err());
+        solver.close_proof().unwrap();
+
+        let proof = Proof::from_file(&path, &encoding.sudoku_to_cnf(&sudoku)).unwrap();
+        assert!(proof.is_valid());
+        assert!(proof.to_lrat().is_ok());
+    }
+}
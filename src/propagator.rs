@@ -0,0 +1,317 @@
+//! Lazy clause generation for the sudoku's uniqueness rules with an external propagator.
+//! Only the clues and the cell at least one rule are encoded as clauses, as for the lazy
+//! re-solve baseline in `lazy`. The uniqueness of the values in each row, column and box is
+//! enforced by `SudokuPropagator` during the search of cadical: when a cell gets a value, it
+//! propagates that no other cell of its units has that value. The reason of such a
+//! propagation is the uniqueness clause `[-other, -cell]`, which is only given to the solver
+//! when conflict analysis asks for it. So only the part of the 8748 uniqueness clauses of
+//! `sudoku_to_cnf` that takes part in the search is ever generated.
+
+use std::sync::{Arc, Mutex};
+
+use cadical::{Callbacks, ExternalPropagator, Solver};
+
+use crate::{
+    cnf::CnfVariable,
+    lazy::LAZY_ENCODING,
+    sudoku::{read_solution, SolveFailure},
+};
+
+/// The number of variables of the decimal encoding
+const VARIABLES: i32 = 9 * 9 * 9;
+
+/// The rows, columns and boxes together with a value, of which at most one cell has the value
+const GROUPS: usize = 3 * 9 * 9;
+
+/// Clauses given to the solver by the propagator, shared with the solve that connected it
+type ClauseLog = Arc<Mutex<Vec<Vec<i32>>>>;
+
+/// Enforces that the values in each row, column and box of the sudoku are different.
+/// It follows the assignments of the decimal encoding's `CnfVariable::Decimal` variables.
+pub struct SudokuPropagator {
+    members: Vec<Vec<i32>>,      // The variables of each group
+    groups: Vec<[usize; 3]>,     // The groups of each variable, by variable
+    occupants: Vec<Option<i32>>, // The true variable of each group
+    assigned: Vec<bool>,         // Whether each variable is assigned, by variable
+    trail: Vec<i32>,             // The assigned literals in the order of assignment
+    levels: Vec<usize>,          // The length of `trail` at the start of each decision level
+    pending: Vec<(i32, i32)>,    // Propagations not yet given to the solver, with their causes
+    causes: Vec<i32>,            // The cause of the latest propagation of each variable
+    reason: Vec<i32>,            // The rest of the reason clause being given to the solver
+    clauses: Vec<Vec<i32>>,      // Clauses to add, each ending with 0 and given in reverse
+    generated: ClauseLog,
+}
+
+impl SudokuPropagator {
+    fn new(generated: ClauseLog) -> Self {
+        let mut members = vec![Vec::new(); GROUPS];
+        let mut groups = vec![[0; 3]; VARIABLES as usize + 1];
+        for variable in 1..=VARIABLES {
+            let CnfVariable::Decimal { row, col, value } =
+                CnfVariable::from_cnf(variable, &LAZY_ENCODING)
+            else {
+                unreachable!("the lazy encoding is decimal");
+            };
+            let (row, col, value) = (row as usize - 1, col as usize - 1, value as usize - 1);
+            let sudoku_box = row / 3 * 3 + col / 3;
+            let variable_groups = [
+                row * 9 + value,
+                81 + col * 9 + value,
+                162 + sudoku_box * 9 + value,
+            ];
+            for group in variable_groups {
+                members[group].push(variable);
+            }
+            groups[variable as usize] = variable_groups;
+        }
+
+        Self {
+            members,
+            groups,
+            occupants: vec![None; GROUPS],
+            assigned: vec![false; VARIABLES as usize + 1],
+            trail: Vec::new(),
+            levels: Vec::new(),
+            pending: Vec::new(),
+            causes: vec![0; VARIABLES as usize + 1],
+            reason: Vec::new(),
+            clauses: Vec::new(),
+            generated,
+        }
+    }
+
+    /// Queues the uniqueness clause of the two variables to be added to the solver
+    fn add_clause(&mut self, variable: i32, other: i32) {
+        let clause = vec![-variable, -other];
+        self.generated.lock().unwrap().push(clause.clone());
+        self.clauses.push(vec![0, -other, -variable]);
+    }
+}
+
+impl ExternalPropagator for SudokuPropagator {
+    fn notify_assignment(&mut self, lit: i32, _is_fixed: bool) {
+        self.assigned[lit.unsigned_abs() as usize] = true;
+        self.trail.push(lit);
+        if lit < 0 {
+            return;
+        }
+        for group in self.groups[lit as usize] {
+            if let Some(other) = self.occupants[group] {
+                // The same value twice in a unit, found before the propagations of the other
+                self.add_clause(lit, other);
+                continue;
+            }
+            self.occupants[group] = Some(lit);
+            let propagations = self.members[group]
+                .iter()
+                .filter(|&&variable| variable != lit)
+                .map(|&variable| (-variable, lit));
+            self.pending.extend(propagations);
+        }
+    }
+
+    fn notify_new_decision_level(&mut self) {
+        self.levels.push(self.trail.len());
+    }
+
+    fn notify_backtrack(&mut self, new_level: usize) {
+        if let Some(&len) = self.levels.get(new_level) {
+            for lit in self.trail.split_off(len) {
+                self.assigned[lit.unsigned_abs() as usize] = false;
+                if lit < 0 {
+                    continue;
+                }
+                for group in self.groups[lit as usize] {
+                    if self.occupants[group] == Some(lit) {
+                        self.occupants[group] = None;
+                    }
+                }
+            }
+        }
+        self.levels.truncate(new_level);
+        // The propagations of the causes that are still assigned are still valid
+        let assigned = &self.assigned;
+        self.pending.retain(|&(_, cause)| assigned[cause as usize]);
+    }
+
+    fn check_found_model(&mut self, model: &[i32]) -> bool {
+        let mut occupants = vec![None; GROUPS];
+        let mut accepted = true;
+        for &lit in model.iter().filter(|&&lit| lit > 0) {
+            for group in self.groups[lit as usize] {
+                match occupants[group] {
+                    Some(other) => {
+                        self.add_clause(lit, other);
+                        accepted = false;
+                    }
+                    None => occupants[group] = Some(lit),
+                }
+            }
+        }
+        accepted
+    }
+
+    fn propagate(&mut self) -> i32 {
+        // A literal that is already assigned keeps the cause it was assigned with, as the reason
+        // must only contain literals assigned before it
+        while let Some((lit, cause)) = self.pending.pop() {
+            if !self.assigned[lit.unsigned_abs() as usize] {
+                self.causes[lit.unsigned_abs() as usize] = cause;
+                return lit;
+            }
+        }
+        0
+    }
+
+    fn add_reason_clause_lit(&mut self, propagated_lit: i32) -> i32 {
+        if self.reason.is_empty() {
+            let cause = self.causes[propagated_lit.unsigned_abs() as usize];
+            let clause = vec![propagated_lit, -cause];
+            self.generated.lock().unwrap().push(clause);
+            self.reason = vec![0, -cause, propagated_lit];
+        }
+        // The clause ends with 0, which leaves the reason empty for the next one
+        self.reason.pop().unwrap()
+    }
+
+    fn has_external_clause(&mut self) -> bool {
+        !self.clauses.is_empty()
+    }
+
+    fn add_external_clause_lit(&mut self) -> i32 {
+        let clause = self.clauses.last_mut().unwrap();
+        let lit = clause.pop().unwrap();
+        if clause.is_empty() {
+            self.clauses.pop();
+        }
+        lit
+    }
+}
+
+/// Result of a solve with the propagator. The generated clauses are kept when the solve fails,
+/// so that a failed solve can be compared too.
+#[derive(Clone, Debug)]
+pub struct PropagatedSolve {
+    pub solution: Result<Vec<Vec<Option<i32>>>, SolveFailure>,
+    pub generated: Vec<Vec<i32>>, // Uniqueness clauses given to the solver by the propagator, in order
+}
+
+/// Solves the sudoku with the uniqueness rules enforced by `SudokuPropagator` instead of
+/// clauses. The solve limits of the solver hold as for any solve.
+pub fn solve_sudoku_with_propagator<C: Callbacks>(
+    sudoku_clues: &[Vec<Option<i32>>],
+    solver: &mut Solver<C>,
+) -> PropagatedSolve {
+    for clause in LAZY_ENCODING.sudoku_to_cnf(sudoku_clues) {
+        solver.add_clause(clause);
+    }
+
+    let generated = ClauseLog::default();
+    solver.connect_external_propagator(SudokuPropagator::new(generated.clone()));
+    for variable in 1..=VARIABLES {
+        solver.add_observed_var(variable);
+    }
+    let solution = match solver.solve() {
+        Some(true) => Ok(read_solution(solver, &LAZY_ENCODING)),
+        status => Err(SolveFailure::from_status(status)),
+    };
+    solver.disconnect_external_propagator();
+
+    let generated = std::mem::take(&mut *generated.lock().unwrap());
+    PropagatedSolve {
+        solution,
+        generated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app_state::EncodingType,
+        backend::{SolveLimits, SolverBackend},
+        cadical_wrapper::CadicalCallbackWrapper,
+        cnf::decimal_encoding::{cnf_identifier, sudoku_to_cnf},
+        sudoku::{get_sudoku, solve_sudoku},
+        validation::{model_values, validate_model},
+        ConstraintList, Trail,
+    };
+
+    use super::*;
+
+    fn solver(learned: &ConstraintList) -> Solver<CadicalCallbackWrapper> {
+        let mut solver = Solver::with_config("plain").unwrap();
+        solver.set_callbacks(Some(CadicalCallbackWrapper::new(
+            learned.clone(),
+            Trail::new(),
+        )));
+        solver
+    }
+
+    #[test]
+    fn test_propagated_solve() {
+        let clues = get_sudoku("data/hardest-ever-sudoku.txt".to_string()).unwrap();
+        let learned = ConstraintList::new();
+        let mut propagated_solver = solver(&learned);
+        let propagated = solve_sudoku_with_propagator(&clues, &mut propagated_solver);
+
+        let eager_learned = ConstraintList::new();
+        let mut eager_solver = solver(&eager_learned);
+        let encoding = EncodingType::Decimal {
+            cell_at_least_one: true,
+            cell_at_most_one: false,
+            sudoku_has_all_values: false,
+            sudoku_has_unique_values: true,
+        };
+        let eager = solve_sudoku(&clues, &mut eager_solver, &encoding).unwrap();
+
+        // The sudoku has a single solution, so both find it
+        assert_eq!(propagated.solution, Ok(eager));
+        assert!(validate_model(&model_values(&propagated_solver, &LAZY_ENCODING)).is_empty());
+
+        // Only a part of the uniqueness clauses of the eager encoding is generated
+        let empty = vec![vec![None; 9]; 9];
+        let uniqueness = sudoku_to_cnf(&empty, false, false, false, true);
+        assert!(!propagated.generated.is_empty());
+        assert!(propagated.generated.len() < uniqueness.len());
+        for clause in &propagated.generated {
+            let mut reversed = clause.clone();
+            reversed.reverse();
+            assert!(uniqueness.contains(clause) || uniqueness.contains(&reversed));
+        }
+
+        // The solver learns from conflicts of the propagator like from the clauses
+        let stats = propagated_solver.stats();
+        assert!(stats.conflicts > 0);
+        assert_eq!(stats.learned_clauses, learned.len() as i64);
+    }
+
+    #[test]
+    fn test_propagated_solve_failure() {
+        // Two fives in the first row are only forbidden by the propagator
+        let mut clues = vec![vec![None; 9]; 9];
+        clues[0][0] = Some(5);
+        clues[0][1] = Some(5);
+        let mut solver = solver(&ConstraintList::new());
+        let propagated = solve_sudoku_with_propagator(&clues, &mut solver);
+
+        assert_eq!(propagated.solution, Err(SolveFailure::Unsatisfiable));
+        let clause = vec![-cnf_identifier(1, 2, 5), -cnf_identifier(1, 1, 5)];
+        let mut reversed = clause.clone();
+        reversed.reverse();
+        assert!(propagated.generated.contains(&clause) || propagated.generated.contains(&reversed));
+    }
+
+    #[test]
+    fn test_propagated_solve_limits() {
+        let clues = get_sudoku("data/hardest-ever-sudoku.txt".to_string()).unwrap();
+        let mut solver = solver(&ConstraintList::new());
+        solver.set_limits(&SolveLimits {
+            conflicts: Some(1),
+            ..Default::default()
+        });
+        let propagated = solve_sudoku_with_propagator(&clues, &mut solver);
+
+        assert_eq!(propagated.solution, Err(SolveFailure::LimitReached));
+        assert!(solver.stats().conflicts <= 1);
+    }
+}
@@ -0,0 +1,177 @@
+//! Replay of a finished solve, stepping through the recorded conflicts in order
+
+use std::collections::BTreeSet;
+
+use crate::{app_state::EncodingType, cnf::CnfVariable};
+
+/// Default replay speed in conflicts per second
+pub const DEFAULT_SPEED: f32 = 5.0;
+
+/// State of the replay player
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub position: Option<usize>, // Index of the shown conflict, None if the replay has not started
+    pub playing: bool,
+    pub speed: f32, // Conflicts per second
+    elapsed: f32,   // Seconds since the last step
+}
+
+impl Replay {
+    /// Starts playing, from the beginning if the replay is at the end or not started
+    pub fn play(&mut self, conflicts: usize) -> Option<usize> {
+        if conflicts == 0 {
+            return None;
+        }
+        self.playing = true;
+        self.elapsed = 0.0;
+        match self.position {
+            Some(position) if position + 1 < conflicts => None,
+            _ => self.seek(0, conflicts),
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Stops the replay and forgets the position
+    pub fn stop(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Moves to the given conflict. Returns the new position if it changed.
+    pub fn seek(&mut self, position: usize, conflicts: usize) -> Option<usize> {
+        if conflicts == 0 {
+            return None;
+        }
+        let position = position.min(conflicts - 1);
+        if self.position == Some(position) {
+            return None;
+        }
+        self.position = Some(position);
+        Some(position)
+    }
+
+    /// Moves one conflict forward or backward. Returns the new position if it changed.
+    pub fn step(&mut self, forward: bool, conflicts: usize) -> Option<usize> {
+        let position = match (self.position, forward) {
+            (None, _) => 0,
+            (Some(position), true) => position + 1,
+            (Some(position), false) => position.saturating_sub(1),
+        };
+        self.seek(position, conflicts)
+    }
+
+    /// Advances a playing replay by `dt` seconds. The replay pauses at the last conflict.
+    /// Returns the new position if it changed.
+    pub fn advance(&mut self, dt: f32, conflicts: usize) -> Option<usize> {
+        if !self.playing || self.speed <= 0.0 {
+            return None;
+        }
+        self.elapsed += dt;
+        let steps = (self.elapsed * self.speed) as usize;
+        if steps == 0 {
+            return None;
+        }
+        self.elapsed -= steps as f32 / self.speed;
+
+        let position = self.position.map_or(0, |position| position + steps);
+        if position + 1 >= conflicts {
+            self.playing = false;
+        }
+        self.seek(position, conflicts)
+    }
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Self {
+            position: None,
+            playing: false,
+            speed: DEFAULT_SPEED,
+            elapsed: 0.0,
+        }
+    }
+}
+
+/// Cells with literals that were added to or removed from the trail between two conflicts
+pub fn changed_cells(
+    previous_trail: &[i32],
+    trail: &[i32],
+    encoding: &EncodingType,
+) -> BTreeSet<(i32, i32)> {
+    let previous = previous_trail.iter().collect::<BTreeSet<&i32>>();
+    let current = trail.iter().collect::<BTreeSet<&i32>>();
+    previous
+        .symmetric_difference(&current)
+        .flat_map(|&&literal| CnfVariable::from_cnf(literal, encoding).cells())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cnf::decimal_encoding::cnf_identifier;
+
+    use super::*;
+
+    #[test]
+    fn test_replay_controls() {
+        let mut replay = Replay::default();
+        assert_eq!(replay.play(0), None);
+        assert!(!replay.playing);
+
+        assert_eq!(replay.play(3), Some(0));
+        assert!(replay.playing);
+        assert_eq!(replay.step(true, 3), Some(1));
+        assert_eq!(replay.step(false, 3), Some(0));
+        assert_eq!(replay.step(false, 3), None);
+        assert_eq!(replay.seek(10, 3), Some(2));
+
+        replay.pause();
+        assert!(!replay.playing);
+        // Playing at the end starts from the beginning
+        assert_eq!(replay.play(3), Some(0));
+
+        replay.stop();
+        assert_eq!(replay, Replay::default());
+    }
+
+    #[test]
+    fn test_replay_advance() {
+        let mut replay = Replay {
+            speed: 2.0,
+            ..Default::default()
+        };
+        // A paused replay does not move
+        assert_eq!(replay.advance(1.0, 5), None);
+
+        replay.play(5);
+        assert_eq!(replay.advance(0.25, 5), None);
+        assert_eq!(replay.advance(0.25, 5), Some(1));
+        assert_eq!(replay.advance(1.0, 5), Some(3));
+        assert!(replay.playing);
+
+        // The replay pauses at the last conflict
+        assert_eq!(replay.advance(10.0, 5), Some(4));
+        assert!(!replay.playing);
+        assert_eq!(replay.advance(1.0, 5), None);
+    }
+
+    #[test]
+    fn test_changed_cells() {
+        let encoding = EncodingType::Decimal {
+            cell_at_least_one: true,
+            cell_at_most_one: false,
+            sudoku_has_all_values: false,
+            sudoku_has_unique_values: true,
+        };
+        let previous = [cnf_identifier(1, 1, 1), cnf_identifier(2, 2, 2)];
+        let current = [cnf_identifier(1, 1, 1), -cnf_identifier(3, 3, 3)];
+
+        assert_eq!(
+            changed_cells(&previous, &current, &encoding),
+            BTreeSet::from([(2, 2), (3, 3)])
+        );
+        assert!(changed_cells(&previous, &previous, &encoding).is_empty());
+    }
+}
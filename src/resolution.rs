@@ -0,0 +1,163 @@
+//! Resolution derivation of a learned clause, from the conflict clause to the minimized learned clause
+
+/// A single reason clause resolved with the previous resolvent
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolutionStep {
+    pub pivot: Option<i32>, // Literal of the reason the clause is resolved on, None for the conflict clause
+    pub reason: Vec<i32>,
+    pub resolvent: Vec<i32>,
+}
+
+/// Steps of conflict analysis for a learned clause
+#[derive(Clone, Debug, PartialEq)]
+pub struct Derivation {
+    pub steps: Vec<ResolutionStep>,
+    pub minimization: Vec<ResolutionStep>, // Reasons resolved to remove literals from the 1st UIP clause
+    pub minimized: Vec<i32>,               // Literals of the 1st UIP clause removed by minimization
+    pub learned: Vec<i32>,
+}
+
+impl Derivation {
+    /// Resolves the reasons reported by the solver in order, first those of conflict analysis
+    /// and then those of minimization. The resolvents keep the order in which literals were
+    /// added. The last resolvent of the analysis is the 1st UIP clause, and the last one of the
+    /// minimization has the literals of the learned clause.
+    pub fn new(
+        steps: &[(i32, Vec<i32>)],
+        minimization: &[(i32, Vec<i32>)],
+        learned: &[i32],
+    ) -> Self {
+        let mut resolvent: Vec<i32> = Vec::new();
+        let mut resolve = |pivot: Option<i32>, reason: &Vec<i32>| {
+            if let Some(pivot) = pivot {
+                resolvent.retain(|&literal| literal != -pivot);
+            }
+            for &literal in reason {
+                if Some(literal) != pivot && !resolvent.contains(&literal) {
+                    resolvent.push(literal);
+                }
+            }
+            ResolutionStep {
+                pivot,
+                reason: reason.clone(),
+                resolvent: resolvent.clone(),
+            }
+        };
+
+        let resolution_steps: Vec<ResolutionStep> = steps
+            .iter()
+            .enumerate()
+            .map(|(index, (pivot, reason))| resolve((index > 0).then_some(*pivot), reason))
+            .collect();
+        let minimization = minimization
+            .iter()
+            .map(|(pivot, reason)| resolve(Some(*pivot), reason))
+            .collect();
+
+        let minimized = resolution_steps
+            .last()
+            .map_or(&[][..], |step| step.resolvent.as_slice())
+            .iter()
+            .filter(|literal| !learned.contains(literal))
+            .copied()
+            .collect();
+        Self {
+            steps: resolution_steps,
+            minimization,
+            minimized,
+            learned: learned.to_vec(),
+        }
+    }
+
+    /// The clause derived by resolution before minimization
+    pub fn first_uip_clause(&self) -> &[i32] {
+        self.steps
+            .last()
+            .map_or(&[], |step| step.resolvent.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cadical::Solver;
+
+    use crate::{
+        app_state::EncodingType,
+        cadical_wrapper::CadicalCallbackWrapper,
+        sudoku::{get_sudoku, solve_sudoku},
+        ConstraintList, Trail,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_derivation() {
+        // Conflict (-1 v -2 v -3), where 3 was propagated by (3 v -2) and 2 by (2 v -1 v -4)
+        let steps = vec![
+            (0, vec![-1, -2, -3]),
+            (3, vec![3, -2]),
+            (2, vec![2, -1, -4]),
+        ];
+        let derivation = Derivation::new(&steps, &[], &[-1, -4]);
+
+        assert_eq!(derivation.steps.len(), 3);
+        assert_eq!(derivation.steps[0].pivot, None);
+        assert_eq!(derivation.steps[0].resolvent, vec![-1, -2, -3]);
+        assert_eq!(derivation.steps[1].pivot, Some(3));
+        assert_eq!(derivation.steps[1].resolvent, vec![-1, -2]);
+        assert_eq!(derivation.first_uip_clause(), &[-1, -4]);
+        assert!(derivation.minimized.is_empty());
+        assert!(derivation.minimization.is_empty());
+
+        // -1 is removed by minimization, since 1 was propagated by (1 v -4)
+        let minimized = Derivation::new(&steps, &[(1, vec![1, -4])], &[-4]);
+        assert_eq!(minimized.first_uip_clause(), &[-1, -4]);
+        assert_eq!(minimized.minimized, vec![-1]);
+        assert_eq!(minimized.minimization[0].pivot, Some(1));
+        assert_eq!(minimized.minimization[0].resolvent, vec![-4]);
+
+        let empty = Derivation::new(&[], &[], &[]);
+        assert!(empty.first_uip_clause().is_empty());
+    }
+
+    #[test]
+    fn test_derivations_of_solve() {
+        let constraints = ConstraintList::new();
+        let trails = Trail::new();
+        let mut solver = Solver::with_config("plain").unwrap();
+        solver.set_callbacks(Some(CadicalCallbackWrapper::new(
+            constraints.clone(),
+            trails.clone(),
+        )));
+        let clues = get_sudoku("data/hardest-ever-sudoku.txt".to_string()).unwrap();
+        let encoding = EncodingType::Decimal {
+            cell_at_least_one: true,
+            cell_at_most_one: false,
+            sudoku_has_all_values: false,
+            sudoku_has_unique_values: true,
+        };
+        solve_sudoku(&clues, &mut solver, &encoding).unwrap();
+
+        assert!(!constraints.is_empty());
+        assert_eq!(trails.resolutions.borrow().len(), constraints.len());
+        for (index, learned) in constraints.borrow().iter().enumerate() {
+            let (steps, minimization) = trails.resolution_at_index(index).unwrap();
+            let derivation = Derivation::new(&steps, &minimization, learned);
+            assert_eq!(
+                derivation.first_uip_clause().len(),
+                learned.len() + derivation.minimized.len()
+            );
+
+            // Resolving the minimization steps leaves exactly the learned clause
+            let mut resolvent = derivation
+                .minimization
+                .last()
+                .map_or(derivation.first_uip_clause(), |step| &step.resolvent)
+                .to_vec();
+            let mut learned = learned.clone();
+            resolvent.sort();
+            learned.sort();
+            assert_eq!(resolvent, learned);
+        }
+    }
+}
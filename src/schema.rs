@@ -0,0 +1,404 @@
+//! Versioned JSON documents for exchanging puzzles, encodings, solve results and learned
+//! clauses with other tools. Every document has the form
+//! `{ "version": 1, "kind": "...", "data": ... }`, where `kind` is one of `puzzle`, `encoding`,
+//! `results` and `learned_clauses`. Grids are arrays of 9 rows of 9 values, with `null` for
+//! an empty cell.
+//!
+//! The version is increased whenever a change would make older readers misread a document.
+//! Documents of other versions are rejected instead of being guessed at.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{app_state::EncodingType, cnf::CnfVariable, error::AppError, statistics::Statistics};
+
+/// Version of the documents written by this version of the app
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A document with its schema version
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    pub version: u32,
+    #[serde(flatten)]
+    pub content: Content,
+}
+
+/// What a document contains
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+pub enum Content {
+    Puzzle(Puzzle),
+    Encoding(EncodingType),
+    Results(Vec<SolveRecord>),
+    LearnedClauses(ClauseLog),
+}
+
+impl Content {
+    fn kind(&self) -> &'static str {
+        match self {
+            Content::Puzzle(_) => "puzzle",
+            Content::Encoding(_) => "encoding",
+            Content::Results(_) => "results",
+            Content::LearnedClauses(_) => "learned_clauses",
+        }
+    }
+}
+
+/// Rule set of a puzzle. Only classic sudokus can be solved by the app, but the common
+/// variants are recognized so that they are not solved with the wrong rules. Other values are
+/// rejected when the document is read, so a misspelled variant is reported as such.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    #[default]
+    Classic,
+    Killer,
+    Jigsaw,
+    Diagonal,
+    Thermo,
+    Sandwich,
+}
+
+impl Variant {
+    /// Name of the variant, as it is written in documents
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant::Classic => "classic",
+            Variant::Killer => "killer",
+            Variant::Jigsaw => "jigsaw",
+            Variant::Diagonal => "diagonal",
+            Variant::Thermo => "thermo",
+            Variant::Sandwich => "sandwich",
+        }
+    }
+}
+
+/// A sudoku and where it came from
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Puzzle {
+    #[serde(default)]
+    pub variant: Variant,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub grid: Vec<Vec<Option<i32>>>,
+}
+
+impl Puzzle {
+    pub fn new(grid: Vec<Vec<Option<i32>>>) -> Self {
+        Self {
+            variant: Variant::Classic,
+            name: None,
+            author: None,
+            source: None,
+            grid,
+        }
+    }
+
+    /// The clues of the puzzle, if it is a classic sudoku and the grid has 9 rows of 9 cells
+    /// with values from 1 to 9
+    pub fn clues(self) -> Result<Vec<Vec<Option<i32>>>, AppError> {
+        if self.variant != Variant::Classic {
+            return Err(AppError::UnsupportedVariant {
+                found: self.variant,
+            });
+        }
+        let valid = self.grid.len() == 9
+            && self.grid.iter().all(|row| {
+                row.len() == 9 && row.iter().flatten().all(|value| (1..=9).contains(value))
+            });
+        if !valid {
+            return Err(AppError::InvalidInput {
+                field: "The grid of the puzzle",
+                expected: "9 rows of 9 values from 1 to 9 or null",
+            });
+        }
+        Ok(self.grid)
+    }
+}
+
+/// Statistics of a solve as they are written in results documents. This is kept apart from
+/// `Statistics`, so that the app can change its statistics without changing the schema.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SolveRecord {
+    pub process_time: f64,
+    pub real_time: f64,
+    pub max_resident_set_size_mb: f64,
+    pub conflicts: i64,
+    pub learned_clauses: i64,
+    pub learned_literals: i64,
+    pub decisions: i64,
+    pub restarts: i64,
+    pub propagations: i64,
+    pub difficulty: Option<f64>,
+    pub lazy_clauses: Option<usize>,
+    pub reason_clauses: Option<usize>,
+    pub solver: String,
+    pub status: Option<bool>, // null if the solve reached a limit
+    pub encoding: EncodingType,
+    pub clues: Vec<Vec<Option<i32>>>,
+    pub sudoku: Vec<Vec<Option<i32>>>,
+}
+
+impl From<&Statistics> for SolveRecord {
+    fn from(stats: &Statistics) -> Self {
+        Self {
+            process_time: stats.process_time,
+            real_time: stats.real_time,
+            max_resident_set_size_mb: stats.max_resident_set_size_mb,
+            conflicts: stats.conflicts,
+            learned_clauses: stats.learned_clauses,
+            learned_literals: stats.learned_literals,
+            decisions: stats.decisions,
+            restarts: stats.restarts,
+            propagations: stats.propagations,
+            difficulty: stats.difficulty,
+            lazy_clauses: stats.lazy_clauses,
+            reason_clauses: stats.reason_clauses,
+            solver: stats.solver.clone(),
+            status: stats.status,
+            encoding: stats.encoding,
+            clues: stats.clues.clone(),
+            sudoku: stats.sudoku.clone(),
+        }
+    }
+}
+
+/// Clauses learned by the solver, with the variables of each clause decoded for readers that
+/// do not know the encoding
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClauseLog {
+    pub encoding: EncodingType,
+    pub clauses: Vec<LoggedClause>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LoggedClause {
+    pub literals: Vec<i32>,
+    pub variables: Vec<CnfVariable>,
+}
+
+impl ClauseLog {
+    pub fn new(encoding: EncodingType, clauses: &[Vec<i32>]) -> Self {
+        let clauses = clauses
+            .iter()
+            .map(|clause| LoggedClause {
+                literals: clause.clone(),
+                variables: clause
+                    .iter()
+                    .map(|&literal| CnfVariable::from_cnf(literal, &encoding))
+                    .collect(),
+            })
+            .collect();
+        Self { encoding, clauses }
+    }
+}
+
+impl Document {
+    pub fn new(content: Content) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            content,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        // Serializing can only fail for maps with non-string keys, which the schema has none of
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Reads a document, rejecting other schema versions before the content is looked at
+    pub fn from_json(json: &str) -> Result<Self, AppError> {
+        #[derive(Deserialize)]
+        struct Version {
+            version: u32,
+        }
+
+        let Version { version } = serde_json::from_str(json)?;
+        if version != SCHEMA_VERSION {
+            return Err(AppError::SchemaVersion { found: version });
+        }
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// The puzzle of the document, or an error if it contains something else
+    pub fn into_puzzle(self) -> Result<Puzzle, AppError> {
+        match self.content {
+            Content::Puzzle(puzzle) => Ok(puzzle),
+            other => Err(AppError::DocumentKind {
+                expected: "puzzle",
+                found: other.kind(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::Backend,
+        sudoku::{get_sudoku, solve_sudoku, SolveFailure},
+        CadicalCallbackWrapper, ConstraintList, Trail,
+    };
+    use cadical::Solver;
+
+    const DECIMAL: EncodingType = EncodingType::Decimal {
+        cell_at_least_one: true,
+        cell_at_most_one: false,
+        sudoku_has_all_values: true,
+        sudoku_has_unique_values: true,
+    };
+
+    fn round_trip(content: Content) {
+        let document = Document::new(content);
+        let json = document.to_json();
+        assert_eq!(Document::from_json(&json).unwrap(), document, "{}", json);
+    }
+
+    #[test]
+    fn test_puzzle_round_trip() {
+        let clues = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        let mut puzzle = Puzzle::new(clues.clone());
+        puzzle.name = Some("Sample".to_string());
+        round_trip(Content::Puzzle(puzzle.clone()));
+
+        let json = Document::new(Content::Puzzle(puzzle)).to_json();
+        let read = Document::from_json(&json).unwrap().into_puzzle().unwrap();
+        assert_eq!(read.clues().unwrap(), clues);
+    }
+
+    #[test]
+    fn test_encoding_round_trip() {
+        round_trip(Content::Encoding(DECIMAL));
+        round_trip(Content::Encoding(EncodingType::Binary));
+    }
+
+    #[test]
+    fn test_results_round_trip() {
+        let clues = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        let mut solver = Solver::with_config("plain").unwrap();
+        let callback_wrapper = CadicalCallbackWrapper::new(ConstraintList::new(), Trail::new());
+        solver.set_callbacks(Some(callback_wrapper));
+        let sudoku = solve_sudoku(&clues, &mut solver, &DECIMAL).unwrap();
+
+        let mut statistics = Statistics::from_cadical_stats(solver.stats(), DECIMAL, clues, sudoku);
+        statistics.difficulty = Some(1.5);
+        statistics.solver = Backend::Cdcl.name().to_string();
+        round_trip(Content::Results(vec![SolveRecord::from(&statistics)]));
+    }
+
+    #[test]
+    fn test_float_round_trip() {
+        // Needs all 17 significant digits to be read back as the same f64
+        let mut solver: Solver<CadicalCallbackWrapper> = Solver::with_config("plain").unwrap();
+        let mut statistics = Statistics::from_failure(
+            solver.stats(),
+            DECIMAL,
+            vec![vec![None; 9]; 9],
+            SolveFailure::LimitReached,
+        );
+        statistics.process_time = 0.38566829194149443;
+        statistics.difficulty = Some(0.38566829194149443);
+        round_trip(Content::Results(vec![SolveRecord::from(&statistics)]));
+    }
+
+    #[test]
+    fn test_learned_clauses_round_trip() {
+        let log = ClauseLog::new(EncodingType::Binary, &[vec![1, -2], vec![-400]]);
+        assert!(matches!(
+            log.clauses[0].variables[1],
+            CnfVariable::Bit { .. }
+        ));
+        assert!(matches!(
+            log.clauses[1].variables[0],
+            CnfVariable::Equality { .. }
+        ));
+        round_trip(Content::LearnedClauses(log));
+        round_trip(Content::LearnedClauses(ClauseLog::new(
+            DECIMAL,
+            &[vec![-1, 2]],
+        )));
+    }
+
+    #[test]
+    fn test_stable_format() {
+        let json = r#"{
+            "version": 1,
+            "kind": "puzzle",
+            "data": {
+                "variant": "classic",
+                "grid": [[5, null, null, null, null, null, null, null, null]]
+            }
+        }"#;
+        let puzzle = Document::from_json(json).unwrap().into_puzzle().unwrap();
+        assert_eq!(puzzle.grid[0][0], Some(5));
+        assert!(puzzle.clues().is_err());
+
+        let json = r#"{
+            "version": 1,
+            "kind": "puzzle",
+            "data": { "variant": "killer", "grid": [] }
+        }"#;
+        let puzzle = Document::from_json(json).unwrap().into_puzzle().unwrap();
+        assert_eq!(puzzle.variant, Variant::Killer);
+        assert!(matches!(
+            puzzle.clues(),
+            Err(AppError::UnsupportedVariant {
+                found: Variant::Killer
+            })
+        ));
+
+        // A misspelled variant is not mistaken for an unsupported one
+        let json = r#"{
+            "version": 1,
+            "kind": "puzzle",
+            "data": { "variant": "kiler", "grid": [] }
+        }"#;
+        match Document::from_json(json) {
+            Err(AppError::Json(error)) => {
+                assert!(error.to_string().contains("unknown variant `kiler`"))
+            }
+            other => panic!("expected a JSON error, got {:?}", other.map(|_| ())),
+        }
+
+        let json = r#"{ "version": 1, "kind": "encoding", "data": { "type": "binary" } }"#;
+        let document = Document::from_json(json).unwrap();
+        assert_eq!(document.content, Content::Encoding(EncodingType::Binary));
+        assert!(matches!(
+            document.into_puzzle(),
+            Err(AppError::DocumentKind {
+                expected: "puzzle",
+                found: "encoding"
+            })
+        ));
+
+        let json = r#"{
+            "version": 1,
+            "kind": "results",
+            "data": [{
+                "process_time": 0.5, "real_time": 0.6, "max_resident_set_size_mb": 8.0,
+                "conflicts": 1, "learned_clauses": 1, "learned_literals": 2, "decisions": 3,
+                "restarts": 0, "propagations": 100, "difficulty": null, "lazy_clauses": null,
+                "solver": "cadical", "status": null,
+                "encoding": { "type": "binary" },
+                "clues": [], "sudoku": []
+            }]
+        }"#;
+        let Content::Results(records) = Document::from_json(json).unwrap().content else {
+            panic!("expected results");
+        };
+        assert_eq!(records[0].propagations, 100);
+        assert_eq!(records[0].status, None);
+        // Results written before the propagator have no reason clauses
+        assert_eq!(records[0].reason_clauses, None);
+
+        let json = r#"{ "version": 2, "kind": "something new" }"#;
+        assert!(matches!(
+            Document::from_json(json),
+            Err(AppError::SchemaVersion { found: 2 })
+        ));
+    }
+}
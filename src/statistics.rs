@@ -1,8 +1,9 @@
 use crate::app_state::EncodingType;
-use crate::sudoku::string_from_grid;
+use crate::backend::Backend;
+use crate::sudoku::{string_from_grid, SolveFailure};
 use cadical::CadicalStats;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Statistics {
     pub process_time: f64,
     pub real_time: f64,
@@ -12,6 +13,12 @@ pub struct Statistics {
     pub learned_literals: i64,
     pub decisions: i64,
     pub restarts: i64,
+    pub propagations: i64,
+    pub difficulty: Option<f64>, // Score from `rate_difficulty`, if the sudoku has been rated
+    pub lazy_clauses: Option<usize>, // Uniqueness clauses generated by the lazy re-solve baseline, None for eager encodings
+    pub reason_clauses: Option<usize>, // Uniqueness clauses generated by the sudoku propagator, None without it
+    pub solver: String,                // Name of the solver, see `Backend::name`
+    pub status: Option<bool>,          // Result of the solve, None if it reached a solve limit
     pub encoding: EncodingType,
     pub clues: Vec<Vec<Option<i32>>>,
     pub sudoku: Vec<Vec<Option<i32>>>,
@@ -33,12 +40,40 @@ impl Statistics {
             learned_literals: stats.learned_literals,
             decisions: stats.decisions,
             restarts: stats.restarts,
+            propagations: stats.propagations,
+            difficulty: None,
+            lazy_clauses: None,
+            reason_clauses: None,
+            solver: Backend::Cadical.name().to_string(),
+            status: Some(true),
             encoding,
             clues,
             sudoku,
         }
     }
 
+    /// Statistics of a solve that found no solution. The sudoku is left empty.
+    pub fn from_failure(
+        stats: CadicalStats,
+        encoding: EncodingType,
+        clues: Vec<Vec<Option<i32>>>,
+        failure: SolveFailure,
+    ) -> Self {
+        let mut statistics =
+            Self::from_cadical_stats(stats, encoding, clues, vec![vec![None; 9]; 9]);
+        statistics.status = failure.status();
+        statistics
+    }
+
+    /// Result of the solve as a word, like the `s` line of a solver
+    pub fn result(&self) -> &'static str {
+        match self.status {
+            Some(true) => "SAT",
+            Some(false) => "UNSAT",
+            None => "UNKNOWN",
+        }
+    }
+
     pub fn csv_header() -> String {
         "process_time;\
             real_time;\
@@ -48,6 +83,12 @@ impl Statistics {
             learned_literals;\
             decisions;\
             restarts;\
+            propagations;\
+            difficulty;\
+            lazy_clauses;\
+            reason_clauses;\
+            solver;\
+            result;\
             is_binary;\
             cell_at_least_one;\
             cell_at_most_one;\
@@ -85,7 +126,7 @@ impl Statistics {
             (true, false, false, false, false)
         };
         format!(
-            "{};{};{};{};{};{};{};{};{};{};{};{};{};\"{}\";\"{}\"\n",
+            "{};{};{};{};{};{};{};{};{};{};{};{};{};{};{};{};{};{};{};\"{}\";\"{}\"\n",
             self.process_time,
             self.real_time,
             self.max_resident_set_size_mb,
@@ -94,6 +135,15 @@ impl Statistics {
             self.learned_literals,
             self.decisions,
             self.restarts,
+            self.propagations,
+            self.difficulty
+                .map_or(String::new(), |score| format!("{:.2}", score)),
+            self.lazy_clauses
+                .map_or(String::new(), |clauses| clauses.to_string()),
+            self.reason_clauses
+                .map_or(String::new(), |clauses| clauses.to_string()),
+            self.solver,
+            self.result(),
             is_binary,
             cell_at_least_one,
             cell_at_most_one,
@@ -140,6 +190,7 @@ mod tests {
         assert!(stats.real_time > 0.0);
         assert!(stats.max_resident_set_size_mb > 0.0);
         assert!(stats.decisions > 0);
+        assert!(stats.propagations > 0);
     }
 
     #[test]
@@ -164,14 +215,37 @@ mod tests {
 
         let csv = stats.csv();
         let parts = csv.split(';').collect::<Vec<&str>>();
-        assert_eq!(parts.len(), 15);
-        assert_eq!(parts[8], "false");
-        assert_eq!(parts[9], "true");
+        assert_eq!(parts.len(), 21);
+        assert_eq!(parts[9], "");
+        assert_eq!(parts[10], "");
+        assert_eq!(parts[11], "");
+        assert_eq!(parts[12], "CaDiCaL");
+        assert_eq!(parts[13], "SAT");
+        assert_eq!(parts[14], "false");
+        assert_eq!(parts[15], "true");
         assert_eq!(
-            parts[13],
+            parts[19],
             "\".......1.4.........2...........5.4.7..8...3....1.9....3..4..2...5.1........8.6...\""
         );
-        assert_eq!(parts[14], "\"693784512487512936125963874932651487568247391741398625319475268856129743274836159\"\n");
+        assert_eq!(parts[20], "\"693784512487512936125963874932651487568247391741398625319475268856129743274836159\"\n");
+    }
+
+    #[test]
+    fn test_statistics_of_failure() {
+        let clues = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        let mut solver: cadical::Solver<CadicalCallbackWrapper> =
+            cadical::Solver::with_config("plain").unwrap();
+        solver.set_limit("decisions", 1).unwrap();
+
+        let encoding = EncodingType::Binary;
+        let failure = solve_sudoku(&clues, &mut solver, &encoding).unwrap_err();
+        let stats = Statistics::from_failure(solver.stats(), encoding, clues, failure);
+
+        assert_eq!(stats.status, None);
+        let csv = stats.csv();
+        let parts = csv.split(';').collect::<Vec<&str>>();
+        assert_eq!(parts[13], "UNKNOWN");
+        assert_eq!(parts[20], format!("\"{}\"\n", ".".repeat(81)));
     }
 
     #[test]
@@ -185,6 +259,12 @@ mod tests {
             learned_literals;\
             decisions;\
             restarts;\
+            propagations;\
+            difficulty;\
+            lazy_clauses;\
+            reason_clauses;\
+            solver;\
+            result;\
             is_binary;\
             cell_at_least_one;\
             cell_at_most_one;\
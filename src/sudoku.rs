@@ -1,57 +1,202 @@
 //! Functions focused on the Sudoku puzzle itself
 
-use crate::{app_state::EncodingType, CadicalCallbackWrapper, GenericError, Solver};
-use std::{fs, path::Path};
+use crate::{
+    app_state::EncodingType, backend::SolverBackend, schema::Document, sudoku_format::parse_sudoku,
+    AppError, CadicalCallbackWrapper, Solver,
+};
+use std::{fmt, fs, path::Path};
+
+/// Why solving a sudoku gave no solution
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SolveFailure {
+    Unsatisfiable,
+    LimitReached, // The solver stopped at a solve limit before it found out
+}
+
+impl SolveFailure {
+    /// Result of the solve in the form of `Solver::status`
+    pub fn status(&self) -> Option<bool> {
+        match self {
+            SolveFailure::Unsatisfiable => Some(false),
+            SolveFailure::LimitReached => None,
+        }
+    }
+
+    pub fn from_status(status: Option<bool>) -> Self {
+        match status {
+            None => SolveFailure::LimitReached,
+            _ => SolveFailure::Unsatisfiable,
+        }
+    }
+}
+
+impl fmt::Display for SolveFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveFailure::Unsatisfiable => write!(f, "Solving sudoku failed!"),
+            SolveFailure::LimitReached => {
+                write!(f, "A solve limit was reached before the sudoku was solved")
+            }
+        }
+    }
+}
 
 pub fn solve_sudoku(
     sudoku_clues: &[Vec<Option<i32>>],
-    solver: &mut Solver<CadicalCallbackWrapper>,
+    solver: &mut (impl SolverBackend + ?Sized),
     encoding: &EncodingType,
-) -> Result<Vec<Vec<Option<i32>>>, String> {
-    let mut solved: Vec<Vec<Option<i32>>> = Vec::new();
+) -> Result<Vec<Vec<Option<i32>>>, SolveFailure> {
     let cnf_clauses = encoding.sudoku_to_cnf(sudoku_clues);
 
     for clause in cnf_clauses {
         solver.add_clause(clause);
     }
 
-    if let Some(true) = solver.solve() {
-        for row in 1..=9 {
-            let mut row_values = Vec::with_capacity(9);
-            for col in 1..=9 {
-                let value = encoding.get_cell_value(solver, row, col);
-                row_values.push(Some(value));
+    match solver.solve() {
+        Some(true) => Ok(read_solution(solver, encoding)),
+        status => Err(SolveFailure::from_status(status)),
+    }
+}
+
+/// Solves the sudoku with a solver that can be reused for the next solve.
+/// The rules are added as clauses only if `add_rules` is true, so they are added once per solver.
+/// The clues are given as assumptions, which means they do not stay in the solver,
+/// but the clauses learned from them do.
+pub fn solve_sudoku_incremental(
+    sudoku_clues: &[Vec<Option<i32>>],
+    solver: &mut (impl SolverBackend + ?Sized),
+    encoding: &EncodingType,
+    add_rules: bool,
+) -> Result<Vec<Vec<Option<i32>>>, SolveFailure> {
+    if add_rules {
+        let empty = vec![vec![None; 9]; 9];
+        for clause in encoding.sudoku_to_cnf(&empty) {
+            solver.add_clause(clause);
+        }
+    }
+
+    let mut assumptions = Vec::new();
+    for (row, line) in sudoku_clues.iter().enumerate() {
+        for (col, val) in line.iter().enumerate() {
+            if let Some(val) = val {
+                assumptions.extend(encoding.clue_literals(row as i32 + 1, col as i32 + 1, *val));
             }
-            solved.push(row_values);
         }
-        return Ok(solved);
     }
-    Err(String::from("Solving sudoku failed!"))
+
+    match solver.solve_with(assumptions) {
+        Some(true) => Ok(read_solution(solver, encoding)),
+        status => Err(SolveFailure::from_status(status)),
+    }
+}
+
+/// Reads the values of all cells from a solver that has found a solution
+pub fn read_solution(
+    solver: &(impl SolverBackend + ?Sized),
+    encoding: &EncodingType,
+) -> Vec<Vec<Option<i32>>> {
+    let mut solved: Vec<Vec<Option<i32>>> = Vec::new();
+    for row in 1..=9 {
+        let mut row_values = Vec::with_capacity(9);
+        for col in 1..=9 {
+            let value = encoding.get_cell_value(solver, row, col);
+            row_values.push(Some(value));
+        }
+        solved.push(row_values);
+    }
+    solved
 }
 
-/// Read sudoku from file
-pub fn get_sudoku(filename: String) -> Result<Vec<Vec<Option<i32>>>, GenericError> {
-    let sudoku_result = fs::read_to_string(filename);
+/// Finds a minimal set of clues that together make the sudoku unsolvable.
+/// The rules are added as clauses to a separate solver, and the clues as assumptions, so
+/// that the solver can tell which clues were used to prove unsatisfiability.
+/// Returns the (row, col) positions of the conflicting clues (1-9 indexed),
+/// or None if the sudoku can be solved.
+pub fn conflicting_clues(
+    sudoku_clues: &[Vec<Option<i32>>],
+    encoding: &EncodingType,
+) -> Option<Vec<(i32, i32)>> {
+    let mut solver: Solver<CadicalCallbackWrapper> = Solver::new();
+    let empty = vec![vec![None; 9]; 9];
+    for clause in encoding.sudoku_to_cnf(&empty) {
+        solver.add_clause(clause);
+    }
+
+    // Each clue is (row, col, literals that set the value of the cell)
+    let mut clues = Vec::new();
+    for (row, line) in sudoku_clues.iter().enumerate() {
+        for (col, val) in line.iter().enumerate() {
+            if let Some(val) = val {
+                let (row, col) = (row as i32 + 1, col as i32 + 1);
+                clues.push((row, col, encoding.clue_literals(row, col, *val)));
+            }
+        }
+    }
+
+    // Solves with the given clues, and returns the clues used in the proof, if unsolvable
+    let mut failed_clues = |clues: &[(i32, i32, Vec<i32>)]| {
+        let assumptions = clues
+            .iter()
+            .flat_map(|(_, _, literals)| literals.clone())
+            .collect::<Vec<i32>>();
+        match solver.solve_with(assumptions.into_iter()) {
+            Some(false) => Some(
+                clues
+                    .iter()
+                    .filter(|(_, _, literals)| literals.iter().any(|&lit| solver.failed(lit)))
+                    .cloned()
+                    .collect::<Vec<(i32, i32, Vec<i32>)>>(),
+            ),
+            _ => None,
+        }
+    };
+
+    let mut core = failed_clues(&clues)?;
+
+    // Try removing the clues one at a time. If the rest are still unsolvable, the clue is not
+    // needed, and the core can be shrinked to the clues used in the new proof.
+    let mut index = 0;
+    while index < core.len() {
+        let mut candidate = core.clone();
+        candidate.remove(index);
+        match failed_clues(&candidate) {
+            Some(smaller_core) => core = smaller_core,
+            None => index += 1,
+        }
+    }
+
+    Some(core.into_iter().map(|(row, col, _)| (row, col)).collect())
+}
+
+/// Read sudoku from file. Files ending in `.json` are read as puzzle documents of the JSON
+/// schema, others as text in any of the formats in `sudoku_format`.
+pub fn get_sudoku(filename: String) -> Result<Vec<Vec<Option<i32>>>, AppError> {
+    let sudoku_result = fs::read_to_string(&filename);
     match sudoku_result {
+        Ok(json) if filename.ends_with(".json") => {
+            Document::from_json(&json)?.into_puzzle()?.clues()
+        }
         Ok(sudoku) => clues_from_string(sudoku, "."),
-        Err(_) => Err(GenericError {
-            msg: "Invalid filetype!".to_string(),
+        Err(source) => Err(AppError::Read {
+            path: filename.into(),
+            source,
         }),
     }
 }
 
 /// Write sudoku to file
-pub fn write_sudoku(sudoku: String, path: &Path) -> Result<(), GenericError> {
+pub fn write_sudoku(sudoku: String, path: &Path) -> Result<(), AppError> {
     let save_result = fs::write(path.display().to_string(), sudoku);
     match save_result {
-        Err(_) => Err(GenericError {
-            msg: "Saving the file failed".to_string(),
+        Err(source) => Err(AppError::Write {
+            path: path.to_path_buf(),
+            source,
         }),
         _ => Ok(()),
     }
 }
 
-pub fn get_empty_sudoku() -> Result<Vec<Vec<Option<i32>>>, GenericError> {
+pub fn get_empty_sudoku() -> Result<Vec<Vec<Option<i32>>>, AppError> {
     let empty = ".........
         .........
         .........
@@ -66,36 +211,14 @@ pub fn get_empty_sudoku() -> Result<Vec<Vec<Option<i32>>>, GenericError> {
     clues_from_string(empty, ".")
 }
 
-/// Returns a 2D Vec from string to represent clues found in sudoku
+/// Parse a sudoku in any of the formats `sudoku_format` detects. `empty_value` marks an
+/// empty cell in addition to `.`, `0` and `_`.
 pub fn clues_from_string(
     buf: String,
     empty_value: &str,
-) -> Result<Vec<Vec<Option<i32>>>, GenericError> {
-    let mut clues: Vec<Vec<Option<i32>>> = Vec::with_capacity(9);
-    if buf.lines().collect::<Vec<&str>>().len() < 9 {
-        return Err(GenericError {
-            msg: "Invalid sudoku format!".to_owned(),
-        });
-    }
-    for line in buf.lines() {
-        let mut row_buf = Vec::with_capacity(9);
-        for val in line.split("") {
-            if val == empty_value {
-                row_buf.push(None)
-            }
-            if let Ok(val) = val.parse() {
-                row_buf.push(Some(val));
-            }
-        }
-        if row_buf.len() != 9 {
-            return Err(GenericError {
-                msg: "Invalid sudoku format!".to_owned(),
-            });
-        }
-        clues.push(row_buf);
-    }
-
-    Ok(clues)
+) -> Result<Vec<Vec<Option<i32>>>, AppError> {
+    let empty_value = empty_value.chars().next().unwrap_or('.');
+    parse_sudoku(&buf, empty_value)
 }
 
 /// Returns a properly formatted string representation of the gived sudoku grid
@@ -122,6 +245,62 @@ mod tests {
     use super::*;
     use crate::{ConstraintList, Trail};
 
+    #[test]
+    fn test_conflicting_clues() {
+        let encodings = [
+            EncodingType::Decimal {
+                cell_at_least_one: true,
+                cell_at_most_one: false,
+                sudoku_has_all_values: false,
+                sudoku_has_unique_values: true,
+            },
+            EncodingType::Binary,
+        ];
+        for encoding in encodings {
+            let mut sudoku = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+            assert_eq!(conflicting_clues(&sudoku, &encoding), None);
+
+            // The same value twice in the last row
+            sudoku[8][0] = Some(6);
+            let mut conflict = conflicting_clues(&sudoku, &encoding).unwrap();
+            conflict.sort();
+            assert_eq!(conflict, vec![(9, 1), (9, 6)]);
+        }
+    }
+
+    #[test]
+    fn test_solve_sudoku_incremental() {
+        let encoding = EncodingType::Decimal {
+            cell_at_least_one: true,
+            cell_at_most_one: false,
+            sudoku_has_all_values: false,
+            sudoku_has_unique_values: true,
+        };
+        let constraints = ConstraintList::new();
+        let callback_wrapper = CadicalCallbackWrapper::new(constraints.clone(), Trail::new());
+        let mut solver = Solver::new();
+        solver.set_callbacks(Some(callback_wrapper));
+
+        let mut sudoku = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        let solved = solve_sudoku_incremental(&sudoku, &mut solver, &encoding, true).unwrap();
+        let mut fresh_solver: Solver<CadicalCallbackWrapper> = Solver::new();
+        assert_eq!(
+            solved,
+            solve_sudoku(&sudoku, &mut fresh_solver, &encoding).unwrap()
+        );
+        let learned = constraints.len();
+
+        // An unsolvable edit does not break the solver for later solves
+        sudoku[8][0] = Some(6);
+        assert!(solve_sudoku_incremental(&sudoku, &mut solver, &encoding, false).is_err());
+        sudoku[8][0] = None;
+        assert_eq!(
+            solve_sudoku_incremental(&sudoku, &mut solver, &encoding, false).unwrap(),
+            solved
+        );
+        assert!(constraints.len() >= learned);
+    }
+
     #[test]
     fn test_get_sudoku() {
         let sudoku = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
@@ -395,7 +574,7 @@ mod tests {
         let test_file_exists: bool = Path::new("./data/foo_sudoku.txt").exists();
         let test_getting_sudoku = get_sudoku(test_file);
 
-        assert_eq!(test_file_exists, false);
+        assert!(!test_file_exists);
         assert!(test_getting_sudoku.is_err());
     }
 
@@ -403,18 +582,20 @@ mod tests {
     fn test_get_wrong_filetype() {
         let test_file: String = "./data/foo.exe".to_string();
         let file_exists: bool = Path::new("./data/foo.exe").exists();
-        let assumed_error_message = "Invalid filetype!".to_string();
         let test_result = get_sudoku(test_file);
 
-        assert_eq!(file_exists, false);
-        assert_eq!(test_result.err().unwrap().msg, assumed_error_message);
+        assert!(!file_exists);
+        assert!(matches!(
+            test_result,
+            Err(AppError::Read { path, .. }) if path == Path::new("./data/foo.exe")
+        ));
     }
 
     #[test]
     fn test_write_sudoku() {
         let test_text: String = "00000000".to_string();
         let test_path: &Path = Path::new("./data/test_sudoku.txt");
-        let written = write_sudoku(test_text, &test_path);
+        let written = write_sudoku(test_text, test_path);
         let read_to_text = fs::read_to_string(test_path).unwrap();
 
         assert!(written.is_ok());
@@ -425,7 +606,7 @@ mod tests {
     fn test_write_no_sudoku() {
         let test_text: String = "".to_string();
         let test_path: &Path = Path::new("");
-        let written = write_sudoku(test_text, &test_path);
+        let written = write_sudoku(test_text, test_path);
 
         assert!(written.is_err());
     }
@@ -435,11 +616,13 @@ mod tests {
         let test_text2: String = "".to_string();
         let test_path2: &Path = Path::new("./foo/foo.txt");
         let path_exists: bool = test_path2.exists();
-        let assumed_error_message = "Saving the file failed".to_string();
-        let test_result = write_sudoku(test_text2, &test_path2);
+        let test_result = write_sudoku(test_text2, test_path2);
 
-        assert_eq!(path_exists, false);
-        assert_eq!(test_result.err().unwrap().msg, assumed_error_message);
+        assert!(!path_exists);
+        assert!(matches!(
+            test_result,
+            Err(AppError::Write { path, .. }) if path == test_path2
+        ));
     }
 
     #[test]
@@ -506,16 +689,22 @@ mod tests {
 
     #[test]
     fn test_invalid_string_to_sudoku() {
-        // Not enough cols
+        // Not enough cols or rows
         let sudoku = "...".to_string();
         let result = clues_from_string(sudoku, ".");
-        assert!(result.is_err());
+        assert!(matches!(
+            result,
+            Err(AppError::RowLength {
+                line: 1,
+                found: 3,
+                ..
+            })
+        ));
 
         // Second type of error (not enough rows)
         let sudoku2 = ".........\n".to_string();
         let result2 = clues_from_string(sudoku2, ".");
-        println!("{result2:?}");
-        assert!(result2.is_err());
+        assert!(matches!(result2, Err(AppError::RowCount { found: 1 })));
 
         // Third type of error (not numbers)
         let sudoku3 = "tlnaoeut.\n\
@@ -528,7 +717,23 @@ mod tests {
                  .....4...\n\
                  .........\n";
         let result3 = clues_from_string(sudoku3.to_string(), ".");
-        assert!(result3.is_err());
+        assert!(matches!(
+            result3,
+            Err(AppError::InvalidCell {
+                line: 1,
+                column: 1,
+                found: 't',
+                ..
+            })
+        ));
+
+        // Fourth type of error (a row that is too short)
+        let sudoku4 = ".........\n".repeat(4) + "..3.\n" + &".........\n".repeat(4);
+        let result4 = clues_from_string(sudoku4, ".");
+        assert!(matches!(
+            result4,
+            Err(AppError::RowLength { line: 5, found: 4, ref content }) if content == "..3."
+        ));
     }
 
     #[test]
@@ -561,7 +766,7 @@ mod tests {
     #[test]
     fn test_get_empty_sudoku() {
         let sudoku = get_empty_sudoku();
-        assert_eq!(sudoku.is_ok(), true);
+        assert!(sudoku.is_ok());
         if let Ok(sudoku) = sudoku {
             assert_eq!(sudoku[0][0], None);
             assert_eq!(sudoku[8][8], None);
@@ -0,0 +1,192 @@
+//! Parsing sudokus from the text formats they are commonly shared in. The format is detected
+//! from the content, so the same parser reads all of these:
+//!
+//! - nine lines of nine cells, like the files in `data/`
+//! - the 81 cells on a single line
+//! - SadMan `.sdk` files, which may have `#` comments and `[Puzzle]` section headers
+//! - SadMan `.sdm` collections with one puzzle per line, of which the first one is read
+//! - grids drawn with `|`, `-` and `+`, or with box-drawing characters, as posted on forums
+//!
+//! Empty cells can be written as `.`, `0` or `_`. Whitespace between cells is ignored.
+
+use crate::error::AppError;
+
+/// Characters that mark an empty cell in every format
+const EMPTY_CELLS: [char; 3] = ['.', '0', '_'];
+
+/// Characters that separate the boxes of a grid drawing
+const BOX_DRAWING: &str = "|-+=*:│┃║─━═┼╋╬┌┐└┘├┤┬┴╔╗╚╝╠╣╦╩";
+
+/// Parse the first sudoku in the text. `empty_value` is accepted as an empty cell in addition
+/// to the usual ones.
+pub fn parse_sudoku(text: &str, empty_value: char) -> Result<Vec<Vec<Option<i32>>>, AppError> {
+    let mut rows: Vec<Vec<Option<i32>>> = Vec::with_capacity(9);
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if is_skipped(line) {
+            continue;
+        }
+
+        let cells = parse_cells(line, line_index + 1, empty_value)?;
+        match cells.len() {
+            9 => rows.push(cells),
+            81 if rows.is_empty() => rows.extend(cells.chunks(9).map(|row| row.to_vec())),
+            found => {
+                return Err(AppError::RowLength {
+                    line: line_index + 1,
+                    found,
+                    content: line.to_string(),
+                })
+            }
+        }
+
+        // Collections and files with several sections are read up to the first full puzzle
+        if rows.len() == 9 {
+            return Ok(rows);
+        }
+    }
+
+    Err(AppError::RowCount { found: rows.len() })
+}
+
+/// Whether the line has no cells: empty lines, comments, section headers and the lines
+/// between boxes of a grid drawing
+fn is_skipped(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty()
+        || line.starts_with('#')
+        || line.starts_with("//")
+        || (line.starts_with('[') && line.ends_with(']'))
+        || line
+            .chars()
+            .all(|c| c.is_whitespace() || BOX_DRAWING.contains(c))
+}
+
+/// The cells on a line, skipping whitespace and box separators
+fn parse_cells(
+    line: &str,
+    line_number: usize,
+    empty_value: char,
+) -> Result<Vec<Option<i32>>, AppError> {
+    let mut cells = Vec::with_capacity(9);
+    for (column_index, c) in line.chars().enumerate() {
+        if c.is_whitespace() || BOX_DRAWING.contains(c) {
+            continue;
+        }
+        if c == empty_value || EMPTY_CELLS.contains(&c) {
+            cells.push(None);
+            continue;
+        }
+        match c.to_digit(10) {
+            Some(digit) => cells.push(Some(digit as i32)),
+            None => {
+                return Err(AppError::InvalidCell {
+                    line: line_number,
+                    column: column_index + 1,
+                    found: c,
+                    content: line.to_string(),
+                })
+            }
+        }
+    }
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_ROWS: [&str; 9] = [
+        "53..7....",
+        "6..195...",
+        ".98....6.",
+        "8...6...3",
+        "4..8.3..1",
+        "7...2...6",
+        ".6....28.",
+        "...419..5",
+        "....8..79",
+    ];
+
+    fn sample() -> Vec<Vec<Option<i32>>> {
+        parse_sudoku(&SAMPLE_ROWS.join("\n"), '.').unwrap()
+    }
+
+    #[test]
+    fn test_single_line() {
+        let line = SAMPLE_ROWS.concat().replace('.', "0");
+        assert_eq!(parse_sudoku(&line, '.').unwrap(), sample());
+        assert_eq!(sample()[0][0], Some(5));
+        assert_eq!(sample()[8][8], Some(9));
+    }
+
+    #[test]
+    fn test_sdk() {
+        let sdk = format!(
+            "#Dsample\r\n#A author\r\n[Puzzle]\r\n{}\r\n",
+            SAMPLE_ROWS.join("\r\n")
+        );
+        assert_eq!(parse_sudoku(&sdk, '.').unwrap(), sample());
+    }
+
+    #[test]
+    fn test_sdm_reads_first_puzzle() {
+        let sdm = format!(
+            "{}\n{}\n",
+            SAMPLE_ROWS.concat().replace('.', "0"),
+            "1".repeat(81)
+        );
+        assert_eq!(parse_sudoku(&sdm, '.').unwrap(), sample());
+    }
+
+    #[test]
+    fn test_box_drawings() {
+        let ascii = "+-------+-------+-------+\n\
+                     | 5 3 _ | _ 7 _ | _ _ _ |\n\
+                     | 6 _ _ | 1 9 5 | _ _ _ |\n\
+                     | _ 9 8 | _ _ _ | _ 6 _ |\n\
+                     +-------+-------+-------+\n\
+                     | 8 _ _ | _ 6 _ | _ _ 3 |\n\
+                     | 4 _ _ | 8 _ 3 | _ _ 1 |\n\
+                     | 7 _ _ | _ 2 _ | _ _ 6 |\n\
+                     +-------+-------+-------+\n\
+                     | _ 6 _ | _ _ _ | 2 8 _ |\n\
+                     | _ _ _ | 4 1 9 | _ _ 5 |\n\
+                     | _ _ _ | _ 8 _ | _ 7 9 |\n\
+                     +-------+-------+-------+\n";
+        assert_eq!(parse_sudoku(ascii, '.').unwrap(), sample());
+
+        let unicode = SAMPLE_ROWS
+            .iter()
+            .map(|row| format!("│{}│{}│{}│", &row[0..3], &row[3..6], &row[6..9]))
+            .collect::<Vec<_>>()
+            .join("\n──────────────\n");
+        assert_eq!(parse_sudoku(&unicode, '.').unwrap(), sample());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            parse_sudoku("# only a comment\n", '.'),
+            Err(AppError::RowCount { found: 0 })
+        ));
+
+        let short_row = SAMPLE_ROWS.join("\n").replace("8...6...3", "8...6..3");
+        assert!(matches!(
+            parse_sudoku(&short_row, '.'),
+            Err(AppError::RowLength {
+                line: 4,
+                found: 8,
+                ..
+            })
+        ));
+
+        let invalid = SAMPLE_ROWS.join("\n").replace("4..8.3..1", "4..8.x..1");
+        assert!(matches!(
+            parse_sudoku(&invalid, '.'),
+            Err(AppError::InvalidCell { line: 5, column: 6, found: 'x', ref content })
+                if content == "4..8.x..1"
+        ));
+    }
+}
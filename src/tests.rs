@@ -46,14 +46,14 @@ fn test_constraint_list() {
 
     c_list.clear();
     assert_eq!(c_list.len(), 0);
-    assert_eq!(c_list.is_empty(), true);
+    assert!(c_list.is_empty());
 }
 
 #[test]
 fn test_trail() {
-    let conflict_literals = vec![vec![100, 101], vec![300, 301]];
-    let trail_data = vec![vec![1, 2, 3], vec![4, 5, 6]];
-    let var_propagated_data = vec![vec![false, true, false], vec![true, true, false]];
+    let conflict_literals = [vec![100, 101], vec![300, 301]];
+    let trail_data = [vec![1, 2, 3], vec![4, 5, 6]];
+    let var_propagated_data = [vec![false, true, false], vec![true, true, false]];
     let mut trail = Trail::new();
 
     trail.push(
@@ -66,17 +66,41 @@ fn test_trail() {
         trail_data[1].clone(),
         var_propagated_data[1].clone(),
     );
+    trail.push_restart();
+    trail.push_resolution(
+        vec![(0, vec![-1, -2]), (2, vec![2, -4])],
+        vec![(4, vec![4])],
+    );
     assert_eq!(trail.len(), 2);
+    assert_eq!(*trail.restarts.borrow(), vec![2]);
     assert_eq!(trail.trail_at_index(1), vec![4, 5, 6]);
     assert_eq!(trail.literals_at_index(1), vec![300, 301]);
     assert_eq!(trail.var_is_propagated_at_index(1), vec![true, true, false]);
-    assert_eq!(trail.is_empty(), false);
+    assert_eq!(
+        trail.decision_levels_at_index(0),
+        vec![vec![], vec![(1, false), (2, true)], vec![(3, false)]]
+    );
+    assert_eq!(
+        trail.decision_levels_at_index(1),
+        vec![vec![(4, true), (5, true)], vec![(6, false)]]
+    );
+    assert!(!trail.is_empty());
+    assert_eq!(
+        trail.resolution_at_index(0),
+        Some((
+            vec![(0, vec![-1, -2]), (2, vec![2, -4])],
+            vec![(4, vec![4])]
+        ))
+    );
+    assert_eq!(trail.resolution_at_index(1), None);
 
     trail.clear();
     assert_eq!(trail.len(), 0);
-    assert_eq!(trail.is_empty(), true);
+    assert!(trail.is_empty());
     assert_eq!(trail.conflict_literals.borrow().len(), 0);
     assert_eq!(trail.var_is_propagated.borrow().len(), 0);
+    assert!(trail.restarts.borrow().is_empty());
+    assert!(trail.resolutions.borrow().is_empty());
 }
 
 #[test]
@@ -0,0 +1,159 @@
+//! Timeline of the learning process, with one point for each conflict
+
+use std::fmt;
+
+use crate::{ConstraintList, Trail};
+
+/// Values that can be plotted on the timeline
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimelineSeries {
+    ClauseLength,
+    TrailLength,
+    Decisions,
+}
+
+impl TimelineSeries {
+    pub fn all() -> [TimelineSeries; 3] {
+        [
+            TimelineSeries::ClauseLength,
+            TimelineSeries::TrailLength,
+            TimelineSeries::Decisions,
+        ]
+    }
+
+    pub fn value(&self, point: &TimelinePoint) -> usize {
+        match self {
+            TimelineSeries::ClauseLength => point.clause_length,
+            TimelineSeries::TrailLength => point.trail_length,
+            TimelineSeries::Decisions => point.decisions,
+        }
+    }
+}
+
+impl fmt::Display for TimelineSeries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TimelineSeries::ClauseLength => "Learned clause length",
+            TimelineSeries::TrailLength => "Trail length",
+            TimelineSeries::Decisions => "Decisions on trail",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// State of the search at a single conflict
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimelinePoint {
+    pub clause_length: usize,
+    pub trail_length: usize,
+    pub decisions: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Timeline {
+    pub points: Vec<TimelinePoint>,
+    pub restarts: Vec<usize>, // Conflict indexes the restarts happened before
+}
+
+impl Timeline {
+    /// Builds the timeline from the learned constraints and the trails at each conflict.
+    /// The index of a point is the same as the index of the constraint learned at that conflict.
+    pub fn new(constraints: &ConstraintList, trails: &Trail) -> Self {
+        let constraints = constraints.borrow();
+        let var_is_propagated = trails.var_is_propagated.borrow();
+        let points = constraints
+            .iter()
+            .zip(var_is_propagated.iter())
+            .map(|(constraint, propagated)| TimelinePoint {
+                clause_length: constraint.len(),
+                trail_length: propagated.len(),
+                decisions: propagated
+                    .iter()
+                    .filter(|&&is_propagated| !is_propagated)
+                    .count(),
+            })
+            .collect();
+
+        Self {
+            points,
+            restarts: trails.restarts.borrow().clone(),
+        }
+    }
+
+    /// Largest value of the series, used for scaling the chart
+    pub fn max_value(&self, series: TimelineSeries) -> usize {
+        self.points
+            .iter()
+            .map(|point| series.value(point))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app_state::EncodingType,
+        cadical_wrapper::CadicalCallbackWrapper,
+        sudoku::{get_sudoku, solve_sudoku},
+        ConstraintList, Trail,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_timeline() {
+        let mut constraints = ConstraintList::new();
+        let mut trails = Trail::new();
+        constraints.push(vec![1, 2, 3]);
+        trails.push(vec![1], vec![4, 5, 6], vec![false, true, true]);
+        constraints.push(vec![7]);
+        trails.push(vec![2], vec![8, 9], vec![false, false]);
+        trails.push_restart();
+
+        let timeline = Timeline::new(&constraints, &trails);
+        assert_eq!(
+            timeline.points,
+            vec![
+                TimelinePoint {
+                    clause_length: 3,
+                    trail_length: 3,
+                    decisions: 1
+                },
+                TimelinePoint {
+                    clause_length: 1,
+                    trail_length: 2,
+                    decisions: 2
+                },
+            ]
+        );
+        assert_eq!(timeline.restarts, vec![2]);
+        assert_eq!(timeline.max_value(TimelineSeries::ClauseLength), 3);
+        assert_eq!(timeline.max_value(TimelineSeries::Decisions), 2);
+    }
+
+    #[test]
+    fn test_timeline_from_solver() {
+        let clues = get_sudoku("data/hardest-ever-sudoku.txt".to_string()).unwrap();
+        let constraints = ConstraintList::new();
+        let trails = Trail::new();
+        let mut solver = cadical::Solver::with_config("plain").unwrap();
+        solver.set_callbacks(Some(CadicalCallbackWrapper::new(
+            constraints.clone(),
+            trails.clone(),
+        )));
+        solve_sudoku(&clues, &mut solver, &EncodingType::Binary).unwrap();
+
+        let timeline = Timeline::new(&constraints, &trails);
+        assert_eq!(timeline.points.len(), constraints.len());
+        assert!(timeline
+            .points
+            .iter()
+            .all(|point| point.decisions <= point.trail_length));
+        assert_eq!(timeline.restarts.len() as i64, solver.stats().restarts);
+        assert!(timeline
+            .restarts
+            .windows(2)
+            .all(|restarts| restarts[0] <= restarts[1]));
+    }
+}
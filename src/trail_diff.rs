@@ -0,0 +1,112 @@
+//! Comparison of the trails of two conflicts, showing how the solver backjumped between them
+
+use std::collections::BTreeMap;
+
+use crate::{app_state::EncodingType, cnf::CnfVariable};
+
+/// What happened to a trail literal between two conflicts
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TrailChange {
+    Kept,      // In the common prefix that the solver did not backtrack over
+    Retracted, // Only in the earlier trail, undone by backjumping
+    New,       // Only in the later trail, assigned after backjumping
+}
+
+/// Difference between the trail of an earlier and a later conflict
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrailDiff {
+    pub kept: Vec<i32>,
+    pub retracted: Vec<i32>,
+    pub new: Vec<i32>,
+}
+
+impl TrailDiff {
+    /// The kept literals are the longest common prefix of the trails, since the solver only
+    /// undoes assignments from the end of the trail. A literal that was retracted and then
+    /// assigned again is both retracted and new.
+    pub fn new(earlier: &[i32], later: &[i32]) -> Self {
+        let prefix = earlier
+            .iter()
+            .zip(later.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        Self {
+            kept: earlier[..prefix].to_vec(),
+            retracted: earlier[prefix..].to_vec(),
+            new: later[prefix..].to_vec(),
+        }
+    }
+
+    /// Decision level the solver backjumped to, counted from the decisions in the kept prefix
+    pub fn backjump_level(&self, earlier_var_is_propagated: &[bool]) -> usize {
+        earlier_var_is_propagated
+            .iter()
+            .take(self.kept.len())
+            .filter(|&&is_propagated| !is_propagated)
+            .count()
+    }
+
+    /// Literals of both trails in order with their changes, the retracted literals before the new ones
+    pub fn literals(&self) -> Vec<(i32, TrailChange)> {
+        let kept = self.kept.iter().map(|&l| (l, TrailChange::Kept));
+        let retracted = self.retracted.iter().map(|&l| (l, TrailChange::Retracted));
+        let new = self.new.iter().map(|&l| (l, TrailChange::New));
+        kept.chain(retracted).chain(new).collect()
+    }
+
+    /// The most significant change of each cell on either trail. New literals are shown over
+    /// retracted ones, and both over kept ones.
+    pub fn cell_changes(&self, encoding: &EncodingType) -> BTreeMap<(i32, i32), TrailChange> {
+        let mut changes = BTreeMap::new();
+        for (literal, change) in self.literals() {
+            for cell in CnfVariable::from_cnf(literal, encoding).cells() {
+                let cell_change = changes.entry(cell).or_insert(change);
+                *cell_change = (*cell_change).max(change);
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cnf::decimal_encoding::cnf_identifier;
+
+    use super::*;
+
+    const ENCODING: EncodingType = EncodingType::Decimal {
+        cell_at_least_one: true,
+        cell_at_most_one: false,
+        sudoku_has_all_values: false,
+        sudoku_has_unique_values: true,
+    };
+
+    #[test]
+    fn test_trail_diff() {
+        let (a, b, c, d) = (
+            cnf_identifier(1, 1, 1),
+            cnf_identifier(2, 2, 2),
+            cnf_identifier(3, 3, 3),
+            cnf_identifier(4, 4, 4),
+        );
+        let diff = TrailDiff::new(&[a, b, c], &[a, -c, d]);
+        assert_eq!(diff.kept, vec![a]);
+        assert_eq!(diff.retracted, vec![b, c]);
+        assert_eq!(diff.new, vec![-c, d]);
+        assert_eq!(diff.backjump_level(&[false, false, true]), 1);
+        assert_eq!(diff.literals().len(), 5);
+
+        let changes = diff.cell_changes(&ENCODING);
+        assert_eq!(changes[&(1, 1)], TrailChange::Kept);
+        assert_eq!(changes[&(2, 2)], TrailChange::Retracted);
+        assert_eq!(changes[&(3, 3)], TrailChange::New);
+        assert_eq!(changes[&(4, 4)], TrailChange::New);
+
+        // A restart retracts the whole trail
+        let restarted = TrailDiff::new(&[a, b], &[c]);
+        assert!(restarted.kept.is_empty());
+        assert_eq!(restarted.backjump_level(&[false, true]), 0);
+
+        assert_eq!(TrailDiff::new(&[a, b], &[a, b]).kept, vec![a, b]);
+    }
+}
@@ -0,0 +1,262 @@
+//! Validation of the solver's model against the actual rules of sudoku.
+//! With an incomplete set of encoding rules the solver may find a model that is not a valid sudoku,
+//! so each broken rule is reported separately.
+
+use std::fmt;
+
+use crate::{app_state::EncodingType, backend::SolverBackend};
+
+/// A row, column or 3x3 box of the sudoku, all 1-9 indexed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Row(i32),
+    Column(i32),
+    Box(i32),
+}
+
+impl Unit {
+    /// All 27 units of the sudoku: rows, then columns, then boxes
+    pub fn all() -> Vec<Unit> {
+        (1..=9)
+            .map(Unit::Row)
+            .chain((1..=9).map(Unit::Column))
+            .chain((1..=9).map(Unit::Box))
+            .collect()
+    }
+
+    /// Index (1-9) of the box that contains the cell
+    pub fn box_of(row: i32, col: i32) -> i32 {
+        (row - 1) / 3 * 3 + (col - 1) / 3 + 1
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unit::Row(row) => write!(f, "row {}", row),
+            Unit::Column(col) => write!(f, "column {}", col),
+            Unit::Box(index) => write!(f, "box {}", index),
+        }
+    }
+}
+
+/// A single sudoku rule broken by the model
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuleViolation {
+    NoValue {
+        row: i32,
+        col: i32,
+    },
+    MultipleValues {
+        row: i32,
+        col: i32,
+        values: Vec<i32>,
+    },
+    InvalidValue {
+        row: i32,
+        col: i32,
+        value: i32,
+    },
+    Duplicate {
+        unit: Unit,
+        value: i32,
+        cells: Vec<(i32, i32)>,
+    },
+}
+
+impl RuleViolation {
+    /// Cells (row, col) that break the rule
+    pub fn cells(&self) -> Vec<(i32, i32)> {
+        match self {
+            RuleViolation::NoValue { row, col }
+            | RuleViolation::MultipleValues { row, col, .. }
+            | RuleViolation::InvalidValue { row, col, .. } => vec![(*row, *col)],
+            RuleViolation::Duplicate { cells, .. } => cells.clone(),
+        }
+    }
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleViolation::NoValue { row, col } => {
+                write!(f, "Cell ({}, {}) has no value", row, col)
+            }
+            RuleViolation::MultipleValues { row, col, values } => {
+                write!(
+                    f,
+                    "Cell ({}, {}) has multiple values: {:?}",
+                    row, col, values
+                )
+            }
+            RuleViolation::InvalidValue { row, col, value } => {
+                write!(f, "Cell ({}, {}) has an invalid value {}", row, col, value)
+            }
+            RuleViolation::Duplicate { unit, value, cells } => {
+                write!(
+                    f,
+                    "Value {} appears {} times in {}",
+                    value,
+                    cells.len(),
+                    unit
+                )
+            }
+        }
+    }
+}
+
+/// Reads all values the model gives to each cell, as a 9x9 grid of value lists
+pub fn model_values(
+    solver: &(impl SolverBackend + ?Sized),
+    encoding: &EncodingType,
+) -> Vec<Vec<Vec<i32>>> {
+    (1..=9)
+        .map(|row| {
+            (1..=9)
+                .map(|col| encoding.get_cell_values(solver, row, col))
+                .collect()
+        })
+        .collect()
+}
+
+/// Checks a model (all values of each cell) against the rules of sudoku
+/// and returns every rule that is broken
+pub fn validate_model(values: &[Vec<Vec<i32>>]) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+
+    for (row_index, line) in values.iter().enumerate() {
+        for (col_index, cell_values) in line.iter().enumerate() {
+            let (row, col) = (row_index as i32 + 1, col_index as i32 + 1);
+            match cell_values.as_slice() {
+                [] => violations.push(RuleViolation::NoValue { row, col }),
+                [value] if !(1..=9).contains(value) => {
+                    violations.push(RuleViolation::InvalidValue {
+                        row,
+                        col,
+                        value: *value,
+                    })
+                }
+                [_] => {}
+                _ => violations.push(RuleViolation::MultipleValues {
+                    row,
+                    col,
+                    values: cell_values.clone(),
+                }),
+            }
+        }
+    }
+
+    for unit in Unit::all() {
+        let cells = unit_cells(unit);
+        for value in 1..=9 {
+            let containing = cells
+                .iter()
+                .filter(|(row, col)| values[*row as usize - 1][*col as usize - 1].contains(&value))
+                .cloned()
+                .collect::<Vec<(i32, i32)>>();
+            if containing.len() > 1 {
+                violations.push(RuleViolation::Duplicate {
+                    unit,
+                    value,
+                    cells: containing,
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+/// All cells (row, col) of a unit
+pub fn unit_cells(unit: Unit) -> Vec<(i32, i32)> {
+    match unit {
+        Unit::Row(row) => (1..=9).map(|col| (row, col)).collect(),
+        Unit::Column(col) => (1..=9).map(|row| (row, col)).collect(),
+        Unit::Box(index) => {
+            let top = (index - 1) / 3 * 3;
+            let left = (index - 1) % 3 * 3;
+            (1..=3)
+                .flat_map(|row| (1..=3).map(move |col| (top + row, left + col)))
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cnf::decimal_encoding::cnf_identifier,
+        sudoku::{get_sudoku, solve_sudoku},
+        CadicalCallbackWrapper, Solver,
+    };
+
+    fn valid_values() -> Vec<Vec<Vec<i32>>> {
+        (0..9)
+            .map(|row| {
+                (0..9)
+                    .map(|col| vec![(row * 3 + row / 3 + col) % 9 + 1])
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_model() {
+        assert_eq!(validate_model(&valid_values()), Vec::new());
+    }
+
+    #[test]
+    fn test_broken_model() {
+        let mut values = valid_values();
+        values[0][0] = Vec::new();
+        values[0][1] = vec![2, 1];
+
+        assert_eq!(
+            validate_model(&values),
+            vec![
+                RuleViolation::NoValue { row: 1, col: 1 },
+                RuleViolation::MultipleValues {
+                    row: 1,
+                    col: 2,
+                    values: vec![2, 1]
+                },
+                RuleViolation::Duplicate {
+                    unit: Unit::Column(2),
+                    value: 1,
+                    cells: vec![(1, 2), (9, 2)]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_weak_encoding_model() {
+        // Without at most one rule, cells may get multiple values
+        let encoding = EncodingType::Decimal {
+            cell_at_least_one: true,
+            cell_at_most_one: false,
+            sudoku_has_all_values: true,
+            sudoku_has_unique_values: false,
+        };
+        let clues = get_sudoku("data/sample_sudoku.txt".to_string()).unwrap();
+        let mut solver: Solver<CadicalCallbackWrapper> = Solver::new();
+        // Give an empty cell two values, which the weak encoding allows
+        solver.add_clause([cnf_identifier(1, 3, 1)]);
+        solver.add_clause([cnf_identifier(1, 3, 2)]);
+        solve_sudoku(&clues, &mut solver, &encoding).unwrap();
+
+        let violations = validate_model(&model_values(&solver, &encoding));
+        assert!(violations.iter().any(|violation| matches!(
+            violation,
+            RuleViolation::MultipleValues { row: 1, col: 3, values }
+                if values.contains(&1) && values.contains(&2)
+        )));
+        for violation in violations {
+            assert!(matches!(
+                violation,
+                RuleViolation::MultipleValues { .. } | RuleViolation::Duplicate { .. }
+            ));
+        }
+    }
+}